@@ -0,0 +1,165 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote, quote_spanned};
+use syn::meta::ParseNestedMeta;
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::{Error, ItemFn, LitInt, LitStr, Path, Result};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Main,
+    Test,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    CurrentThread,
+    MultiThread,
+}
+
+struct Config {
+    flavor: Option<Flavor>,
+    workers: Option<LitInt>,
+    krate: Option<Path>,
+}
+
+impl Config {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("flavor") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let flavor = match lit.value().as_str() {
+                "current_thread" => Flavor::CurrentThread,
+                "multi_thread" => Flavor::MultiThread,
+                _ => {
+                    return Err(Error::new(
+                        lit.span(),
+                        "expected `current_thread` or `multi_thread`",
+                    ));
+                }
+            };
+
+            self.flavor = Some(flavor);
+            Ok(())
+        } else if meta.path.is_ident("workers") {
+            let lit: LitInt = meta.value()?.parse()?;
+            if lit.base10_parse::<usize>()? == 0 {
+                return Err(Error::new(lit.span(), "at least one worker is needed"));
+            }
+
+            self.workers = Some(lit);
+            Ok(())
+        } else if meta.path.is_ident("crate") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.krate = Some(lit.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `flavor`, `workers` or `crate`"))
+        }
+    }
+
+    // Amount of workers, `None` for the available parallelism.
+    fn workers(&self, kind: Kind) -> Result<TokenStream> {
+        let flavor = self.flavor.unwrap_or(match kind {
+            Kind::Main => Flavor::MultiThread,
+            Kind::Test => Flavor::CurrentThread,
+        });
+
+        match (flavor, &self.workers) {
+            (Flavor::CurrentThread, Some(workers)) => Err(Error::new(
+                workers.span(),
+                "`workers` can't be set with the `current_thread` flavor",
+            )),
+            (Flavor::CurrentThread, None) => Ok(quote!(::core::option::Option::Some(1))),
+            (Flavor::MultiThread, Some(workers)) => {
+                Ok(quote!(::core::option::Option::Some(#workers)))
+            }
+            (Flavor::MultiThread, None) => Ok(quote!(::core::option::Option::None)),
+        }
+    }
+}
+
+pub(crate) fn expand(args: TokenStream, item: TokenStream, kind: Kind) -> TokenStream {
+    // On errors, the function is still emitted so that it doesn't show up as missing.
+    let mut item: ItemFn = match syn::parse2(item.clone()) {
+        Ok(item) => item,
+        Err(e) => return token_stream_with_error(item, e),
+    };
+
+    match expand_fn(args, &mut item, kind) {
+        Ok(tokens) => tokens,
+        Err(e) => token_stream_with_error(item.into_token_stream(), e),
+    }
+}
+
+fn expand_fn(args: TokenStream, item: &mut ItemFn, kind: Kind) -> Result<TokenStream> {
+    let mut config = Config {
+        flavor: None,
+        workers: None,
+        krate: None,
+    };
+    syn::meta::parser(|meta| config.parse(meta)).parse2(args)?;
+
+    if item.sig.asyncness.is_none() {
+        return Err(Error::new(
+            item.sig.fn_token.span(),
+            "the `async` keyword is missing from the function declaration",
+        ));
+    }
+
+    if !item.sig.inputs.is_empty() {
+        let what = match kind {
+            Kind::Main => "the main function",
+            Kind::Test => "tests",
+        };
+
+        return Err(Error::new(
+            item.sig.inputs.span(),
+            format!("{what} can't take arguments"),
+        ));
+    }
+
+    if kind == Kind::Main && item.sig.ident != "main" {
+        return Err(Error::new(
+            item.sig.ident.span(),
+            "`#[lamp::main]` can only be used on `main`",
+        ));
+    }
+
+    if kind == Kind::Test && item.attrs.iter().any(|attr| attr.path().is_ident("test")) {
+        return Err(Error::new(
+            item.sig.ident.span(),
+            "second test attribute is supplied, remove `#[test]`",
+        ));
+    }
+
+    let workers = config.workers(kind)?;
+    let krate = match config.krate {
+        Some(path) => path.into_token_stream(),
+        None => quote!(::lamp),
+    };
+
+    item.sig.asyncness = None;
+    let body = &item.block;
+    let span = body.brace_token.span.join();
+    let block = quote_spanned! {span=>
+        {
+            #krate::macros::support::block_on(#workers, async move #body)
+        }
+    };
+    item.block = syn::parse2(block)?;
+
+    let test = match kind {
+        Kind::Main => quote!(),
+        Kind::Test => quote!(#[::core::prelude::v1::test]),
+    };
+
+    Ok(quote! {
+        #test
+        #item
+    })
+}
+
+fn token_stream_with_error(mut tokens: TokenStream, error: Error) -> TokenStream {
+    tokens.extend(error.into_compile_error());
+    tokens
+}
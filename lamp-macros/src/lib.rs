@@ -0,0 +1,45 @@
+//! Attribute macros for lamp, re-exported as `lamp::main` and `lamp::test`.
+
+mod entry;
+
+use proc_macro::TokenStream;
+
+/// Runs an `async fn main` on a lamp runtime.
+///
+/// The runtime is built before the body runs and shut down once it returns.
+/// The value returned by the body is returned from `main`, and a panic in the body
+/// is resumed after the shutdown.
+///
+/// # Arguments
+///
+/// - `flavor = "multi_thread"` (default) or `flavor = "current_thread"`, which uses a
+///   single worker.
+/// - `workers = N`, the amount of worker threads, defaulting to the available parallelism.
+/// - `crate = "path"`, the path to the lamp crate if it was renamed.
+///
+/// ```ignore
+/// #[lamp::main(workers = 2)]
+/// async fn main() -> std::io::Result<()> {
+///     Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    entry::expand(args.into(), item.into(), entry::Kind::Main).into()
+}
+
+/// Runs an `async fn` test on its own lamp runtime.
+///
+/// Takes the same arguments as `#[lamp::main]`, but defaults to
+/// `flavor = "current_thread"`.
+///
+/// ```ignore
+/// #[lamp::test]
+/// async fn sends() {
+///     assert!(true);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    entry::expand(args.into(), item.into(), entry::Kind::Test).into()
+}
@@ -0,0 +1,39 @@
+use super::{Decoder, Encoder};
+
+use std::io;
+use std::mem;
+
+/// Codec passing raw bytes through unchanged.
+///
+/// Each decoded frame holds whatever was available in the read buffer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BytesCodec(());
+
+impl BytesCodec {
+    /// Creates a new `BytesCodec`.
+    pub fn new() -> BytesCodec {
+        BytesCodec(())
+    }
+}
+
+impl Decoder for BytesCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(mem::take(src)))
+    }
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for BytesCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, data: T, dst: &mut Vec<u8>) -> io::Result<()> {
+        dst.extend_from_slice(data.as_ref());
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use std::io;
+
+/// Decodes frames from a buffer of bytes.
+///
+/// Used by `FramedRead` and `Framed` to turn a byte stream into a stream of frames.
+pub trait Decoder {
+    /// The type of the decoded frames.
+    type Item;
+
+    /// The type of decoding errors.
+    ///
+    /// I/O errors of the underlying reader are converted into it.
+    type Error: From<io::Error>;
+
+    /// Attempts to decode a frame from `src`.
+    ///
+    /// The bytes belonging to a returned frame must be removed from `src`.
+    /// `Ok(None)` means more data is needed, the buffer is kept as is
+    /// and this is called again once more bytes arrive.
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Called when the reader reached EOF.
+    ///
+    /// Defaults to calling `decode` and failing if it leaves bytes behind.
+    fn decode_eof(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            None => Err(
+                io::Error::new(io::ErrorKind::UnexpectedEof, "bytes remaining on stream").into(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use std::io;
+
+/// Encodes frames into a buffer of bytes.
+///
+/// Used by `FramedWrite` and `Framed` to turn a sink of frames into a byte stream.
+pub trait Encoder<Item> {
+    /// The type of encoding errors.
+    ///
+    /// I/O errors of the underlying writer are converted into it.
+    type Error: From<io::Error>;
+
+    /// Encodes `item` by appending it to `dst`.
+    fn encode(&mut self, item: Item, dst: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,111 @@
+use super::framed_impl::{
+    ReadFrame, WriteFrame, poll_flush_frames, poll_next_frame, poll_ready_frames,
+};
+use super::{Decoder, Encoder};
+use crate::io::{AsyncRead, AsyncWrite};
+
+use futures::{Sink, Stream};
+use pin_project_lite::pin_project;
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Transport of frames over a single I/O object.
+    ///
+    /// Decoded frames are read as a `Stream`
+    /// and frames to encode are written through a `Sink`.
+    /// Use `StreamExt::split` to handle both halves separately.
+    pub struct Framed<T, U> {
+        #[pin]
+        inner: T,
+        codec: U,
+        read: ReadFrame,
+        write: WriteFrame,
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite, U> Framed<T, U> {
+    /// Creates a new `Framed` transport over `inner` using `codec`.
+    pub fn new(inner: T, codec: U) -> Framed<T, U> {
+        Framed {
+            inner,
+            codec,
+            read: ReadFrame::new(),
+            write: WriteFrame::new(),
+        }
+    }
+}
+
+impl<T, U> Framed<T, U> {
+    /// Obtains a reference to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Obtains a mutable reference to the inner I/O object.
+    ///
+    /// Using it directly may corrupt the frame streams.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Obtains a reference to the codec.
+    pub fn codec(&self) -> &U {
+        &self.codec
+    }
+
+    /// Obtains a mutable reference to the codec.
+    pub fn codec_mut(&mut self) -> &mut U {
+        &mut self.codec
+    }
+
+    /// Returns the bytes read but not yet decoded.
+    pub fn read_buffer(&self) -> &[u8] {
+        &self.read.buffer
+    }
+
+    /// Returns the bytes encoded but not yet written.
+    pub fn write_buffer(&self) -> &[u8] {
+        &self.write.buffer
+    }
+
+    /// Consumes the `Framed`, returning the inner I/O object.
+    ///
+    /// Buffered bytes are lost.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsyncRead, U: Decoder> Stream for Framed<T, U> {
+    type Item = Result<U::Item, U::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let pinned = self.project();
+        poll_next_frame(pinned.inner, pinned.codec, pinned.read, cx)
+    }
+}
+
+impl<T: AsyncWrite, U: Encoder<I>, I> Sink<I> for Framed<T, U> {
+    type Error = U::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let pinned = self.project();
+        poll_ready_frames(pinned.inner, pinned.write, cx).map_err(Into::into)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: I) -> Result<(), Self::Error> {
+        let pinned = self.project();
+        pinned.codec.encode(item, &mut pinned.write.buffer)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let pinned = self.project();
+        poll_flush_frames(pinned.inner, pinned.write, cx).map_err(Into::into)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
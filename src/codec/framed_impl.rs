@@ -0,0 +1,180 @@
+use super::Decoder;
+use crate::io::{AsyncRead, AsyncWrite};
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Initial capacity of the frame buffers.
+const INITIAL_CAPACITY: usize = 8 * 1024;
+
+/// Amount of bytes read from the reader at once.
+const READ_CHUNK: usize = 4 * 1024;
+
+/// Size of the write buffer after which a flush is forced.
+pub(crate) const BACKPRESSURE_BOUNDARY: usize = INITIAL_CAPACITY;
+
+/// Read half state of the framed transports.
+pub(crate) struct ReadFrame {
+    pub(crate) buffer: Vec<u8>,
+    eof: bool,
+    readable: bool,
+    has_errored: bool,
+}
+
+impl ReadFrame {
+    pub(crate) fn new() -> ReadFrame {
+        ReadFrame {
+            buffer: Vec::with_capacity(INITIAL_CAPACITY),
+            eof: false,
+            readable: false,
+            has_errored: false,
+        }
+    }
+}
+
+/// Write half state of the framed transports.
+pub(crate) struct WriteFrame {
+    pub(crate) buffer: Vec<u8>,
+}
+
+impl WriteFrame {
+    pub(crate) fn new() -> WriteFrame {
+        WriteFrame {
+            buffer: Vec::with_capacity(INITIAL_CAPACITY),
+        }
+    }
+}
+
+/// Polls the next frame out of `io`.
+///
+/// The stream ends after the reader hits EOF and the decoder
+/// has nothing left to produce, or after any error.
+pub(crate) fn poll_next_frame<T, D>(
+    mut io: Pin<&mut T>,
+    decoder: &mut D,
+    state: &mut ReadFrame,
+    cx: &mut Context<'_>,
+) -> Poll<Option<Result<D::Item, D::Error>>>
+where
+    T: AsyncRead + ?Sized,
+    D: Decoder,
+{
+    loop {
+        if state.has_errored {
+            state.readable = false;
+            return Poll::Ready(None);
+        }
+
+        if state.readable {
+            let frame = if state.eof {
+                decoder.decode_eof(&mut state.buffer)
+            } else {
+                decoder.decode(&mut state.buffer)
+            };
+
+            match frame {
+                Ok(Some(frame)) => return Poll::Ready(Some(Ok(frame))),
+                Ok(None) if state.eof => {
+                    state.readable = false;
+                    return Poll::Ready(None);
+                }
+                Ok(None) => state.readable = false,
+                Err(e) => {
+                    state.has_errored = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+
+        let len = state.buffer.len();
+        state.buffer.resize(len + READ_CHUNK, 0);
+
+        let res = io.as_mut().poll_read(cx, &mut state.buffer[len..]);
+        let read = match res {
+            Poll::Ready(Ok(read)) => read,
+            Poll::Ready(Err(e)) => {
+                state.buffer.truncate(len);
+                state.has_errored = true;
+                return Poll::Ready(Some(Err(e.into())));
+            }
+            Poll::Pending => {
+                state.buffer.truncate(len);
+                return Poll::Pending;
+            }
+        };
+
+        state.buffer.truncate(len + read);
+
+        if read == 0 {
+            if state.eof {
+                return Poll::Ready(None);
+            }
+
+            state.eof = true;
+        } else {
+            state.eof = false;
+        }
+
+        state.readable = true;
+    }
+}
+
+/// Writes out the whole write buffer, without flushing `io`.
+pub(crate) fn poll_write_frames<T>(
+    mut io: Pin<&mut T>,
+    state: &mut WriteFrame,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>>
+where
+    T: AsyncWrite + ?Sized,
+{
+    while !state.buffer.is_empty() {
+        let written = match io.as_mut().poll_write(cx, &state.buffer) {
+            Poll::Ready(Ok(0)) => {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write frame to transport",
+                )));
+            }
+            Poll::Ready(Ok(written)) => written,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        state.buffer.drain(..written);
+    }
+
+    Poll::Ready(Ok(()))
+}
+
+/// Writes out the whole write buffer and flushes `io`.
+pub(crate) fn poll_flush_frames<T>(
+    mut io: Pin<&mut T>,
+    state: &mut WriteFrame,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>>
+where
+    T: AsyncWrite + ?Sized,
+{
+    match poll_write_frames(io.as_mut(), state, cx) {
+        Poll::Ready(Ok(())) => io.poll_flush(cx),
+        other => other,
+    }
+}
+
+/// Makes room in the write buffer, writing it out if it grew past the boundary.
+pub(crate) fn poll_ready_frames<T>(
+    io: Pin<&mut T>,
+    state: &mut WriteFrame,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>>
+where
+    T: AsyncWrite + ?Sized,
+{
+    if state.buffer.len() >= BACKPRESSURE_BOUNDARY {
+        return poll_write_frames(io, state, cx);
+    }
+
+    Poll::Ready(Ok(()))
+}
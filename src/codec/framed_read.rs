@@ -0,0 +1,75 @@
+use super::Decoder;
+use super::framed_impl::{ReadFrame, poll_next_frame};
+use crate::io::AsyncRead;
+
+use futures::Stream;
+use pin_project_lite::pin_project;
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Stream of frames decoded from a reader.
+    pub struct FramedRead<R, D> {
+        #[pin]
+        inner: R,
+        decoder: D,
+        state: ReadFrame,
+    }
+}
+
+impl<R: AsyncRead, D: Decoder> FramedRead<R, D> {
+    /// Creates a new `FramedRead` decoding frames from `inner` with `decoder`.
+    pub fn new(inner: R, decoder: D) -> FramedRead<R, D> {
+        FramedRead {
+            inner,
+            decoder,
+            state: ReadFrame::new(),
+        }
+    }
+}
+
+impl<R, D> FramedRead<R, D> {
+    /// Obtains a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Obtains a mutable reference to the inner reader.
+    ///
+    /// Reading from it directly may corrupt the frame stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Obtains a reference to the decoder.
+    pub fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    /// Obtains a mutable reference to the decoder.
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+
+    /// Returns the bytes read but not yet decoded.
+    pub fn read_buffer(&self) -> &[u8] {
+        &self.state.buffer
+    }
+
+    /// Consumes the `FramedRead`, returning the inner reader.
+    ///
+    /// Bytes not yet decoded are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead, D: Decoder> Stream for FramedRead<R, D> {
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let pinned = self.project();
+        poll_next_frame(pinned.inner, pinned.decoder, pinned.state, cx)
+    }
+}
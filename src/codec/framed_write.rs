@@ -0,0 +1,89 @@
+use super::Encoder;
+use super::framed_impl::{WriteFrame, poll_flush_frames, poll_ready_frames};
+use crate::io::AsyncWrite;
+
+use futures::Sink;
+use pin_project_lite::pin_project;
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Sink of frames encoded into a writer.
+    pub struct FramedWrite<W, E> {
+        #[pin]
+        inner: W,
+        encoder: E,
+        state: WriteFrame,
+    }
+}
+
+impl<W: AsyncWrite, E> FramedWrite<W, E> {
+    /// Creates a new `FramedWrite` encoding frames into `inner` with `encoder`.
+    pub fn new(inner: W, encoder: E) -> FramedWrite<W, E> {
+        FramedWrite {
+            inner,
+            encoder,
+            state: WriteFrame::new(),
+        }
+    }
+}
+
+impl<W, E> FramedWrite<W, E> {
+    /// Obtains a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Obtains a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly may corrupt the frame stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Obtains a reference to the encoder.
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Obtains a mutable reference to the encoder.
+    pub fn encoder_mut(&mut self) -> &mut E {
+        &mut self.encoder
+    }
+
+    /// Returns the bytes encoded but not yet written.
+    pub fn write_buffer(&self) -> &[u8] {
+        &self.state.buffer
+    }
+
+    /// Consumes the `FramedWrite`, returning the inner writer.
+    ///
+    /// Bytes not yet written are lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite, E: Encoder<I>, I> Sink<I> for FramedWrite<W, E> {
+    type Error = E::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let pinned = self.project();
+        poll_ready_frames(pinned.inner, pinned.state, cx).map_err(Into::into)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: I) -> Result<(), Self::Error> {
+        let pinned = self.project();
+        pinned.encoder.encode(item, &mut pinned.state.buffer)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let pinned = self.project();
+        poll_flush_frames(pinned.inner, pinned.state, cx).map_err(Into::into)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
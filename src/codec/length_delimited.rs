@@ -0,0 +1,248 @@
+use super::{Decoder, Encoder, Framed, FramedRead, FramedWrite};
+use crate::io::{AsyncRead, AsyncWrite};
+
+use std::io;
+
+/// Default maximum frame length, 8 MiB.
+const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// Byte order of the length field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// Configuration for a `LengthDelimitedCodec`.
+#[derive(Debug, Clone, Copy)]
+pub struct Builder {
+    // Width of the length field in bytes.
+    length_field_length: usize,
+
+    // Byte order of the length field.
+    endianness: Endianness,
+
+    // Largest accepted payload.
+    max_frame_length: usize,
+}
+
+impl Builder {
+    /// Creates a new `Builder` with the defaults:
+    /// a 4 byte big endian length field and frames up to 8 MiB.
+    pub fn new() -> Builder {
+        Builder {
+            length_field_length: 4,
+            endianness: Endianness::Big,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+
+    /// Sets the width of the length field in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is not in `1..=8`.
+    pub fn length_field_length(&mut self, len: usize) -> &mut Self {
+        assert!(
+            (1..=8).contains(&len),
+            "length field must be between 1 and 8 bytes, got {len}"
+        );
+
+        self.length_field_length = len;
+        self
+    }
+
+    /// Reads and writes the length field as big endian.
+    pub fn big_endian(&mut self) -> &mut Self {
+        self.endianness = Endianness::Big;
+        self
+    }
+
+    /// Reads and writes the length field as little endian.
+    pub fn little_endian(&mut self) -> &mut Self {
+        self.endianness = Endianness::Little;
+        self
+    }
+
+    /// Reads and writes the length field in the native byte order.
+    pub fn native_endian(&mut self) -> &mut Self {
+        if cfg!(target_endian = "big") {
+            self.big_endian()
+        } else {
+            self.little_endian()
+        }
+    }
+
+    /// Sets the largest accepted payload length.
+    ///
+    /// Bigger frames fail to decode and encode with `InvalidData`.
+    pub fn max_frame_length(&mut self, len: usize) -> &mut Self {
+        self.max_frame_length = len;
+        self
+    }
+
+    /// Creates a codec with this configuration.
+    pub fn new_codec(&self) -> LengthDelimitedCodec {
+        LengthDelimitedCodec {
+            builder: *self,
+            state: DecodeState::Head,
+        }
+    }
+
+    /// Creates a `FramedRead` over `inner` with this configuration.
+    pub fn new_read<R: AsyncRead>(&self, inner: R) -> FramedRead<R, LengthDelimitedCodec> {
+        FramedRead::new(inner, self.new_codec())
+    }
+
+    /// Creates a `FramedWrite` over `inner` with this configuration.
+    pub fn new_write<W: AsyncWrite>(&self, inner: W) -> FramedWrite<W, LengthDelimitedCodec> {
+        FramedWrite::new(inner, self.new_codec())
+    }
+
+    /// Creates a `Framed` over `inner` with this configuration.
+    pub fn new_framed<T: AsyncRead + AsyncWrite>(
+        &self,
+        inner: T,
+    ) -> Framed<T, LengthDelimitedCodec> {
+        Framed::new(inner, self.new_codec())
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DecodeState {
+    // Waiting for the length field.
+    Head,
+
+    // Waiting for a payload of the given length.
+    Data(usize),
+}
+
+/// Codec for frames prefixed with their length.
+///
+/// The length field holds the payload length only, not counting itself.
+#[derive(Debug, Clone)]
+pub struct LengthDelimitedCodec {
+    builder: Builder,
+    state: DecodeState,
+}
+
+impl LengthDelimitedCodec {
+    /// Creates a new `LengthDelimitedCodec` with the default configuration.
+    pub fn new() -> LengthDelimitedCodec {
+        Builder::new().new_codec()
+    }
+
+    /// Creates a `Builder` to configure the codec.
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Returns the largest accepted payload length.
+    pub fn max_frame_length(&self) -> usize {
+        self.builder.max_frame_length
+    }
+
+    /// Sets the largest accepted payload length.
+    pub fn set_max_frame_length(&mut self, len: usize) {
+        self.builder.max_frame_length = len;
+    }
+
+    fn decode_head(&mut self, src: &mut Vec<u8>) -> io::Result<Option<usize>> {
+        let field_len = self.builder.length_field_length;
+
+        if src.len() < field_len {
+            return Ok(None);
+        }
+
+        let mut bytes = [0u8; 8];
+        let len = match self.builder.endianness {
+            Endianness::Big => {
+                bytes[8 - field_len..].copy_from_slice(&src[..field_len]);
+                u64::from_be_bytes(bytes)
+            }
+            Endianness::Little => {
+                bytes[..field_len].copy_from_slice(&src[..field_len]);
+                u64::from_le_bytes(bytes)
+            }
+        };
+
+        let len = match usize::try_from(len) {
+            Ok(len) if len <= self.builder.max_frame_length => len,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "frame length exceeds the maximum",
+                ));
+            }
+        };
+
+        src.drain(..field_len);
+        src.reserve(len);
+
+        Ok(Some(len))
+    }
+}
+
+impl Default for LengthDelimitedCodec {
+    fn default() -> Self {
+        LengthDelimitedCodec::new()
+    }
+}
+
+impl Decoder for LengthDelimitedCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+        let len = match self.state {
+            DecodeState::Head => match self.decode_head(src)? {
+                Some(len) => {
+                    self.state = DecodeState::Data(len);
+                    len
+                }
+                None => return Ok(None),
+            },
+            DecodeState::Data(len) => len,
+        };
+
+        if src.len() < len {
+            return Ok(None);
+        }
+
+        self.state = DecodeState::Head;
+        Ok(Some(src.drain(..len).collect()))
+    }
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for LengthDelimitedCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, data: T, dst: &mut Vec<u8>) -> io::Result<()> {
+        let data = data.as_ref();
+        let field_len = self.builder.length_field_length;
+        let len = data.len() as u64;
+
+        let fits = field_len == 8 || len < (1 << (field_len * 8));
+        if data.len() > self.builder.max_frame_length || !fits {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame length exceeds the maximum",
+            ));
+        }
+
+        dst.reserve(field_len + data.len());
+        match self.builder.endianness {
+            Endianness::Big => dst.extend_from_slice(&len.to_be_bytes()[8 - field_len..]),
+            Endianness::Little => dst.extend_from_slice(&len.to_le_bytes()[..field_len]),
+        }
+        dst.extend_from_slice(data);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Decoder, Encoder};
+
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Codec for newline separated UTF-8 text.
+///
+/// Decoded lines have the trailing `\n` or `\r\n` removed,
+/// encoded lines get a `\n` appended.
+#[derive(Debug, Clone)]
+pub struct LinesCodec {
+    // Index up to which the buffer was already searched for a newline.
+    next_index: usize,
+
+    // Longest accepted line, without the newline.
+    max_length: usize,
+
+    // Skipping the rest of a line which was too long.
+    is_discarding: bool,
+}
+
+impl LinesCodec {
+    /// Creates a new `LinesCodec` without a line length limit.
+    ///
+    /// A peer that never sends a newline makes the buffer grow unbounded,
+    /// prefer `new_with_max_length` for untrusted input.
+    pub fn new() -> LinesCodec {
+        LinesCodec::new_with_max_length(usize::MAX)
+    }
+
+    /// Creates a new `LinesCodec` rejecting lines longer than `max_length`.
+    ///
+    /// A line over the limit yields `LinesCodecError::MaxLineLengthExceeded`
+    /// and is skipped up to the next newline.
+    pub fn new_with_max_length(max_length: usize) -> LinesCodec {
+        LinesCodec {
+            next_index: 0,
+            max_length,
+            is_discarding: false,
+        }
+    }
+
+    /// Returns the maximum accepted line length.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+}
+
+impl Default for LinesCodec {
+    fn default() -> Self {
+        LinesCodec::new()
+    }
+}
+
+fn into_line(mut bytes: Vec<u8>) -> Result<String, LinesCodecError> {
+    if bytes.ends_with(b"\n") {
+        bytes.pop();
+    }
+
+    if bytes.ends_with(b"\r") {
+        bytes.pop();
+    }
+
+    String::from_utf8(bytes).map_err(|_| {
+        LinesCodecError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "line is not valid UTF-8",
+        ))
+    })
+}
+
+impl Decoder for LinesCodec {
+    type Item = String;
+    type Error = LinesCodecError;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<String>, LinesCodecError> {
+        loop {
+            let read_to = cmp::min(self.max_length.saturating_add(1), src.len());
+            let newline = src[self.next_index..read_to]
+                .iter()
+                .position(|b| *b == b'\n');
+
+            match (self.is_discarding, newline) {
+                (true, Some(offset)) => {
+                    src.drain(..self.next_index + offset + 1);
+                    self.is_discarding = false;
+                    self.next_index = 0;
+                }
+
+                (true, None) => {
+                    src.drain(..read_to);
+                    self.next_index = 0;
+
+                    if src.is_empty() {
+                        return Ok(None);
+                    }
+                }
+
+                (false, Some(offset)) => {
+                    let end = self.next_index + offset + 1;
+                    self.next_index = 0;
+
+                    let line: Vec<u8> = src.drain(..end).collect();
+                    return into_line(line).map(Some);
+                }
+
+                (false, None) if src.len() > self.max_length => {
+                    self.is_discarding = true;
+                    return Err(LinesCodecError::MaxLineLengthExceeded);
+                }
+
+                (false, None) => {
+                    self.next_index = read_to;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut Vec<u8>) -> Result<Option<String>, LinesCodecError> {
+        match self.decode(src)? {
+            Some(line) => Ok(Some(line)),
+            None if src.is_empty() || self.is_discarding => Ok(None),
+            None => {
+                self.next_index = 0;
+                into_line(std::mem::take(src)).map(Some)
+            }
+        }
+    }
+}
+
+impl<T: AsRef<str>> Encoder<T> for LinesCodec {
+    type Error = LinesCodecError;
+
+    fn encode(&mut self, line: T, dst: &mut Vec<u8>) -> Result<(), LinesCodecError> {
+        let line = line.as_ref();
+
+        dst.reserve(line.len() + 1);
+        dst.extend_from_slice(line.as_bytes());
+        dst.push(b'\n');
+
+        Ok(())
+    }
+}
+
+/// Errors produced by `LinesCodec`.
+#[derive(Debug)]
+pub enum LinesCodecError {
+    /// A line was longer than the configured maximum.
+    MaxLineLengthExceeded,
+
+    /// An I/O error, or a line which is not valid UTF-8.
+    Io(io::Error),
+}
+
+impl fmt::Display for LinesCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinesCodecError::MaxLineLengthExceeded => write!(f, "max line length exceeded"),
+            LinesCodecError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LinesCodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LinesCodecError::MaxLineLengthExceeded => None,
+            LinesCodecError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for LinesCodecError {
+    fn from(e: io::Error) -> Self {
+        LinesCodecError::Io(e)
+    }
+}
@@ -0,0 +1,154 @@
+//! Turning byte streams into streams of frames.
+//!
+//! A `Decoder` splits incoming bytes into frames and an `Encoder` turns frames into bytes.
+//! `FramedRead`, `FramedWrite` and `Framed` apply them to any `AsyncRead`/`AsyncWrite`,
+//! exposing a `Stream` of decoded frames and a `Sink` of frames to encode.
+
+mod bytes_codec;
+mod decoder;
+mod encoder;
+mod framed;
+mod framed_impl;
+mod framed_read;
+mod framed_write;
+mod lines_codec;
+
+pub mod length_delimited;
+
+pub use bytes_codec::BytesCodec;
+pub use decoder::Decoder;
+pub use encoder::Encoder;
+pub use framed::Framed;
+pub use framed_read::FramedRead;
+pub use framed_write::FramedWrite;
+pub use length_delimited::LengthDelimitedCodec;
+pub use lines_codec::{LinesCodec, LinesCodecError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::AsyncRead;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// Reader handing out at most `chunk` bytes per read.
+    struct Chunked<'d> {
+        data: &'d [u8],
+        chunk: usize,
+    }
+
+    impl AsyncRead for Chunked<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let chunk = std::cmp::min(self.chunk, buf.len());
+            Pin::new(&mut self.data).poll_read(cx, &mut buf[..chunk])
+        }
+    }
+
+    #[test]
+    fn lines_codec_roundtrip() {
+        block_on(async {
+            let mut sink = FramedWrite::new(Vec::new(), LinesCodec::new());
+            sink.send("hello").await.unwrap();
+            sink.send(String::from("world")).await.unwrap();
+            assert_eq!(sink.get_ref(), b"hello\nworld\n");
+
+            let input = b"first\r\nsecond\nlast";
+            let reader = Chunked {
+                data: &input[..],
+                chunk: 3,
+            };
+            let lines: Vec<String> = FramedRead::new(reader, LinesCodec::new())
+                .map(|line| line.unwrap())
+                .collect()
+                .await;
+            assert_eq!(lines, ["first", "second", "last"]);
+        })
+    }
+
+    #[test]
+    fn lines_codec_max_length() {
+        let mut codec = LinesCodec::new_with_max_length(4);
+        let mut buf = b"toolong\nok\n".to_vec();
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LinesCodecError::MaxLineLengthExceeded)
+        ));
+        assert_eq!(codec.decode(&mut buf).unwrap().as_deref(), Some("ok"));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn length_delimited_roundtrip() {
+        block_on(async {
+            let mut builder = LengthDelimitedCodec::builder();
+            builder.length_field_length(2).little_endian();
+
+            let mut sink = builder.new_write(Vec::new());
+            sink.send(b"abc").await.unwrap();
+            sink.send(b"").await.unwrap();
+            assert_eq!(sink.get_ref(), b"\x03\x00abc\x00\x00");
+
+            let bytes = sink.into_inner();
+            let reader = Chunked {
+                data: &bytes,
+                chunk: 1,
+            };
+            let frames: Vec<Vec<u8>> = builder
+                .new_read(reader)
+                .map(|frame| frame.unwrap())
+                .collect()
+                .await;
+            assert_eq!(frames, [b"abc".to_vec(), Vec::new()]);
+        })
+    }
+
+    #[test]
+    fn length_delimited_limits() {
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_length(1)
+            .max_frame_length(300)
+            .new_codec();
+
+        let mut dst = Vec::new();
+        assert!(codec.encode(vec![0u8; 256], &mut dst).is_err());
+
+        codec.set_max_frame_length(2);
+        let mut src = b"\x03abc".to_vec();
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn truncated_frame_at_eof() {
+        block_on(async {
+            let input = b"\x00\x00\x00\x05abc";
+            let mut frames = FramedRead::new(&input[..], LengthDelimitedCodec::new());
+
+            let err = frames.next().await.unwrap().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+            assert!(frames.next().await.is_none());
+        })
+    }
+
+    #[test]
+    fn bytes_codec_passthrough() {
+        block_on(async {
+            let mut sink = FramedWrite::new(Vec::new(), BytesCodec::new());
+            sink.send(&b"raw"[..]).await.unwrap();
+            assert_eq!(sink.get_ref(), b"raw");
+
+            let chunks: Vec<Vec<u8>> = FramedRead::new(&b"data"[..], BytesCodec::new())
+                .map(|chunk| chunk.unwrap())
+                .collect()
+                .await;
+            assert_eq!(chunks, [b"data".to_vec()]);
+        })
+    }
+}
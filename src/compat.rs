@@ -0,0 +1,337 @@
+//! Interoperability with the `futures::io` traits.
+//!
+//! `Compat` adapts I/O types in either direction:
+//! wrapping a lamp reader or writer gives a `futures::io` one, and the other way around.
+//!
+//! With the `futures-io` feature enabled, lamp's own I/O types
+//! implement the `futures::io` traits directly.
+
+use crate::io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+use futures::io as fio;
+use pin_project_lite::pin_project;
+
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Adapter between lamp's and `futures::io`'s I/O traits.
+    ///
+    /// Implements the `futures::io` traits when `T` implements lamp's,
+    /// and lamp's traits when `T` implements the `futures::io` ones.
+    ///
+    /// lamp's `AsyncWrite` has no close operation,
+    /// so `poll_close` only flushes the wrapped writer.
+    #[derive(Debug)]
+    pub struct Compat<T> {
+        #[pin]
+        inner: T,
+    }
+}
+
+impl<T> Compat<T> {
+    /// Wraps `inner`.
+    pub fn new(inner: T) -> Compat<T> {
+        Compat { inner }
+    }
+
+    /// Obtains a reference to the wrapped I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Obtains a mutable reference to the wrapped I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Obtains a pinned mutable reference to the wrapped I/O object.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.project().inner
+    }
+
+    /// Consumes the `Compat`, returning the wrapped I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Adapts a lamp reader to `futures::io`.
+pub trait LampAsyncReadCompatExt: AsyncRead {
+    fn compat(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: AsyncRead + ?Sized> LampAsyncReadCompatExt for T {}
+
+/// Adapts a lamp writer to `futures::io`.
+pub trait LampAsyncWriteCompatExt: AsyncWrite {
+    fn compat_write(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: AsyncWrite + ?Sized> LampAsyncWriteCompatExt for T {}
+
+/// Adapts a `futures::io` reader to lamp.
+pub trait FuturesAsyncReadCompatExt: fio::AsyncRead {
+    fn compat(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: fio::AsyncRead + ?Sized> FuturesAsyncReadCompatExt for T {}
+
+/// Adapts a `futures::io` writer to lamp.
+pub trait FuturesAsyncWriteCompatExt: fio::AsyncWrite {
+    fn compat_write(self) -> Compat<Self>
+    where
+        Self: Sized,
+    {
+        Compat::new(self)
+    }
+}
+
+impl<T: fio::AsyncWrite + ?Sized> FuturesAsyncWriteCompatExt for T {}
+
+// lamp -> futures
+
+impl<T: AsyncRead> fio::AsyncRead for Compat<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        AsyncRead::poll_read(self.project().inner, cx, buf)
+    }
+}
+
+impl<T: AsyncBufRead> fio::AsyncBufRead for Compat<T> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        AsyncBufRead::poll_fill_buf(self.project().inner, cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        AsyncBufRead::consume(self.project().inner, amt)
+    }
+}
+
+impl<T: AsyncWrite> fio::AsyncWrite for Compat<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        AsyncWrite::poll_write(self.project().inner, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        AsyncWrite::poll_flush(self.project().inner, cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        AsyncWrite::poll_flush(self.project().inner, cx)
+    }
+}
+
+// futures -> lamp
+
+impl<T: fio::AsyncRead> AsyncRead for Compat<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        fio::AsyncRead::poll_read(self.project().inner, cx, buf)
+    }
+}
+
+impl<T: fio::AsyncBufRead> AsyncBufRead for Compat<T> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        fio::AsyncBufRead::poll_fill_buf(self.project().inner, cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        fio::AsyncBufRead::consume(self.project().inner, amt)
+    }
+}
+
+impl<T: fio::AsyncWrite> AsyncWrite for Compat<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        fio::AsyncWrite::poll_write(self.project().inner, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        fio::AsyncWrite::poll_flush(self.project().inner, cx)
+    }
+}
+
+/// Implements the `futures::io` traits for lamp's own I/O types
+/// by forwarding to their lamp implementations.
+#[cfg(feature = "futures-io")]
+mod direct {
+    use crate::io::{
+        AsyncBufRead, AsyncRead, AsyncWrite, BufReader, BufStream, BufWriter, DuplexStream,
+        TcpStream,
+    };
+
+    use futures::io as fio;
+
+    use std::io::Result;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    macro_rules! futures_read {
+        ($([$($gen: tt)*] $ty: ty),* $(,)?) => {$(
+            impl<$($gen)*> fio::AsyncRead for $ty {
+                fn poll_read(
+                    self: Pin<&mut Self>,
+                    cx: &mut Context<'_>,
+                    buf: &mut [u8],
+                ) -> Poll<Result<usize>> {
+                    AsyncRead::poll_read(self, cx, buf)
+                }
+            }
+        )*};
+    }
+
+    macro_rules! futures_write {
+        ($([$($gen: tt)*] $ty: ty),* $(,)?) => {$(
+            impl<$($gen)*> fio::AsyncWrite for $ty {
+                fn poll_write(
+                    self: Pin<&mut Self>,
+                    cx: &mut Context<'_>,
+                    buf: &[u8],
+                ) -> Poll<Result<usize>> {
+                    AsyncWrite::poll_write(self, cx, buf)
+                }
+
+                fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                    AsyncWrite::poll_flush(self, cx)
+                }
+
+                fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                    AsyncWrite::poll_flush(self, cx)
+                }
+            }
+        )*};
+    }
+
+    macro_rules! futures_buf_read {
+        ($([$($gen: tt)*] $ty: ty),* $(,)?) => {$(
+            impl<$($gen)*> fio::AsyncBufRead for $ty {
+                fn poll_fill_buf(
+                    self: Pin<&mut Self>,
+                    cx: &mut Context<'_>,
+                ) -> Poll<Result<&[u8]>> {
+                    AsyncBufRead::poll_fill_buf(self, cx)
+                }
+
+                fn consume(self: Pin<&mut Self>, amt: usize) {
+                    AsyncBufRead::consume(self, amt)
+                }
+            }
+        )*};
+    }
+
+    futures_read!(
+        [] TcpStream,
+        ['t] &'t TcpStream,
+        [] DuplexStream,
+        [R: AsyncRead] BufReader<R>,
+        [W: AsyncWrite + AsyncRead] BufWriter<W>,
+        [S: AsyncRead + AsyncWrite] BufStream<S>,
+    );
+
+    futures_write!(
+        [] TcpStream,
+        ['t] &'t TcpStream,
+        [] DuplexStream,
+        [R: AsyncRead + AsyncWrite] BufReader<R>,
+        [W: AsyncWrite] BufWriter<W>,
+        [S: AsyncRead + AsyncWrite] BufStream<S>,
+    );
+
+    futures_buf_read!(
+        [R: AsyncRead] BufReader<R>,
+        [W: AsyncWrite + AsyncBufRead] BufWriter<W>,
+        [S: AsyncRead + AsyncWrite] BufStream<S>,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+
+    #[test]
+    fn lamp_to_futures() {
+        block_on(async {
+            let mut reader = LampAsyncReadCompatExt::compat(BufReader::new(&b"line one\nrest"[..]));
+            let mut line = String::new();
+            fio::AsyncBufReadExt::read_line(&mut reader, &mut line)
+                .await
+                .unwrap();
+            assert_eq!(line, "line one\n");
+
+            let mut rest = Vec::new();
+            fio::AsyncReadExt::read_to_end(&mut reader, &mut rest)
+                .await
+                .unwrap();
+            assert_eq!(rest, b"rest");
+
+            let mut writer = LampAsyncWriteCompatExt::compat_write(Vec::new());
+            fio::AsyncWriteExt::write_all(&mut writer, b"out")
+                .await
+                .unwrap();
+            fio::AsyncWriteExt::close(&mut writer).await.unwrap();
+            assert_eq!(writer.get_ref(), b"out");
+        })
+    }
+
+    #[test]
+    fn futures_to_lamp() {
+        block_on(async {
+            let mut reader = Cursor::new(b"abc\ndef".to_vec()).compat();
+            let mut buf = Vec::new();
+            AsyncBufReadExt::read_until(&mut reader, b'\n', &mut buf)
+                .await
+                .unwrap();
+            assert_eq!(buf, b"abc\n");
+
+            let mut rest = [0u8; 8];
+            let read = AsyncReadExt::read(&mut reader, &mut rest).await.unwrap();
+            assert_eq!(&rest[..read], b"def");
+
+            let mut writer = Cursor::new(Vec::new()).compat_write();
+            AsyncWriteExt::write(&mut writer, b"in").await.unwrap();
+            AsyncWriteExt::flush(&mut writer).await.unwrap();
+            assert_eq!(writer.get_ref().get_ref(), b"in");
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "futures-io")]
+    fn direct_impls() {
+        block_on(async {
+            let (mut a, mut b) = crate::io::duplex(8);
+            fio::AsyncWriteExt::write_all(&mut a, b"hey").await.unwrap();
+            drop(a);
+
+            let mut out = Vec::new();
+            fio::AsyncReadExt::read_to_end(&mut b, &mut out)
+                .await
+                .unwrap();
+            assert_eq!(out, b"hey");
+        })
+    }
+}
@@ -0,0 +1,314 @@
+use super::{asyncify, shut_down};
+use crate::io::{AsyncRead, AsyncSeek, AsyncWrite};
+use crate::runtime::blocking::BlockingTask;
+use crate::runtime::{Executor, ExecutorHandle};
+
+use std::cmp;
+use std::fs;
+use std::future::poll_fn;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Biggest chunk of data moved in one blocking operation.
+const MAX_BUF: usize = 2 * 1024 * 1024;
+
+/// Blocking operation performed on the file.
+enum Operation {
+    Read(io::Result<usize>),
+    Write(io::Result<()>),
+    Seek(io::Result<u64>),
+}
+
+enum State {
+    // No operation in flight, holds the buffer.
+    Idle(Option<Vec<u8>>),
+
+    // Operation running on the blocking pool, which hands back the buffer.
+    Busy(BlockingTask<(Operation, Vec<u8>)>),
+}
+
+/// Handle to an open file.
+///
+/// Operations are run on the runtime's blocking threads.
+/// Writes are done in the background, a write resolving
+/// only means the data was handed off, call `flush` to wait for it.
+/// Errors of background writes are reported by the next write or flush.
+pub struct File {
+    std: Arc<fs::File>,
+    handle: Arc<ExecutorHandle>,
+    state: State,
+
+    // Read position inside the buffer.
+    pos: usize,
+
+    // Error of a background write, not yet reported.
+    last_write_err: Option<io::Error>,
+}
+
+impl File {
+    /// Opens a file in read-only mode.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let std = asyncify(move || fs::File::open(path)).await?;
+
+        Ok(File::from_std(std))
+    }
+
+    /// Opens a file in write-only mode, creating or truncating it.
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let std = asyncify(move || fs::File::create(path)).await?;
+
+        Ok(File::from_std(std))
+    }
+
+    /// Wraps a `std::fs::File`.
+    pub fn from_std(std: fs::File) -> File {
+        File {
+            std: Arc::new(std),
+            handle: Executor::get(),
+            state: State::Idle(Some(Vec::new())),
+            pos: 0,
+            last_write_err: None,
+        }
+    }
+
+    /// Queries metadata about the file.
+    pub async fn metadata(&self) -> io::Result<fs::Metadata> {
+        let std = Arc::clone(&self.std);
+        asyncify(move || std.metadata()).await
+    }
+
+    /// Waits for pending writes and syncs all data and metadata to disk.
+    pub async fn sync_all(&mut self) -> io::Result<()> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_flush(cx)).await?;
+
+        let std = Arc::clone(&self.std);
+        asyncify(move || std.sync_all()).await
+    }
+
+    /// Waits for pending writes and syncs the data to disk,
+    /// without necessarily syncing metadata.
+    pub async fn sync_data(&mut self) -> io::Result<()> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_flush(cx)).await?;
+
+        let std = Arc::clone(&self.std);
+        asyncify(move || std.sync_data()).await
+    }
+
+    /// Truncates or extends the file to `size` bytes.
+    ///
+    /// The cursor is not moved.
+    pub async fn set_len(&mut self, size: u64) -> io::Result<()> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_flush(cx)).await?;
+
+        let unread = self.discard_buffer();
+        let std = Arc::clone(&self.std);
+
+        asyncify(move || {
+            if unread > 0 {
+                (&*std).seek(SeekFrom::Current(-unread))?;
+            }
+
+            std.set_len(size)
+        })
+        .await
+    }
+
+    /// Clears the buffer, returning how many read bytes were not handed out yet.
+    fn discard_buffer(&mut self) -> i64 {
+        match self.state {
+            State::Idle(Some(ref mut buf)) => {
+                let unread = buf.len() - self.pos;
+                buf.clear();
+                self.pos = 0;
+                unread as i64
+            }
+
+            _ => 0,
+        }
+    }
+
+    /// Waits for the operation in flight, if any.
+    ///
+    /// The buffer is only kept after a successful read.
+    /// Fails if the runtime shut down before running the operation.
+    fn poll_complete(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Operation>>> {
+        let task = match self.state {
+            State::Busy(ref mut task) => task,
+            State::Idle(_) => return Poll::Ready(Ok(None)),
+        };
+
+        let (op, mut buf) = match task.poll_unless_cancelled(cx) {
+            Poll::Ready(Some(done)) => done,
+            Poll::Ready(None) => {
+                // The buffer went away with the operation.
+                self.pos = 0;
+                self.state = State::Idle(Some(Vec::new()));
+                return Poll::Ready(Err(shut_down()));
+            }
+            Poll::Pending => return Poll::Pending,
+        };
+
+        if !matches!(op, Operation::Read(Ok(_))) {
+            buf.clear();
+        }
+
+        self.pos = 0;
+        self.state = State::Idle(Some(buf));
+
+        Poll::Ready(Ok(Some(op)))
+    }
+
+    /// Takes the buffer out of an idle file.
+    fn take_buffer(&mut self) -> Vec<u8> {
+        match self.state {
+            State::Idle(ref mut buf) => buf.take().expect("file buffer missing"),
+            State::Busy(_) => unreachable!("file is busy"),
+        }
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+
+        if dst.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            match me.poll_complete(cx)? {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Operation::Read(Ok(0)))) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Some(Operation::Read(Err(e)))) => return Poll::Ready(Err(e)),
+                Poll::Ready(Some(Operation::Write(Err(e)))) => me.last_write_err = Some(e),
+                Poll::Ready(_) => {}
+            }
+
+            if let State::Idle(Some(ref buf)) = me.state
+                && me.pos < buf.len()
+            {
+                let amnt = cmp::min(buf.len() - me.pos, dst.len());
+                dst[..amnt].copy_from_slice(&buf[me.pos..me.pos + amnt]);
+                me.pos += amnt;
+
+                return Poll::Ready(Ok(amnt));
+            }
+
+            let mut buf = me.take_buffer();
+            let max = cmp::min(dst.len(), MAX_BUF);
+            let std = Arc::clone(&me.std);
+
+            buf.clear();
+            me.pos = 0;
+            me.state = State::Busy(me.handle.spawn_blocking(move || {
+                buf.resize(max, 0);
+                let res = (&*std).read(&mut buf);
+                buf.truncate(*res.as_ref().unwrap_or(&0));
+
+                (Operation::Read(res), buf)
+            }));
+        }
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+
+        match me.poll_complete(cx)? {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(Operation::Write(Err(e)))) => return Poll::Ready(Err(e)),
+            Poll::Ready(_) => {}
+        }
+
+        if let Some(e) = me.last_write_err.take() {
+            return Poll::Ready(Err(e));
+        }
+
+        // Bytes read ahead but not handed out have to be skipped back over.
+        let unread = me.discard_buffer();
+        let mut buf = me.take_buffer();
+        let amnt = cmp::min(src.len(), MAX_BUF);
+        let std = Arc::clone(&me.std);
+
+        buf.extend_from_slice(&src[..amnt]);
+        me.state = State::Busy(me.handle.spawn_blocking(move || {
+            let res = if unread > 0 {
+                (&*std).seek(SeekFrom::Current(-unread)).map(drop)
+            } else {
+                Ok(())
+            };
+
+            let res = res.and_then(|_| (&*std).write_all(&buf));
+            (Operation::Write(res), buf)
+        }));
+
+        Poll::Ready(Ok(amnt))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let me = self.get_mut();
+
+        match me.poll_complete(cx)? {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(Operation::Write(Err(e)))) => return Poll::Ready(Err(e)),
+            Poll::Ready(_) => {}
+        }
+
+        match me.last_write_err.take() {
+            Some(e) => Poll::Ready(Err(e)),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+impl AsyncSeek for File {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let me = self.get_mut();
+
+        loop {
+            match me.poll_complete(cx)? {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Operation::Seek(res))) => return Poll::Ready(res),
+                Poll::Ready(Some(Operation::Write(Err(e)))) => return Poll::Ready(Err(e)),
+                Poll::Ready(_) => {}
+            }
+
+            if let Some(e) = me.last_write_err.take() {
+                return Poll::Ready(Err(e));
+            }
+
+            // The cursor of the file is ahead by the unread bytes.
+            let unread = me.discard_buffer();
+            let pos = match pos {
+                SeekFrom::Current(offset) => SeekFrom::Current(offset - unread),
+                other => other,
+            };
+
+            let buf = me.take_buffer();
+            let std = Arc::clone(&me.std);
+
+            me.state = State::Busy(
+                me.handle
+                    .spawn_blocking(move || (Operation::Seek((&*std).seek(pos)), buf)),
+            );
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Asynchronous file system operations.
+//!
+//! The underlying syscalls are blocking, so they run on the runtime's
+//! blocking threads and the awaiting task is woken once they finish.
+
+mod file;
+mod ops;
+mod read_dir;
+
+pub use file::File;
+pub use ops::{create_dir_all, metadata, read, read_to_string, remove_file, rename, write};
+pub use read_dir::{DirEntry, ReadDir, read_dir};
+
+use crate::runtime::Executor;
+
+use std::future::poll_fn;
+use std::io;
+
+/// Runs a blocking file system operation on the blocking pool.
+///
+/// Fails if the runtime shut down before running it.
+pub(crate) async fn asyncify<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut task = Executor::get().spawn_blocking(f);
+
+    match poll_fn(|cx| task.poll_unless_cancelled(cx)).await {
+        Some(res) => res,
+        None => Err(shut_down()),
+    }
+}
+
+/// Error of an operation the blocking pool dropped when the runtime shut down.
+pub(crate) fn shut_down() -> io::Error {
+    io::Error::other("runtime shut down")
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::*;
+    use crate::Executor;
+    use crate::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+    use futures::StreamExt;
+    use std::io::SeekFrom;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lamp-fs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn file_operations() {
+        let dir = temp_dir("file");
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async move {
+            create_dir_all(dir.join("nested")).await.unwrap();
+
+            let path = dir.join("nested/data.txt");
+            let mut file = File::create(&path).await.unwrap();
+            file.write_all(b"hello world").await.unwrap();
+            file.sync_all().await.unwrap();
+            drop(file);
+
+            assert_eq!(read_to_string(&path).await.unwrap(), "hello world");
+            assert_eq!(metadata(&path).await.unwrap().len(), 11);
+
+            let mut file = File::open(&path).await.unwrap();
+            let mut buf = [0u8; 5];
+            file.read(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"hello");
+
+            // Seeking accounts for the bytes read ahead.
+            let pos = file.seek(SeekFrom::Current(1)).await.unwrap();
+            assert_eq!(pos, 6);
+
+            let mut rest = Vec::new();
+            file.read_to_end(&mut rest).await.unwrap();
+            assert_eq!(rest, b"world");
+
+            let renamed = dir.join("renamed.txt");
+            rename(&path, &renamed).await.unwrap();
+            remove_file(&renamed).await.unwrap();
+            assert!(metadata(&renamed).await.is_err());
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn read_write_and_dirs() {
+        let dir = temp_dir("dir");
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async move {
+            create_dir_all(&dir).await.unwrap();
+
+            for i in 0..40 {
+                write(dir.join(format!("{i}.bin")), [i as u8; 3])
+                    .await
+                    .unwrap();
+            }
+
+            // From a worker thread as well.
+            let path = dir.join("7.bin");
+            let data = Executor::spawn(async move { read(path).await.unwrap() }).await;
+            assert_eq!(data, [7u8; 3]);
+
+            let mut names: Vec<String> = read_dir(&dir)
+                .await
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect()
+                .await;
+            names.sort();
+            assert_eq!(names.len(), 40);
+            assert_eq!(names[0], "0.bin");
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn fails_after_shutdown() {
+        let dir = temp_dir("shutdown");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.txt");
+        std::fs::write(&path, b"hello").unwrap();
+
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+        exec.shutdown();
+
+        handle.block_on(async {
+            let err = read(&path).await.unwrap_err();
+            assert_eq!(err.to_string(), "runtime shut down");
+
+            let mut file = File::from_std(std::fs::File::open(&path).unwrap());
+            let mut buf = [0u8; 5];
+            assert!(file.read(&mut buf).await.is_err());
+            assert!(file.read(&mut buf).await.is_err());
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
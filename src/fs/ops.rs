@@ -0,0 +1,49 @@
+use super::asyncify;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Reads the whole contents of a file.
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::read(path)).await
+}
+
+/// Reads the whole contents of a file into a string.
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::read_to_string(path)).await
+}
+
+/// Writes `contents` to a file, creating or truncating it.
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    let contents = contents.as_ref().to_owned();
+    asyncify(move || fs::write(path, contents)).await
+}
+
+/// Creates a directory and all of its missing parents.
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::create_dir_all(path)).await
+}
+
+/// Removes a file.
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::remove_file(path)).await
+}
+
+/// Renames a file or directory, replacing `to` if it exists.
+pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    let from = from.as_ref().to_owned();
+    let to = to.as_ref().to_owned();
+    asyncify(move || fs::rename(from, to)).await
+}
+
+/// Queries metadata about a file or directory, following symlinks.
+pub async fn metadata(path: impl AsRef<Path>) -> io::Result<fs::Metadata> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::metadata(path)).await
+}
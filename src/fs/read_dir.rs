@@ -0,0 +1,137 @@
+use super::{asyncify, shut_down};
+use crate::runtime::Executor;
+use crate::runtime::blocking::BlockingTask;
+
+use futures::Stream;
+
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::future::poll_fn;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Amount of entries fetched in one blocking operation.
+const CHUNK_SIZE: usize = 32;
+
+/// Entries fetched so far, the iterator and whether it has more entries.
+type Chunk = (VecDeque<io::Result<DirEntry>>, fs::ReadDir, bool);
+
+enum State {
+    Idle(Option<Chunk>),
+    Busy(BlockingTask<Chunk>),
+}
+
+/// Returns a stream over the entries of a directory.
+pub async fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
+    let path = path.as_ref().to_owned();
+    let std = asyncify(move || fs::read_dir(path)).await?;
+
+    Ok(ReadDir {
+        state: State::Idle(Some((VecDeque::new(), std, true))),
+    })
+}
+
+/// Stream over the entries of a directory, created by `read_dir`.
+pub struct ReadDir {
+    state: State,
+}
+
+impl ReadDir {
+    /// Returns the next entry, `None` once all entries were returned.
+    pub async fn next_entry(&mut self) -> io::Result<Option<DirEntry>> {
+        poll_fn(|cx| self.poll_next_entry(cx)).await
+    }
+
+    /// Polls for the next entry, `None` once all entries were returned.
+    pub fn poll_next_entry(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<DirEntry>>> {
+        loop {
+            match self.state {
+                State::Idle(ref mut chunk) => {
+                    // The iterator went away if the runtime shut down while fetching.
+                    let Some((mut entries, mut std, more)) = chunk.take() else {
+                        return Poll::Ready(Err(shut_down()));
+                    };
+
+                    if let Some(entry) = entries.pop_front() {
+                        *chunk = Some((entries, std, more));
+                        return Poll::Ready(entry.map(Some));
+                    }
+
+                    if !more {
+                        *chunk = Some((entries, std, more));
+                        return Poll::Ready(Ok(None));
+                    }
+
+                    let handle = Executor::get();
+                    self.state = State::Busy(handle.spawn_blocking(move || {
+                        let more = next_chunk(&mut entries, &mut std);
+                        (entries, std, more)
+                    }));
+                }
+
+                State::Busy(ref mut task) => match task.poll_unless_cancelled(cx) {
+                    Poll::Ready(Some(chunk)) => self.state = State::Idle(Some(chunk)),
+                    Poll::Ready(None) => {
+                        self.state = State::Idle(None);
+                        return Poll::Ready(Err(shut_down()));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
+
+fn next_chunk(entries: &mut VecDeque<io::Result<DirEntry>>, std: &mut fs::ReadDir) -> bool {
+    for _ in 0..CHUNK_SIZE {
+        match std.next() {
+            Some(entry) => entries.push_back(entry.map(|std| DirEntry { std: Arc::new(std) })),
+            None => return false,
+        }
+    }
+
+    true
+}
+
+impl Stream for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .poll_next_entry(cx)
+            .map(|entry| entry.transpose())
+    }
+}
+
+/// Entry of a directory, returned by `ReadDir`.
+pub struct DirEntry {
+    std: Arc<fs::DirEntry>,
+}
+
+impl DirEntry {
+    /// Returns the full path of the entry.
+    pub fn path(&self) -> PathBuf {
+        self.std.path()
+    }
+
+    /// Returns the name of the entry, without the leading path.
+    pub fn file_name(&self) -> OsString {
+        self.std.file_name()
+    }
+
+    /// Queries metadata about the entry, without following symlinks.
+    pub async fn metadata(&self) -> io::Result<fs::Metadata> {
+        let std = Arc::clone(&self.std);
+        asyncify(move || std.metadata()).await
+    }
+
+    /// Returns the type of the entry.
+    pub async fn file_type(&self) -> io::Result<fs::FileType> {
+        let std = Arc::clone(&self.std);
+        asyncify(move || std.file_type()).await
+    }
+}
@@ -1,13 +1,29 @@
+pub mod codec;
+pub mod compat;
+pub mod fs;
+#[doc(hidden)]
+pub mod macros;
+#[cfg(unix)]
+pub mod process;
 mod reactor;
 pub mod runtime;
-mod task;
+#[cfg(unix)]
+pub mod signal;
+pub mod sync;
+pub mod task;
+mod trace;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
 
 pub use reactor::io;
 pub use runtime::Executor;
 
+#[cfg(feature = "macros")]
+pub use lamp_macros::{main, test};
+
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::{Executor, io};
     use log::{Level, Metadata, Record};
 
     struct Logger;
@@ -0,0 +1,126 @@
+/// Waits on several futures at once, returning all of their outputs.
+///
+/// The futures are polled by the current task, no task is spawned.
+/// Returns a tuple with the outputs in the order the futures are written.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (a, b) = lamp::join!(fetch("a"), fetch("b"));
+/// ```
+#[macro_export]
+macro_rules! join {
+    (@index [$($done:tt)*] [$($path:tt)*] []) => {
+        $crate::join!(@emit $($done)*)
+    };
+    (@index [$($done:tt)*] [$($path:tt)*] [$fut:expr $(, $($rest:tt)*)?]) => {
+        $crate::join!(@index [$($done)* [[$($path)*] ($fut)]] [$($path)* . 1] [$($($rest)*)?])
+    };
+
+    (@emit $([[$($path:tt)*] ($fut:expr)])*) => {{
+        let mut __futures = $crate::join!(@futures $(($fut))*);
+        let mut __futures = $crate::macros::support::pin!(__futures);
+
+        $crate::macros::support::poll_fn(|__cx| {
+            let mut __done = true;
+            $(
+                // Safety: the tuple is pinned, its fields are never moved.
+                let __fut = unsafe {
+                    $crate::macros::support::project(__futures.as_mut(), |__f| &mut __f $($path)* . 0)
+                };
+                __done &= __fut.poll_done(__cx);
+            )*
+
+            if !__done {
+                return ::core::task::Poll::Pending;
+            }
+
+            ::core::task::Poll::Ready(($(
+                // Safety: as above.
+                unsafe {
+                    $crate::macros::support::project(__futures.as_mut(), |__f| &mut __f $($path)* . 0)
+                }
+                .take_output(),
+            )*))
+        })
+        .await
+    }};
+
+    (@futures) => {
+        ()
+    };
+    (@futures ($fut:expr) $($rest:tt)*) => {
+        (
+            $crate::macros::support::maybe_done($fut),
+            $crate::join!(@futures $($rest)*),
+        )
+    };
+
+    ($($futures:tt)*) => {
+        $crate::join!(@index [] [] [$($futures)*])
+    };
+}
+
+/// Waits on several futures returning `Result`, stopping at the first error.
+///
+/// Returns `Ok` with a tuple of the values in the order the futures are written,
+/// or the first error, dropping the futures still running.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (a, b) = lamp::try_join!(read("a"), read("b"))?;
+/// ```
+#[macro_export]
+macro_rules! try_join {
+    (@index [$($done:tt)*] [$($path:tt)*] []) => {
+        $crate::try_join!(@emit $($done)*)
+    };
+    (@index [$($done:tt)*] [$($path:tt)*] [$fut:expr $(, $($rest:tt)*)?]) => {
+        $crate::try_join!(@index [$($done)* [[$($path)*] ($fut)]] [$($path)* . 1] [$($($rest)*)?])
+    };
+
+    (@emit $([[$($path:tt)*] ($fut:expr)])*) => {{
+        let mut __futures = $crate::join!(@futures $(($fut))*);
+        let mut __futures = $crate::macros::support::pin!(__futures);
+
+        $crate::macros::support::poll_fn(|__cx| {
+            let mut __done = true;
+            $(
+                // Safety: the tuple is pinned, its fields are never moved.
+                let mut __fut = unsafe {
+                    $crate::macros::support::project(__futures.as_mut(), |__f| &mut __f $($path)* . 0)
+                };
+
+                if __fut.as_mut().poll_done(__cx) {
+                    if let $crate::macros::support::Some(e) = __fut.take_err() {
+                        return ::core::task::Poll::Ready($crate::macros::support::Err(e));
+                    }
+                } else {
+                    __done = false;
+                }
+            )*
+
+            if !__done {
+                return ::core::task::Poll::Pending;
+            }
+
+            ::core::task::Poll::Ready($crate::macros::support::Ok(($(
+                // Safety: as above.
+                match unsafe {
+                    $crate::macros::support::project(__futures.as_mut(), |__f| &mut __f $($path)* . 0)
+                }
+                .take_output()
+                {
+                    $crate::macros::support::Ok(value) => value,
+                    $crate::macros::support::Err(_) => ::core::unreachable!(),
+                },
+            )*)))
+        })
+        .await
+    }};
+
+    ($($futures:tt)*) => {
+        $crate::try_join!(@index [] [] [$($futures)*])
+    };
+}
@@ -0,0 +1,207 @@
+//! Macros for running futures concurrently within a task.
+
+mod join;
+mod select;
+
+#[doc(hidden)]
+pub mod support;
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{AsyncReadExt, AsyncWriteExt, duplex};
+    use crate::sync::{mpsc, oneshot};
+    use futures::executor::block_on;
+    use futures::future::{pending, ready};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn select_first_ready() {
+        block_on(async {
+            let dropped = Arc::new(AtomicBool::new(false));
+            let flag = DropFlag(Arc::clone(&dropped));
+
+            let out = crate::select! {
+                _ = async move { let _flag = flag; pending::<()>().await } => 0,
+                v = ready(1) => {
+                    // The losing branch is gone before the handler runs.
+                    assert!(dropped.load(Ordering::SeqCst));
+                    v
+                }
+            };
+            assert_eq!(out, 1);
+
+            let out = crate::select! {
+                biased;
+                a = ready("a") => a,
+                b = ready("b") => b,
+            };
+            assert_eq!(out, "a");
+
+            let out = crate::select! {
+                Ok((a, mut b)) | Err((a, mut b)) = ready(Err::<(u8, u8), _>((1, 2))) => {
+                    b += a;
+                    b
+                }
+            };
+            assert_eq!(out, 3);
+        })
+    }
+
+    #[test]
+    fn select_disabled_branches() {
+        block_on(async {
+            let (tx, mut rx) = mpsc::unbounded_channel::<u8>();
+            drop(tx);
+
+            // `None` doesn't match the pattern, the precondition disables the other.
+            let out = crate::select! {
+                Some(mut v) = rx.recv() => { v += 1; v }
+                _ = ready(()), if false => 0,
+                else => 7,
+            };
+            assert_eq!(out, 7);
+
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            for i in 0..3 {
+                tx.send(i).unwrap();
+            }
+            drop(tx);
+
+            let mut sum = 0;
+            loop {
+                crate::select! {
+                    Some(v) = rx.recv() => {
+                        if v == 1 {
+                            continue;
+                        }
+                        sum += v;
+                    }
+                    else => break,
+                }
+            }
+            assert_eq!(sum, 2);
+        })
+    }
+
+    #[test]
+    #[should_panic(expected = "all branches are disabled")]
+    fn select_without_else() {
+        block_on(async {
+            crate::select! {
+                Some(()) = ready(None) => {}
+            }
+        })
+    }
+
+    #[test]
+    fn select_loop_is_fair() {
+        block_on(async {
+            let mut counts = [0; 2];
+            for _ in 0..200 {
+                crate::select! {
+                    _ = ready(()) => counts[0] += 1,
+                    _ = ready(()) => counts[1] += 1,
+                }
+            }
+
+            assert!(counts[0] > 0 && counts[1] > 0);
+        })
+    }
+
+    #[test]
+    fn read_and_write_are_cancel_safe() {
+        block_on(async {
+            let (mut a, mut b) = duplex(4);
+            let mut buf = [0u8; 4];
+
+            // A cancelled read takes nothing out of the stream.
+            crate::select! {
+                biased;
+                _ = b.read(&mut buf) => panic!("nothing was written"),
+                _ = ready(()) => {}
+            }
+            a.write(b"ab").await.unwrap();
+            assert_eq!(b.read(&mut buf).await.unwrap(), 2);
+            assert_eq!(&buf[..2], b"ab");
+
+            // A cancelled write puts nothing into the stream.
+            assert_eq!(a.write(b"abcd").await.unwrap(), 4);
+            crate::select! {
+                biased;
+                _ = a.write(b"ef") => panic!("the stream is full"),
+                _ = ready(()) => {}
+            }
+            assert_eq!(b.read(&mut buf).await.unwrap(), 4);
+            assert_eq!(&buf, b"abcd");
+        })
+    }
+
+    #[test]
+    fn join_all() {
+        block_on(async {
+            let (tx, rx) = oneshot::channel();
+
+            let (a, (), b) = crate::join!(rx, async { tx.send(1).unwrap() }, ready("b"));
+            assert_eq!((a.unwrap(), b), (1, "b"));
+            assert_eq!(crate::join!(), ());
+        })
+    }
+
+    #[test]
+    fn try_join_first_error() {
+        block_on(async {
+            let res = crate::try_join!(ready(Ok::<_, ()>(1)), async { Ok(2) });
+            assert_eq!(res, Ok((1, 2)));
+
+            let dropped = Arc::new(AtomicBool::new(false));
+            let flag = DropFlag(Arc::clone(&dropped));
+            let res: Result<((), u8), &str> = crate::try_join!(
+                async move {
+                    let _flag = flag;
+                    pending().await
+                },
+                ready(Err("failed")),
+            );
+
+            assert_eq!(res, Err("failed"));
+            assert!(dropped.load(Ordering::SeqCst));
+        })
+    }
+
+    #[cfg(all(feature = "macros", not(miri)))]
+    #[crate::test(crate = "crate")]
+    async fn test_attribute() -> Result<(), String> {
+        let handle = crate::Executor::spawn(async { 1 });
+        assert_eq!(handle.await, 1);
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "macros", not(miri)))]
+    #[crate::test(crate = "crate", flavor = "multi_thread", workers = 1)]
+    #[should_panic(expected = "from the body")]
+    async fn test_attribute_panics() {
+        panic!("from the body");
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn block_on_returns_output() {
+        let out = super::support::block_on(Some(1), async {
+            let (tx, rx) = oneshot::channel();
+            crate::Executor::spawn(async move { tx.send(5).unwrap() });
+            rx.await.unwrap()
+        });
+
+        assert_eq!(out, 5);
+    }
+}
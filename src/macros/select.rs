@@ -0,0 +1,271 @@
+/// Waits on several futures at once, running the handler of the first one to complete.
+///
+/// ```text
+/// lamp::select! {
+///     <pattern> = <future>, if <precondition> => <handler>,
+///     ...
+///     else => <handler>,
+/// }
+/// ```
+///
+/// Every `<future>` is created up front, and all of them are polled by the current task.
+/// Once one completes with an output matching its `<pattern>`, the others are dropped
+/// and the handler runs with the bindings of the pattern.
+/// The handlers run outside of `select!`, so they can use `.await`, `break`, `continue`
+/// and `return` as if written in place.
+///
+/// A branch is disabled when its `if <precondition>` is false, or when its output
+/// doesn't match its pattern. Once every branch is disabled, the `else` handler runs,
+/// and `select!` panics if there is none.
+///
+/// Branches are polled starting from a random one, so that a future which is always
+/// ready can't starve the others. Starting the block with `biased;` polls them in the
+/// order they are written instead.
+///
+/// Losing futures are dropped without being polled to completion, so `select!` in a loop
+/// should only be used with cancel safe futures, like `read` and `write` on lamp's I/O
+/// types or `recv` on its channels.
+///
+/// # Panics
+///
+/// Panics if every branch is disabled and there is no `else` handler.
+///
+/// # Examples
+///
+/// ```ignore
+/// loop {
+///     lamp::select! {
+///         Some(msg) = rx.recv() => handle(msg).await,
+///         _ = token.cancelled() => break,
+///         else => break,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! select {
+    // Parses the branches into
+    // `[ (pattern tokens) (future) (precondition) { handler } ]`.
+    (@parse $biased:tt [$($branches:tt)*] [$($else:tt)*]) => {
+        $crate::select!(@index $biased [$($else)*] [] [] [] [$($branches)*])
+    };
+    (@parse $biased:tt $branches:tt [] else => $handler:block, $($rest:tt)*) => {
+        $crate::select!(@parse $biased $branches [$handler] $($rest)*)
+    };
+    (@parse $biased:tt $branches:tt [] else => $handler:block $($rest:tt)*) => {
+        $crate::select!(@parse $biased $branches [$handler] $($rest)*)
+    };
+    (@parse $biased:tt $branches:tt [] else => $handler:expr $(, $($rest:tt)*)?) => {
+        $crate::select!(@parse $biased $branches [{ $handler }] $($($rest)*)?)
+    };
+    (@parse $biased:tt $branches:tt $else:tt $($rest:tt)+) => {
+        $crate::select!(@pattern $biased $branches $else [] $($rest)+)
+    };
+
+    // Collects the pattern up to the `=` before the future, a few tokens at a time.
+    (@pattern $biased:tt $branches:tt $else:tt [$($pat:tt)*] = $($rest:tt)*) => {
+        $crate::select!(@future $biased $branches $else [$($pat)*] $($rest)*)
+    };
+    (@pattern $biased:tt $branches:tt $else:tt [$($pat:tt)*] $a:tt = $($rest:tt)*) => {
+        $crate::select!(@future $biased $branches $else [$($pat)* $a] $($rest)*)
+    };
+    (@pattern $biased:tt $branches:tt $else:tt [$($pat:tt)*] $a:tt $b:tt = $($rest:tt)*) => {
+        $crate::select!(@future $biased $branches $else [$($pat)* $a $b] $($rest)*)
+    };
+    (@pattern $biased:tt $branches:tt $else:tt [$($pat:tt)*] $a:tt $b:tt $c:tt = $($rest:tt)*) => {
+        $crate::select!(@future $biased $branches $else [$($pat)* $a $b $c] $($rest)*)
+    };
+    (@pattern $biased:tt $branches:tt $else:tt [$($pat:tt)*] $a:tt $b:tt $c:tt $($rest:tt)*) => {
+        $crate::select!(@pattern $biased $branches $else [$($pat)* $a $b $c] $($rest)*)
+    };
+    (@pattern $biased:tt $branches:tt $else:tt [$($pat:tt)*] $($rest:tt)*) => {
+        ::core::compile_error!("expected `<pattern> = <future> => <handler>` in select!")
+    };
+
+    (@future $biased:tt $branches:tt $else:tt $pat:tt $fut:expr, if $cond:expr => $($rest:tt)*) => {
+        $crate::select!(@handler $biased $branches $else [$pat ($fut) ($cond)] $($rest)*)
+    };
+    (@future $biased:tt $branches:tt $else:tt $pat:tt $fut:expr => $($rest:tt)*) => {
+        $crate::select!(@handler $biased $branches $else [$pat ($fut) (true)] $($rest)*)
+    };
+
+    (@handler $biased:tt [$($branches:tt)*] $else:tt [$($branch:tt)*] $handler:block, $($rest:tt)*) => {
+        $crate::select!(@parse $biased [$($branches)* [$($branch)* $handler]] $else $($rest)*)
+    };
+    (@handler $biased:tt [$($branches:tt)*] $else:tt [$($branch:tt)*] $handler:block $($rest:tt)*) => {
+        $crate::select!(@parse $biased [$($branches)* [$($branch)* $handler]] $else $($rest)*)
+    };
+    (@handler $biased:tt [$($branches:tt)*] $else:tt [$($branch:tt)*] $handler:expr $(, $($rest:tt)*)?) => {
+        $crate::select!(
+            @parse $biased [$($branches)* [$($branch)* { $handler }]] $else $($($rest)*)?
+        )
+    };
+
+    // Gives every branch its tuple field path and its index, as `+ 1` tokens.
+    (@index $biased:tt $else:tt [$($done:tt)*] [$($path:tt)*] [$($index:tt)*] []) => {
+        $crate::select!(@emit $biased $else [$($index)*] $($done)*)
+    };
+    (@index $biased:tt $else:tt [$($done:tt)*] [$($path:tt)*] [$($index:tt)*] [$branch:tt $($rest:tt)*]) => {
+        $crate::select!(
+            @index $biased $else
+            [$($done)* [[$($path)*] [$($index)*] $branch]]
+            [$($path)* . 1]
+            [$($index)* + 1]
+            [$($rest)*]
+        )
+    };
+
+    (@emit [$biased:literal] [$($else:block)?] [$($count:tt)*] $(
+        [[$($path:tt)*] [$($index:tt)*] [[$($pat:tt)*] ($fut:expr) ($cond:expr) $handler:block]]
+    )*) => {{
+        let __count: u32 = 0 $($count)*;
+        let mut __disabled: u64 = 0;
+        $(
+            if !$cond {
+                __disabled |= 1 << (0 $($index)*);
+            }
+        )*
+
+        // The losing futures are dropped at the end of the block, before the handler runs.
+        let __out = {
+            let mut __futures = $crate::select!(@futures $([$fut])*);
+            let mut __futures = $crate::macros::support::pin!(__futures);
+
+            $crate::macros::support::poll_fn(|__cx| {
+                let __start = if $biased {
+                    0
+                } else {
+                    $crate::macros::support::random(__count)
+                };
+
+                for __i in 0..__count {
+                    let __branch = (__start + __i) % __count;
+                    $(
+                        if __branch == 0 $($index)* && __disabled & (1 << (0 $($index)*)) == 0 {
+                            // Safety: the tuple is pinned, its fields are never moved.
+                            let __fut = unsafe {
+                                $crate::macros::support::project(
+                                    __futures.as_mut(),
+                                    |__f| &mut __f $($path)* . 0,
+                                )
+                            };
+
+                            if let ::core::task::Poll::Ready(__value) =
+                                $crate::macros::support::poll(__fut, __cx)
+                            {
+                                __disabled |= 1 << (0 $($index)*);
+
+                                #[allow(unused_variables, unreachable_patterns)]
+                                let __matches = match &__value {
+                                    $crate::select!(@clean [] [] [$($pat)*]) => true,
+                                    _ => false,
+                                };
+
+                                if __matches {
+                                    return ::core::task::Poll::Ready($crate::macros::support::Some(
+                                        $crate::select!(@wrap [$($index)*] __value),
+                                    ));
+                                }
+                            }
+                        }
+                    )*
+                }
+
+                if __disabled == $crate::macros::support::mask(__count) {
+                    ::core::task::Poll::Ready($crate::macros::support::None)
+                } else {
+                    ::core::task::Poll::Pending
+                }
+            })
+            .await
+        };
+
+        match __out {
+            $(
+                $crate::macros::support::Some($crate::select!(@wrap [$($index)*] $($pat)*)) => $handler,
+            )*
+            $crate::macros::support::None => {
+                $crate::select!(@else $($else)?)
+            }
+            // Pins down the type past the last branch.
+            #[allow(unreachable_patterns)]
+            $crate::macros::support::Some($crate::select!(@nest [$($count)*] __never)) => {
+                $crate::macros::support::never(__never)
+            }
+            #[allow(unreachable_patterns)]
+            _ => ::core::unreachable!("select! output not matching its pattern"),
+        }
+    }};
+
+    (@else) => {
+        ::core::panic!("all branches are disabled and there is no else branch")
+    };
+    (@else $else:block) => {
+        $else
+    };
+
+    (@futures) => {
+        ()
+    };
+    (@futures [$fut:expr] $($rest:tt)*) => {
+        (
+            ::core::future::IntoFuture::into_future($fut),
+            $crate::select!(@futures $($rest)*),
+        )
+    };
+
+    // Wraps a value or pattern in `Out::Next` once per `+ 1`.
+    (@wrap [] $($inner:tt)*) => {
+        $crate::macros::support::Out::Branch($($inner)*)
+    };
+    (@wrap [+ 1 $($index:tt)*] $($inner:tt)*) => {
+        $crate::macros::support::Out::Next($crate::select!(@wrap [$($index)*] $($inner)*))
+    };
+
+    (@nest [] $($inner:tt)*) => {
+        $($inner)*
+    };
+    (@nest [+ 1 $($index:tt)*] $($inner:tt)*) => {
+        $crate::macros::support::Out::Next($crate::select!(@nest [$($index)*] $($inner)*))
+    };
+
+    // Strips `mut` and `ref` from a pattern, so it can be checked against a reference
+    // to the output. Nested groups are cleaned by pushing a frame holding the tokens
+    // around them.
+    (@clean [] [$($cur:tt)*] []) => {
+        $($cur)*
+    };
+    (@clean [(paren [$($prev:tt)*] [$($rest:tt)*]) $($stack:tt)*] [$($cur:tt)*] []) => {
+        $crate::select!(@clean [$($stack)*] [$($prev)* ($($cur)*)] [$($rest)*])
+    };
+    (@clean [(bracket [$($prev:tt)*] [$($rest:tt)*]) $($stack:tt)*] [$($cur:tt)*] []) => {
+        $crate::select!(@clean [$($stack)*] [$($prev)* [$($cur)*]] [$($rest)*])
+    };
+    (@clean [(brace [$($prev:tt)*] [$($rest:tt)*]) $($stack:tt)*] [$($cur:tt)*] []) => {
+        $crate::select!(@clean [$($stack)*] [$($prev)* {$($cur)*}] [$($rest)*])
+    };
+    (@clean $stack:tt $cur:tt [mut $($rest:tt)*]) => {
+        $crate::select!(@clean $stack $cur [$($rest)*])
+    };
+    (@clean $stack:tt $cur:tt [ref $($rest:tt)*]) => {
+        $crate::select!(@clean $stack $cur [$($rest)*])
+    };
+    (@clean [$($stack:tt)*] $cur:tt [($($group:tt)*) $($rest:tt)*]) => {
+        $crate::select!(@clean [(paren $cur [$($rest)*]) $($stack)*] [] [$($group)*])
+    };
+    (@clean [$($stack:tt)*] $cur:tt [[$($group:tt)*] $($rest:tt)*]) => {
+        $crate::select!(@clean [(bracket $cur [$($rest)*]) $($stack)*] [] [$($group)*])
+    };
+    (@clean [$($stack:tt)*] $cur:tt [{$($group:tt)*} $($rest:tt)*]) => {
+        $crate::select!(@clean [(brace $cur [$($rest)*]) $($stack)*] [] [$($group)*])
+    };
+    (@clean $stack:tt [$($cur:tt)*] [$token:tt $($rest:tt)*]) => {
+        $crate::select!(@clean $stack [$($cur)* $token] [$($rest)*])
+    };
+
+    (biased; $($branches:tt)*) => {
+        $crate::select!(@parse [true] [] [] $($branches)*)
+    };
+    ($($branches:tt)*) => {
+        $crate::select!(@parse [false] [] [] $($branches)*)
+    };
+}
@@ -0,0 +1,173 @@
+// Items used by the expansions of the macros, not part of the public API.
+
+use crate::Executor;
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::future::{Future, IntoFuture};
+use std::hash::{BuildHasher, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::task::{Context, Poll};
+
+pub use std::future::poll_fn;
+pub use std::option::Option::{self, None, Some};
+pub use std::pin::pin;
+pub use std::result::Result::{Err, Ok};
+
+/// Output of `select!`, nested once per branch before the completed one.
+pub enum Out<T, U> {
+    Branch(T),
+    Next(U),
+}
+
+/// Type past the last branch of `Out`.
+pub enum Never {}
+
+pub fn never(never: Never) -> ! {
+    match never {}
+}
+
+/// Projects to a field of a pinned tuple of futures.
+///
+/// # Safety
+///
+/// `field` must return a field of the tuple, which is never moved.
+pub unsafe fn project<T, F>(
+    tuple: Pin<&mut T>,
+    field: impl FnOnce(&mut T) -> &mut F,
+) -> Pin<&mut F> {
+    // Safety: guaranteed by the caller.
+    unsafe { Pin::map_unchecked_mut(tuple, field) }
+}
+
+pub fn poll<F: Future>(fut: Pin<&mut F>, cx: &mut Context<'_>) -> Poll<F::Output> {
+    fut.poll(cx)
+}
+
+/// Mask with a bit set for each of `branches`.
+pub fn mask(branches: u32) -> u64 {
+    assert!(branches <= 64, "select! supports up to 64 branches");
+    u64::MAX.checked_shr(64 - branches).unwrap_or(0)
+}
+
+/// Random number below `n`, for picking the first branch to poll.
+pub fn random(n: u32) -> u32 {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new({
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u8(0);
+            hasher.finish() | 1
+        });
+    }
+
+    if n == 0 {
+        return 0;
+    }
+
+    // xorshift64*
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+
+        (x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32) as u32 % n
+    })
+}
+
+/// Future polled by `join!` and `try_join!`, keeping its output once done.
+pub enum MaybeDone<F: Future> {
+    Future(F),
+    Done(F::Output),
+    Gone,
+}
+
+pub fn maybe_done<F: IntoFuture>(fut: F) -> MaybeDone<F::IntoFuture> {
+    MaybeDone::Future(fut.into_future())
+}
+
+impl<F: Future> MaybeDone<F> {
+    /// Polls the future if still running, returning whether it is done.
+    pub fn poll_done(self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+        // Safety: the future is never moved out of the pinned enum,
+        // and the output isn't structurally pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        if let MaybeDone::Future(fut) = this {
+            match unsafe { Pin::new_unchecked(fut) }.poll(cx) {
+                Poll::Ready(out) => *this = MaybeDone::Done(out),
+                Poll::Pending => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Takes the output of a done future.
+    pub fn take_output(self: Pin<&mut Self>) -> F::Output {
+        // Safety: the future was already dropped once done.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        match std::mem::replace(this, MaybeDone::Gone) {
+            MaybeDone::Done(out) => out,
+            _ => unreachable!("output taken from an unfinished future"),
+        }
+    }
+}
+
+impl<T, E, F: Future<Output = Result<T, E>>> MaybeDone<F> {
+    /// Takes the error of a future which failed.
+    pub fn take_err(self: Pin<&mut Self>) -> Option<E> {
+        // Safety: as in `take_output`.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        match this {
+            MaybeDone::Done(Err(_)) => match std::mem::replace(this, MaybeDone::Gone) {
+                MaybeDone::Done(Err(e)) => Some(e),
+                _ => unreachable!(),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Runs `fut` on a new runtime with `workers` threads, for `#[lamp::main]` and `#[lamp::test]`.
+///
+/// Shuts the runtime down before returning the output or resuming the panic of `fut`.
+pub fn block_on<F>(workers: Option<usize>, fut: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let workers = workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |amnt| amnt.get()));
+
+    let (tx, rx) = mpsc::channel();
+    let mut fut = Box::pin(fut);
+    let body = poll_fn(move |cx| {
+        match panic::catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+            Ok(Poll::Pending) => return Poll::Pending,
+            Ok(Poll::Ready(out)) => {
+                let _ = tx.send(Ok(out));
+            }
+            Err(payload) => {
+                let _ = tx.send(Err(payload));
+            }
+        }
+
+        Poll::Ready(())
+    });
+
+    let mut exec = Executor::new(workers);
+    let res = exec.block_on(body);
+    exec.shutdown();
+
+    match (res, rx.try_recv()) {
+        (Ok(()), Ok(Ok(out))) => out,
+        (Ok(()), Ok(Err(payload))) => panic::resume_unwind(payload),
+        _ => panic!("runtime shutdown abruptly due to an error"),
+    }
+}
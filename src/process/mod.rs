@@ -0,0 +1,393 @@
+//! Spawning and waiting for child processes.
+//!
+//! `Command` mirrors `std::process::Command`, spawning a `Child`
+//! whose exit can be awaited and whose piped standard streams
+//! are registered in the reactor.
+//!
+//! Exited children are reaped through a pidfd on Linux,
+//! and through a SIGCHLD handler elsewhere, without a thread per child.
+
+mod pipe;
+mod reap;
+
+pub use pipe::{ChildStderr, ChildStdin, ChildStdout};
+pub use std::process::{ExitStatus, Output, Stdio};
+
+use crate::io::AsyncReadExt;
+use reap::Reaper;
+pub(crate) use reap::reap_orphans;
+
+use std::ffi::OsStr;
+use std::future::poll_fn;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::path::Path;
+use std::process;
+
+/// Builder for spawning a child process.
+///
+/// Configured like `std::process::Command`.
+#[derive(Debug)]
+pub struct Command {
+    std: process::Command,
+    kill_on_drop: bool,
+}
+
+impl Command {
+    /// Creates a command running `program`.
+    pub fn new(program: impl AsRef<OsStr>) -> Command {
+        Command {
+            std: process::Command::new(program),
+            kill_on_drop: false,
+        }
+    }
+
+    /// Adds an argument.
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Command {
+        self.std.arg(arg);
+        self
+    }
+
+    /// Adds multiple arguments.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    /// Sets an environment variable.
+    pub fn env(&mut self, key: impl AsRef<OsStr>, val: impl AsRef<OsStr>) -> &mut Command {
+        self.std.env(key, val);
+        self
+    }
+
+    /// Sets multiple environment variables.
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Command
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.envs(vars);
+        self
+    }
+
+    /// Removes an environment variable.
+    pub fn env_remove(&mut self, key: impl AsRef<OsStr>) -> &mut Command {
+        self.std.env_remove(key);
+        self
+    }
+
+    /// Clears the environment, the child won't inherit any variable.
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.std.env_clear();
+        self
+    }
+
+    /// Sets the working directory of the child.
+    pub fn current_dir(&mut self, dir: impl AsRef<Path>) -> &mut Command {
+        self.std.current_dir(dir);
+        self
+    }
+
+    /// Configures the child's standard input.
+    pub fn stdin(&mut self, cfg: impl Into<Stdio>) -> &mut Command {
+        self.std.stdin(cfg);
+        self
+    }
+
+    /// Configures the child's standard output.
+    pub fn stdout(&mut self, cfg: impl Into<Stdio>) -> &mut Command {
+        self.std.stdout(cfg);
+        self
+    }
+
+    /// Configures the child's standard error.
+    pub fn stderr(&mut self, cfg: impl Into<Stdio>) -> &mut Command {
+        self.std.stderr(cfg);
+        self
+    }
+
+    /// Kills the child if its `Child` handle is dropped before it exited.
+    ///
+    /// Off by default, the child keeps running like with the standard library.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Command {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Returns the underlying `std::process::Command`,
+    /// for options this builder doesn't expose.
+    pub fn as_std_mut(&mut self) -> &mut process::Command {
+        &mut self.std
+    }
+
+    /// Spawns the child.
+    ///
+    /// Must be called from within a runtime.
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        reap::reap_orphans();
+
+        let mut std = self.std.spawn()?;
+        let stdin = std.stdin.take().map(ChildStdin::from_std).transpose();
+        let stdout = std.stdout.take().map(ChildStdout::from_std).transpose();
+        let stderr = std.stderr.take().map(ChildStderr::from_std).transpose();
+        let reaper = Reaper::new(&std);
+
+        let mut child = Child {
+            std: ManuallyDrop::new(std),
+            reaper: None,
+            status: None,
+            kill_on_drop: self.kill_on_drop,
+            stdin: None,
+            stdout: None,
+            stderr: None,
+        };
+
+        // Dropping the child on error kills it, if asked to.
+        child.reaper = Some(reaper?);
+        child.stdin = stdin?;
+        child.stdout = stdout?;
+        child.stderr = stderr?;
+
+        Ok(child)
+    }
+
+    /// Spawns the child and waits for it to exit, collecting its output.
+    ///
+    /// Standard output and error are captured, standard input is closed.
+    pub async fn output(&mut self) -> io::Result<Output> {
+        self.std.stdin(Stdio::null());
+        self.std.stdout(Stdio::piped());
+        self.std.stderr(Stdio::piped());
+
+        self.spawn()?.wait_with_output().await
+    }
+
+    /// Spawns the child and waits for it to exit.
+    ///
+    /// The standard streams are inherited unless configured otherwise,
+    /// piped ones are closed right away.
+    pub async fn status(&mut self) -> io::Result<ExitStatus> {
+        let mut child = self.spawn()?;
+
+        child.stdin = None;
+        child.stdout = None;
+        child.stderr = None;
+
+        child.wait().await
+    }
+}
+
+/// Handle to a spawned child process.
+///
+/// Dropping it doesn't wait for or kill the child,
+/// unless `Command::kill_on_drop` was set.
+pub struct Child {
+    // Handed over to the orphan queue when dropped.
+    std: ManuallyDrop<process::Child>,
+    reaper: Option<Reaper>,
+    status: Option<ExitStatus>,
+    kill_on_drop: bool,
+
+    /// The child's standard input, if piped.
+    pub stdin: Option<ChildStdin>,
+
+    /// The child's standard output, if piped.
+    pub stdout: Option<ChildStdout>,
+
+    /// The child's standard error, if piped.
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    /// Returns the OS assigned process id.
+    pub fn id(&self) -> u32 {
+        self.std.id()
+    }
+
+    /// Waits for the child to exit.
+    ///
+    /// Closes the child's standard input first,
+    /// so a child reading it until EOF doesn't wait forever.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        drop(self.stdin.take());
+
+        if let Some(status) = self.status {
+            return Ok(status);
+        }
+
+        let reaper = self.reaper.as_mut().expect("child without a reaper");
+        let status = poll_fn(|cx| reaper.poll_wait(&mut self.std, cx)).await?;
+        self.status = Some(status);
+
+        Ok(status)
+    }
+
+    /// Returns the exit status if the child already exited, without waiting.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_none() {
+            self.status = self.std.try_wait()?;
+        }
+
+        Ok(self.status)
+    }
+
+    /// Sends SIGKILL to the child, without waiting for it to exit.
+    pub fn start_kill(&mut self) -> io::Result<()> {
+        match self.status {
+            Some(_) => Ok(()),
+            None => self.std.kill(),
+        }
+    }
+
+    /// Kills the child and waits for it to exit.
+    pub async fn kill(&mut self) -> io::Result<()> {
+        self.start_kill()?;
+        self.wait().await.map(|_| ())
+    }
+
+    /// Waits for the child to exit, collecting its standard output and error.
+    ///
+    /// Only streams configured as piped and not taken out of the `Child` are collected.
+    pub async fn wait_with_output(mut self) -> io::Result<Output> {
+        async fn read_all<R: crate::io::AsyncRead + Unpin>(io: Option<R>) -> io::Result<Vec<u8>> {
+            let mut buf = Vec::new();
+            if let Some(mut io) = io {
+                io.read_to_end(&mut buf).await?;
+            }
+
+            Ok(buf)
+        }
+
+        drop(self.stdin.take());
+        let stdout = read_all(self.stdout.take());
+        let stderr = read_all(self.stderr.take());
+
+        let (stdout, stderr, status) = futures::join!(stdout, stderr, self.wait());
+
+        Ok(Output {
+            status: status?,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        if self.kill_on_drop && self.try_wait().is_ok_and(|s| s.is_none()) {
+            let _ = self.std.kill();
+        }
+
+        // Safety: not used after this.
+        let std = unsafe { ManuallyDrop::take(&mut self.std) };
+
+        if self.status.is_none() {
+            reap::orphan(std);
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::*;
+    use crate::Executor;
+    use crate::io::AsyncWriteExt;
+    use crate::signal::Listener;
+
+    #[test]
+    fn output_and_status() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let out = Command::new("sh")
+                .args(["-c", "echo out; echo err >&2; exit 3"])
+                .output()
+                .await
+                .unwrap();
+
+            assert_eq!(out.status.code(), Some(3));
+            assert_eq!(out.stdout, b"out\n");
+            assert_eq!(out.stderr, b"err\n");
+
+            let status = Command::new("true").status().await.unwrap();
+            assert!(status.success());
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn piped_stdin() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let mut child = Command::new("cat")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+
+            let mut stdin = child.stdin.take().unwrap();
+            stdin.write_all(b"through the pipe").await.unwrap();
+            drop(stdin);
+
+            let out = child.wait_with_output().await.unwrap();
+            assert!(out.status.success());
+            assert_eq!(out.stdout, b"through the pipe");
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn kill_and_sigchld_fallback() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+            assert!(child.try_wait().unwrap().is_none());
+
+            child.kill().await.unwrap();
+            assert!(!child.try_wait().unwrap().unwrap().success());
+
+            // Waits through SIGCHLD instead of the pidfd.
+            let mut child = Command::new("sleep").arg("0.1").spawn().unwrap();
+            child.reaper = Some(Reaper::Signal(Listener::new(libc::SIGCHLD).unwrap()));
+            assert!(child.wait().await.unwrap().success());
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn dropped_child_is_reaped_on_exit() {
+        use std::time::{Duration, Instant};
+
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let child = Command::new("sleep").arg("0.1").spawn().unwrap();
+            let pid = child.id() as libc::pid_t;
+            drop(child);
+
+            // Signal 0 only checks that the process exists, zombies included.
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while unsafe { libc::kill(pid, 0) } == 0 {
+                assert!(Instant::now() < deadline, "dropped child left a zombie");
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+}
@@ -0,0 +1,128 @@
+use crate::io::{AsyncRead, AsyncWrite, FdSource, set_nonblocking};
+use crate::reactor::Direction;
+
+use mio::Interest;
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::pin::Pin;
+use std::process;
+use std::task::{Context, Poll};
+
+/// Pipe end registered in the reactor.
+struct Pipe {
+    io: FdSource<fs::File>,
+}
+
+impl Pipe {
+    fn new(fd: OwnedFd, interest: Interest) -> io::Result<Pipe> {
+        set_nonblocking(&fd)?;
+
+        Ok(Pipe {
+            io: FdSource::new(fs::File::from(fd), interest)?,
+        })
+    }
+
+    fn poll_read(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.io.poll_io(cx, Direction::Read, |mut f| f.read(buf))
+    }
+
+    fn poll_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.io.poll_io(cx, Direction::Write, |mut f| f.write(buf))
+    }
+}
+
+macro_rules! pipe_fd_impls {
+    ($($ty: ty),*) => {$(
+        impl AsRawFd for $ty {
+            fn as_raw_fd(&self) -> RawFd {
+                self.inner.io.get_ref().as_raw_fd()
+            }
+        }
+
+        impl AsFd for $ty {
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                self.inner.io.get_ref().as_fd()
+            }
+        }
+    )*};
+}
+
+/// Handle to a child's standard input.
+///
+/// Dropping it closes the pipe, signaling EOF to the child.
+pub struct ChildStdin {
+    inner: Pipe,
+}
+
+/// Handle to a child's standard output.
+pub struct ChildStdout {
+    inner: Pipe,
+}
+
+/// Handle to a child's standard error.
+pub struct ChildStderr {
+    inner: Pipe,
+}
+
+pipe_fd_impls!(ChildStdin, ChildStdout, ChildStderr);
+
+impl ChildStdin {
+    pub(crate) fn from_std(stdin: process::ChildStdin) -> io::Result<ChildStdin> {
+        Ok(ChildStdin {
+            inner: Pipe::new(stdin.into(), Interest::WRITABLE)?,
+        })
+    }
+}
+
+impl ChildStdout {
+    pub(crate) fn from_std(stdout: process::ChildStdout) -> io::Result<ChildStdout> {
+        Ok(ChildStdout {
+            inner: Pipe::new(stdout.into(), Interest::READABLE)?,
+        })
+    }
+}
+
+impl ChildStderr {
+    pub(crate) fn from_std(stderr: process::ChildStderr) -> io::Result<ChildStderr> {
+        Ok(ChildStderr {
+            inner: Pipe::new(stderr.into(), Interest::READABLE)?,
+        })
+    }
+}
+
+impl AsyncWrite for ChildStdin {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Pipes aren't buffered.
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for ChildStdout {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.inner.poll_read(cx, buf)
+    }
+}
+
+impl AsyncRead for ChildStderr {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.inner.poll_read(cx, buf)
+    }
+}
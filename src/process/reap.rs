@@ -0,0 +1,113 @@
+// Waiting for children to exit.
+//
+// On Linux a pidfd, which becomes readable once the child exits,
+// is registered in the reactor. Elsewhere, or on kernels without pidfd,
+// every SIGCHLD makes the waiting tasks check their child again.
+use crate::signal::{Listener, watch_orphans};
+
+#[cfg(target_os = "linux")]
+use crate::io::FdSource;
+#[cfg(target_os = "linux")]
+use crate::reactor::Direction;
+
+use std::io;
+use std::process::{self, ExitStatus};
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+/// Dropped children which had not exited yet.
+static ORPHANS: Mutex<Vec<process::Child>> = Mutex::new(Vec::new());
+
+/// Reaps the orphans which exited since the last call.
+pub(crate) fn reap_orphans() {
+    let mut orphans = ORPHANS.lock().unwrap_or_else(|e| e.into_inner());
+    orphans.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+}
+
+/// Hands a child which didn't exit yet over to be reaped later,
+/// so it doesn't linger as a zombie.
+///
+/// The reactor reaps it once SIGCHLD is delivered, outside of a runtime
+/// it is only reaped by the next spawn.
+pub(crate) fn orphan(child: process::Child) {
+    // Watched first, so that exiting right after is noticed.
+    if let Err(e) = watch_orphans() {
+        log::debug!("failed watching SIGCHLD for orphans: {e}");
+    }
+
+    ORPHANS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(child);
+
+    // It might have exited before the handler was installed.
+    reap_orphans();
+}
+
+/// Notifies a task when its child exits.
+pub(crate) enum Reaper {
+    #[cfg(target_os = "linux")]
+    Pidfd(FdSource<std::os::fd::OwnedFd>),
+    Signal(Listener),
+}
+
+impl Reaper {
+    pub(crate) fn new(child: &process::Child) -> io::Result<Reaper> {
+        #[cfg(target_os = "linux")]
+        if let Some(pidfd) = pidfd_open(child.id()) {
+            return Ok(Reaper::Pidfd(FdSource::new(
+                pidfd,
+                mio::Interest::READABLE,
+            )?));
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        let _ = child;
+
+        Ok(Reaper::Signal(Listener::new(libc::SIGCHLD)?))
+    }
+
+    /// Resolves once `child` exited.
+    pub(crate) fn poll_wait(
+        &mut self,
+        child: &mut process::Child,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<ExitStatus>> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Reaper::Pidfd(pidfd) => {
+                pidfd.poll_io(cx, Direction::Read, |_| match child.try_wait()? {
+                    Some(status) => Ok(status),
+                    None => Err(io::ErrorKind::WouldBlock.into()),
+                })
+            }
+
+            Reaper::Signal(listener) => loop {
+                // SIGCHLD is delivered to the process, not the task,
+                // it might be for any child.
+                if let Some(status) = child.try_wait()? {
+                    return Poll::Ready(Ok(status));
+                }
+
+                if listener.poll_recv(cx).is_pending() {
+                    return Poll::Pending;
+                }
+            },
+        }
+    }
+}
+
+/// Opens a pidfd for `pid`, `None` if the kernel doesn't support them.
+#[cfg(target_os = "linux")]
+fn pidfd_open(pid: u32) -> Option<std::os::fd::OwnedFd> {
+    use std::os::fd::FromRawFd;
+
+    // Safety: plain syscall, the result is checked.
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if fd < 0 {
+        return None;
+    }
+
+    // Safety: the kernel returned a new descriptor.
+    Some(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd as i32) })
+}
@@ -0,0 +1,137 @@
+use super::DEFAULT_BUF_SIZE;
+use crate::io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+use pin_project_lite::pin_project;
+
+use std::cmp;
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Adds buffering to a reader.
+    ///
+    /// Reads from the inner reader are done in large chunks,
+    /// so many small reads don't each cost a syscall.
+    pub struct BufReader<R> {
+        #[pin]
+        inner: R,
+        buf: Box<[u8]>,
+        pos: usize,
+        cap: usize,
+    }
+}
+
+impl<R: AsyncRead> BufReader<R> {
+    /// Creates a new `BufReader` with the default capacity of 8 KiB.
+    pub fn new(inner: R) -> BufReader<R> {
+        BufReader::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Creates a new `BufReader` with the specified capacity.
+    pub fn with_capacity(capacity: usize, inner: R) -> BufReader<R> {
+        BufReader {
+            inner,
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: 0,
+            cap: 0,
+        }
+    }
+}
+
+impl<R> BufReader<R> {
+    /// Obtains a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Obtains a mutable reference to the inner reader.
+    ///
+    /// Reading from it directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Obtains a pinned mutable reference to the inner reader.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    /// Returns the currently buffered data.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.cap]
+    }
+
+    /// Consumes the `BufReader`, returning the inner reader.
+    ///
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn discard_buffer(self: Pin<&mut Self>) {
+        let pinned = self.project();
+        *pinned.pos = 0;
+        *pinned.cap = 0;
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for BufReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        // Skip our buffer entirely for reads at least as big as it.
+        if self.pos == self.cap && buf.len() >= self.buf.len() {
+            let res = self.as_mut().get_pin_mut().poll_read(cx, buf);
+            self.discard_buffer();
+            return res;
+        }
+
+        let available = match self.as_mut().poll_fill_buf(cx) {
+            Poll::Ready(Ok(available)) => available,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let amnt = cmp::min(available.len(), buf.len());
+        buf[..amnt].copy_from_slice(&available[..amnt]);
+        self.consume(amnt);
+
+        Poll::Ready(Ok(amnt))
+    }
+}
+
+impl<R: AsyncRead> AsyncBufRead for BufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        let pinned = self.project();
+
+        if *pinned.pos >= *pinned.cap {
+            match pinned.inner.poll_read(cx, pinned.buf) {
+                Poll::Ready(Ok(read)) => *pinned.cap = read,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            *pinned.pos = 0;
+        }
+
+        Poll::Ready(Ok(&pinned.buf[*pinned.pos..*pinned.cap]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let pinned = self.project();
+        *pinned.pos = cmp::min(*pinned.pos + amt, *pinned.cap);
+    }
+}
+
+impl<R: AsyncRead + AsyncWrite> AsyncWrite for BufReader<R> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.get_pin_mut().poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_pin_mut().poll_flush(cx)
+    }
+}
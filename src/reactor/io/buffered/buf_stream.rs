@@ -0,0 +1,91 @@
+use super::{BufReader, BufWriter};
+use crate::io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+use pin_project_lite::pin_project;
+
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Adds buffering to both halves of a stream.
+    ///
+    /// Equivalent to wrapping the stream in a `BufWriter`
+    /// and that in a `BufReader`.
+    pub struct BufStream<S> {
+        #[pin]
+        inner: BufReader<BufWriter<S>>,
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> BufStream<S> {
+    /// Creates a new `BufStream` with the default capacities of 8 KiB.
+    pub fn new(stream: S) -> BufStream<S> {
+        BufStream {
+            inner: BufReader::new(BufWriter::new(stream)),
+        }
+    }
+
+    /// Creates a new `BufStream` with the specified capacities.
+    pub fn with_capacity(read_cap: usize, write_cap: usize, stream: S) -> BufStream<S> {
+        BufStream {
+            inner: BufReader::with_capacity(read_cap, BufWriter::with_capacity(write_cap, stream)),
+        }
+    }
+}
+
+impl<S> BufStream<S> {
+    /// Obtains a reference to the inner stream.
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref().get_ref()
+    }
+
+    /// Obtains a mutable reference to the inner stream.
+    ///
+    /// Using it directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut S {
+        self.inner.get_mut().get_mut()
+    }
+
+    /// Obtains a pinned mutable reference to the inner stream.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().inner.get_pin_mut().get_pin_mut()
+    }
+
+    /// Consumes the `BufStream`, returning the inner stream.
+    ///
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> S {
+        self.inner.into_inner().into_inner()
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> AsyncRead for BufStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> AsyncBufRead for BufStream<S> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        self.project().inner.poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.project().inner.consume(amt)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite> AsyncWrite for BufStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+}
@@ -0,0 +1,167 @@
+use super::DEFAULT_BUF_SIZE;
+use crate::io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+use pin_project_lite::pin_project;
+
+use std::io::{self, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Adds buffering to a writer.
+    ///
+    /// Small writes are collected and sent to the inner writer
+    /// once the buffer fills up or when flushing.
+    ///
+    /// Buffered data is lost if the writer is dropped without being flushed.
+    pub struct BufWriter<W> {
+        #[pin]
+        inner: W,
+        buf: Vec<u8>,
+        written: usize,
+    }
+}
+
+impl<W: AsyncWrite> BufWriter<W> {
+    /// Creates a new `BufWriter` with the default capacity of 8 KiB.
+    pub fn new(inner: W) -> BufWriter<W> {
+        BufWriter::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Creates a new `BufWriter` with the specified capacity.
+    pub fn with_capacity(capacity: usize, inner: W) -> BufWriter<W> {
+        BufWriter {
+            inner,
+            buf: Vec::with_capacity(capacity),
+            written: 0,
+        }
+    }
+
+    /// Writes the whole buffer into the inner writer.
+    fn poll_flush_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut pinned = self.project();
+        let len = pinned.buf.len();
+        let mut result = Ok(());
+
+        while *pinned.written < len {
+            match pinned
+                .inner
+                .as_mut()
+                .poll_write(cx, &pinned.buf[*pinned.written..])
+            {
+                Poll::Ready(Ok(0)) => {
+                    result = Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ));
+                    break;
+                }
+
+                Poll::Ready(Ok(n)) => *pinned.written += n,
+                Poll::Ready(Err(e)) => {
+                    result = Err(e);
+                    break;
+                }
+
+                Poll::Pending => break,
+            }
+        }
+
+        if *pinned.written > 0 {
+            pinned.buf.drain(..*pinned.written);
+        }
+        let pending = *pinned.written < len && result.is_ok();
+        *pinned.written = 0;
+
+        if pending {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(result)
+    }
+}
+
+impl<W> BufWriter<W> {
+    /// Obtains a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Obtains a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Obtains a pinned mutable reference to the inner writer.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().inner
+    }
+
+    /// Returns the currently buffered data.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Consumes the `BufWriter`, returning the inner writer.
+    ///
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for BufWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        if self.buf.len() + buf.len() > self.buf.capacity() {
+            match self.as_mut().poll_flush_buf(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        // Writes that don't fit in the buffer go straight to the inner writer.
+        if buf.len() >= self.buf.capacity() {
+            return self.get_pin_mut().poll_write(cx, buf);
+        }
+
+        self.project().buf.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.as_mut().poll_flush_buf(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        self.get_pin_mut().poll_flush(cx)
+    }
+}
+
+impl<W: AsyncWrite + AsyncRead> AsyncRead for BufWriter<W> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.get_pin_mut().poll_read(cx, buf)
+    }
+}
+
+impl<W: AsyncWrite + AsyncBufRead> AsyncBufRead for BufWriter<W> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        self.get_pin_mut().poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_pin_mut().consume(amt)
+    }
+}
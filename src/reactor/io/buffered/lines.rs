@@ -0,0 +1,81 @@
+use crate::io::AsyncBufRead;
+use crate::io::read_until_internal;
+
+use futures::Stream;
+use pin_project_lite::pin_project;
+
+use std::io::{self, Result};
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Stream of the lines in a reader.
+    ///
+    /// Created by `AsyncBufReadExt::lines`.
+    pub struct Lines<R> {
+        #[pin]
+        reader: R,
+        buf: Vec<u8>,
+        read: usize,
+    }
+}
+
+impl<R> Lines<R> {
+    pub(crate) fn new(reader: R) -> Lines<R> {
+        Lines {
+            reader,
+            buf: Vec::new(),
+            read: 0,
+        }
+    }
+
+    /// Consumes the stream, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncBufRead> Lines<R> {
+    /// Polls for the next line, `None` means EOF.
+    pub fn poll_next_line(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<String>>> {
+        let pinned = self.project();
+
+        let read = match read_until_internal(pinned.reader, cx, b'\n', pinned.buf, pinned.read) {
+            Poll::Ready(Ok(read)) => read,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        if read == 0 && pinned.buf.is_empty() {
+            return Poll::Ready(Ok(None));
+        }
+
+        if pinned.buf.ends_with(b"\n") {
+            pinned.buf.pop();
+
+            if pinned.buf.ends_with(b"\r") {
+                pinned.buf.pop();
+            }
+        }
+
+        match String::from_utf8(mem::take(pinned.buf)) {
+            Ok(line) => Poll::Ready(Ok(Some(line))),
+            Err(_) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            ))),
+        }
+    }
+}
+
+impl<R: AsyncBufRead> Stream for Lines<R> {
+    type Item = Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_line(cx).map(Result::transpose)
+    }
+}
@@ -0,0 +1,157 @@
+mod buf_reader;
+mod buf_stream;
+mod buf_writer;
+mod lines;
+mod split;
+
+pub use buf_reader::BufReader;
+pub use buf_stream::BufStream;
+pub use buf_writer::BufWriter;
+pub use lines::Lines;
+pub use split::Split;
+
+/// Default capacity of the buffers, 8 KiB.
+const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    #[test]
+    fn read_line_and_until() {
+        block_on(async {
+            let mut reader = BufReader::with_capacity(4, &b"hello\nworld\r\nend"[..]);
+            let mut line = String::new();
+
+            assert_eq!(reader.read_line(&mut line).await.unwrap(), 6);
+            assert_eq!(line, "hello\n");
+
+            let mut bytes = Vec::new();
+            assert_eq!(reader.read_until(b'\r', &mut bytes).await.unwrap(), 6);
+            assert_eq!(bytes, b"world\r");
+
+            line.clear();
+            assert_eq!(reader.read_line(&mut line).await.unwrap(), 1);
+            assert_eq!(reader.read_line(&mut line).await.unwrap(), 3);
+            assert_eq!(line, "\nend");
+            assert_eq!(reader.read_line(&mut line).await.unwrap(), 0);
+        })
+    }
+
+    #[test]
+    fn read_line_invalid_utf8() {
+        block_on(async {
+            let mut reader = BufReader::new(&b"ok\n\xff\xfe\n"[..]);
+            let mut line = String::new();
+
+            reader.read_line(&mut line).await.unwrap();
+            assert!(reader.read_line(&mut line).await.is_err());
+            assert_eq!(line, "ok\n", "buffer was modified on error");
+        })
+    }
+
+    #[test]
+    fn lines_and_split() {
+        block_on(async {
+            let reader = BufReader::with_capacity(3, &b"one\r\ntwo\n\nthree"[..]);
+            let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect().await;
+            assert_eq!(lines, ["one", "two", "", "three"]);
+
+            let split: Vec<Vec<u8>> = AsyncBufReadExt::split(&b"a,bc,,d,"[..], b',')
+                .map(|s| s.unwrap())
+                .collect()
+                .await;
+            assert_eq!(split, [&b"a"[..], b"bc", b"", b"d"]);
+        })
+    }
+
+    #[test]
+    fn buf_reader_bypasses_buffer_for_large_reads() {
+        block_on(async {
+            let mut reader = BufReader::with_capacity(2, &b"abcdef"[..]);
+            let mut buf = [0u8; 4];
+
+            assert_eq!(reader.read(&mut buf).await.unwrap(), 4);
+            assert_eq!(&buf, b"abcd");
+            assert!(reader.buffer().is_empty());
+
+            assert_eq!(reader.read(&mut buf[..1]).await.unwrap(), 1);
+            assert_eq!(reader.buffer(), b"f");
+        })
+    }
+
+    #[test]
+    fn buf_writer_holds_until_flush() {
+        block_on(async {
+            let mut writer = BufWriter::with_capacity(8, Vec::new());
+
+            writer.write(b"abc").await.unwrap();
+            writer.write(b"def").await.unwrap();
+            assert!(writer.get_ref().is_empty());
+            assert_eq!(writer.buffer(), b"abcdef");
+
+            // Doesn't fit, so the buffer is flushed first.
+            writer.write(b"ghi").await.unwrap();
+            assert_eq!(writer.get_ref(), b"abcdef");
+
+            // Bigger than the buffer, written directly.
+            writer.write(b"0123456789").await.unwrap();
+            assert_eq!(writer.get_ref(), b"abcdefghi0123456789");
+
+            writer.flush().await.unwrap();
+            assert!(writer.buffer().is_empty());
+        })
+    }
+
+    #[test]
+    fn buf_stream_reads_and_writes() {
+        struct Echo(Vec<u8>);
+
+        impl crate::io::AsyncRead for Echo {
+            fn poll_read(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &mut [u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                let this = self.get_mut();
+                let mut data = &this.0[..];
+                let res = std::pin::Pin::new(&mut data).poll_read(cx, buf);
+                let read = this.0.len() - data.len();
+                this.0.drain(..read);
+                res
+            }
+        }
+
+        impl AsyncWrite for Echo {
+            fn poll_write(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                std::pin::Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+            }
+
+            fn poll_flush(
+                self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                std::task::Poll::Ready(Ok(()))
+            }
+        }
+
+        block_on(async {
+            let mut stream = BufStream::new(Echo(Vec::new()));
+            stream.write(b"ping\n").await.unwrap();
+            assert!(stream.get_ref().0.is_empty());
+
+            stream.flush().await.unwrap();
+
+            let mut line = String::new();
+            stream.read_line(&mut line).await.unwrap();
+            assert_eq!(line, "ping\n");
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use crate::io::AsyncBufRead;
+use crate::io::read_until_internal;
+
+use futures::Stream;
+use pin_project_lite::pin_project;
+
+use std::io::Result;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Stream of the chunks in a reader, split on a delimiter.
+    ///
+    /// Created by `AsyncBufReadExt::split`.
+    pub struct Split<R> {
+        #[pin]
+        reader: R,
+        delim: u8,
+        buf: Vec<u8>,
+        read: usize,
+    }
+}
+
+impl<R> Split<R> {
+    pub(crate) fn new(reader: R, delim: u8) -> Split<R> {
+        Split {
+            reader,
+            delim,
+            buf: Vec::new(),
+            read: 0,
+        }
+    }
+
+    /// Consumes the stream, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncBufRead> Split<R> {
+    /// Polls for the next chunk, `None` means EOF.
+    pub fn poll_next_segment(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<Vec<u8>>>> {
+        let pinned = self.project();
+
+        let read =
+            match read_until_internal(pinned.reader, cx, *pinned.delim, pinned.buf, pinned.read) {
+                Poll::Ready(Ok(read)) => read,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+        if read == 0 && pinned.buf.is_empty() {
+            return Poll::Ready(Ok(None));
+        }
+
+        if pinned.buf.last() == Some(pinned.delim) {
+            pinned.buf.pop();
+        }
+
+        Poll::Ready(Ok(Some(mem::take(pinned.buf))))
+    }
+}
+
+impl<R: AsyncBufRead> Stream for Split<R> {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_segment(cx).map(Result::transpose)
+    }
+}
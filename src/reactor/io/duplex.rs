@@ -0,0 +1,246 @@
+use crate::io::{AsyncRead, AsyncWrite};
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Creates a pair of connected in-memory streams.
+///
+/// Bytes written into one end can be read from the other.
+/// Each direction buffers up to `capacity` bytes,
+/// writes wait for the reader once it is full.
+///
+/// Dropping one end makes reads on the other end return EOF
+/// once the buffered bytes run out, and writes fail with `BrokenPipe`.
+///
+/// The streams are not registered in the reactor
+/// and can be used without a running `Executor`.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+pub fn duplex(capacity: usize) -> (DuplexStream, DuplexStream) {
+    assert!(capacity > 0, "duplex capacity must be greater than 0");
+
+    let one = Arc::new(Mutex::new(Pipe::new(capacity)));
+    let two = Arc::new(Mutex::new(Pipe::new(capacity)));
+
+    (
+        DuplexStream {
+            read: Arc::clone(&one),
+            write: Arc::clone(&two),
+        },
+        DuplexStream {
+            read: two,
+            write: one,
+        },
+    )
+}
+
+/// One end of an in-memory stream, created by `duplex`.
+#[derive(Debug)]
+pub struct DuplexStream {
+    read: Arc<Mutex<Pipe>>,
+    write: Arc<Mutex<Pipe>>,
+}
+
+/// Unidirectional byte buffer shared by both ends.
+#[derive(Debug)]
+struct Pipe {
+    buffer: VecDeque<u8>,
+    capacity: usize,
+
+    // One of the ends was dropped.
+    closed: bool,
+
+    // Reader waiting for data.
+    read_waker: Option<Waker>,
+
+    // Writer waiting for room in the buffer.
+    write_waker: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(capacity: usize) -> Pipe {
+        Pipe {
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            closed: false,
+            read_waker: None,
+            write_waker: None,
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+
+        if let Some(waker) = self.write_waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if self.buffer.is_empty() {
+            if self.closed || buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            self.read_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let amnt = cmp::min(self.buffer.len(), buf.len());
+        for (dst, src) in buf.iter_mut().zip(self.buffer.drain(..amnt)) {
+            *dst = src;
+        }
+
+        if let Some(waker) = self.write_waker.take() {
+            waker.wake();
+        }
+
+        Poll::Ready(Ok(amnt))
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if self.closed {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+
+        let room = self.capacity - self.buffer.len();
+        if room == 0 && !buf.is_empty() {
+            self.write_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let amnt = cmp::min(room, buf.len());
+        self.buffer.extend(&buf[..amnt]);
+
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+
+        Poll::Ready(Ok(amnt))
+    }
+}
+
+impl AsyncRead for DuplexStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.read
+            .lock()
+            .expect("duplex pipe lock poisoned")
+            .poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for DuplexStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.write
+            .lock()
+            .expect("duplex pipe lock poisoned")
+            .poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        // Ignoring poisoning, the other end has to be notified regardless.
+        let mut read = self.read.lock().unwrap_or_else(|e| e.into_inner());
+        read.close();
+        drop(read);
+
+        let mut write = self.write.lock().unwrap_or_else(|e| e.into_inner());
+        write.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::duplex;
+    use crate::io::{AsyncReadExt, AsyncWriteExt};
+    use futures::executor::block_on;
+    use std::io;
+    use std::thread;
+
+    #[test]
+    fn transfer_both_ways() {
+        block_on(async {
+            let (mut a, mut b) = duplex(16);
+            let mut buf = [0u8; 16];
+
+            a.write(b"ping").await.unwrap();
+            assert_eq!(b.read(&mut buf).await.unwrap(), 4);
+            assert_eq!(&buf[..4], b"ping");
+
+            b.write(b"pong").await.unwrap();
+            assert_eq!(a.read(&mut buf).await.unwrap(), 4);
+            assert_eq!(&buf[..4], b"pong");
+        })
+    }
+
+    #[test]
+    fn backpressure() {
+        let (mut a, mut b) = duplex(4);
+
+        let writer = thread::spawn(move || {
+            block_on(async {
+                let mut sent = 0;
+                while sent < 64 {
+                    sent += a.write(&[7u8; 64][sent..]).await.unwrap();
+                }
+            })
+        });
+
+        block_on(async {
+            let mut total = 0;
+            let mut buf = [0u8; 64];
+
+            loop {
+                let read = b.read(&mut buf).await.unwrap();
+                assert!(read <= 4, "buffer grew past its capacity");
+                if read == 0 {
+                    break;
+                }
+                total += read;
+            }
+
+            assert_eq!(total, 64);
+        });
+
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn eof_and_broken_pipe_on_drop() {
+        block_on(async {
+            let (mut a, mut b) = duplex(8);
+            a.write(b"bye").await.unwrap();
+            drop(a);
+
+            let mut buf = [0u8; 8];
+            assert_eq!(b.read(&mut buf).await.unwrap(), 3);
+            assert_eq!(b.read(&mut buf).await.unwrap(), 0);
+
+            let err = b.write(b"late").await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        })
+    }
+}
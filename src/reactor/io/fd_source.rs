@@ -0,0 +1,89 @@
+use crate::reactor::reactor::Direction;
+use crate::runtime::{Executor, ExecutorHandle};
+
+use mio::unix::SourceFd;
+use mio::{Interest, Token};
+
+use std::io;
+use std::os::fd::AsRawFd;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// File descriptor registered in the reactor.
+///
+/// Deregisters itself when dropped.
+pub(crate) struct FdSource<T: AsRawFd> {
+    io: T,
+    handle: Arc<ExecutorHandle>,
+    token: Token,
+}
+
+impl<T: AsRawFd> FdSource<T> {
+    /// Registers `io`, which should be in non-blocking mode.
+    pub(crate) fn new(io: T, interest: Interest) -> io::Result<FdSource<T>> {
+        let handle = Executor::get();
+        let fd = io.as_raw_fd();
+        let token = handle.reactor_fn(|r| r.register(&mut SourceFd(&fd), interest))?;
+
+        Ok(FdSource {
+            io,
+            handle,
+            token: Token(token),
+        })
+    }
+
+    pub(crate) fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Runs `f` until it stops failing with `WouldBlock`,
+    /// waiting for readiness in direction `dir` in between.
+    pub(crate) fn poll_io<R>(
+        &self,
+        cx: &mut Context<'_>,
+        dir: Direction,
+        mut f: impl FnMut(&T) -> io::Result<R>,
+    ) -> Poll<io::Result<R>> {
+        match f(&self.io) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            res => return Poll::Ready(res),
+        }
+
+        self.handle
+            .reactor_fn(|r| r.attach_waker(cx, self.token, dir));
+
+        // The readiness event might have fired before the waker was attached.
+        match f(&self.io) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+}
+
+impl<T: AsRawFd> Drop for FdSource<T> {
+    fn drop(&mut self) {
+        let fd = self.io.as_raw_fd();
+        let _ = self
+            .handle
+            .reactor_fn(|r| r.deregister(&mut SourceFd(&fd), self.token.0));
+    }
+}
+
+/// Puts `fd` into non-blocking mode.
+pub(crate) fn set_nonblocking(fd: &impl AsRawFd) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+
+    // Safety: plain syscalls on a valid descriptor, results are checked.
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
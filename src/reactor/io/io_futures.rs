@@ -1,18 +1,20 @@
-use crate::io::{AsyncRead, AsyncWrite};
-use mio::Token;
+use crate::io::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite};
+use crate::task::poll_budgeted;
 use pin_project_lite::pin_project;
 use std::future::Future;
-use std::io;
+use std::io::{self, SeekFrom};
 use std::marker::{PhantomPinned, Unpin};
+use std::mem;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 pin_project! {
     /// Future representing an asynchronous read.
+    ///
+    /// Cancel safe: if dropped before completing, no data was read.
     pub struct ReadFut<'o, IO: ?Sized> {
         io: &'o mut IO,
         buf: &'o mut [u8],
-        token: Token,
 
         #[pin]
         _pin: PhantomPinned,
@@ -25,16 +27,15 @@ impl<'o, IO: AsyncRead + Unpin + ?Sized> Future for ReadFut<'o, IO> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let pinned = self.project();
 
-        Pin::new(pinned.io).poll_read(cx, pinned.buf)
+        poll_budgeted(cx, |cx| Pin::new(pinned.io).poll_read(cx, pinned.buf))
     }
 }
 
 impl<'w, IO: AsyncRead + Unpin + ?Sized> ReadFut<'w, IO> {
-    pub(crate) fn new(io: &'w mut IO, buf: &'w mut [u8], token: Token) -> ReadFut<'w, IO> {
+    pub(crate) fn new(io: &'w mut IO, buf: &'w mut [u8]) -> ReadFut<'w, IO> {
         ReadFut {
             io,
             buf,
-            token,
             _pin: PhantomPinned,
         }
     }
@@ -42,10 +43,11 @@ impl<'w, IO: AsyncRead + Unpin + ?Sized> ReadFut<'w, IO> {
 
 pin_project! {
     /// Future representing an asynchronous write.
+    ///
+    /// Cancel safe: if dropped before completing, no data was written.
     pub struct WriteFut<'o, IO: ?Sized> {
         io: &'o mut IO,
         buf: &'o [u8],
-        token: Token,
 
         #[pin]
         _pin: PhantomPinned,
@@ -58,16 +60,15 @@ impl<IO: AsyncWrite + Unpin + ?Sized> Future for WriteFut<'_, IO> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let pinned = self.project();
 
-        Pin::new(pinned.io).poll_write(cx, pinned.buf)
+        poll_budgeted(cx, |cx| Pin::new(pinned.io).poll_write(cx, pinned.buf))
     }
 }
 
 impl<'w, IO: AsyncWrite + Unpin + ?Sized> WriteFut<'w, IO> {
-    pub(crate) fn new(io: &'w mut IO, buf: &'w [u8], token: Token) -> WriteFut<'w, IO> {
+    pub(crate) fn new(io: &'w mut IO, buf: &'w [u8]) -> WriteFut<'w, IO> {
         WriteFut {
             io,
             buf,
-            token,
             _pin: PhantomPinned,
         }
     }
@@ -77,16 +78,14 @@ pin_project! {
     /// Future representing an asynchronous flush.
     pub struct FlushFut<'f, IO: ?Sized> {
         io: &'f mut IO,
-        token: Token,
         _pin: PhantomPinned,
     }
 }
 
 impl<'f, IO: AsyncWrite + Unpin + ?Sized> FlushFut<'f, IO> {
-    pub(crate) fn new(io: &'f mut IO, token: Token) -> Self {
+    pub(crate) fn new(io: &'f mut IO) -> Self {
         Self {
             io,
-            token,
             _pin: PhantomPinned,
         }
     }
@@ -98,6 +97,276 @@ impl<IO: AsyncWrite + Unpin + ?Sized> Future for FlushFut<'_, IO> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let pinned = self.project();
 
-        Pin::new(pinned.io).poll_flush(cx)
+        poll_budgeted(cx, |cx| Pin::new(pinned.io).poll_flush(cx))
+    }
+}
+
+/// Reads from `reader` into `buf` until `byte` is found or EOF is hit.
+///
+/// `read` keeps track of the bytes appended across calls that returned `Pending`.
+pub(crate) fn read_until_internal<R: AsyncBufRead + ?Sized>(
+    mut reader: Pin<&mut R>,
+    cx: &mut Context<'_>,
+    byte: u8,
+    buf: &mut Vec<u8>,
+    read: &mut usize,
+) -> Poll<io::Result<usize>> {
+    loop {
+        let (done, used) = {
+            let available = match reader.as_mut().poll_fill_buf(cx) {
+                Poll::Ready(Ok(available)) => available,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match available.iter().position(|b| *b == byte) {
+                Some(i) => {
+                    buf.extend_from_slice(&available[..=i]);
+                    (true, i + 1)
+                }
+
+                None => {
+                    buf.extend_from_slice(available);
+                    (available.is_empty(), available.len())
+                }
+            }
+        };
+
+        reader.as_mut().consume(used);
+        *read += used;
+
+        if done {
+            return Poll::Ready(Ok(mem::replace(read, 0)));
+        }
+    }
+}
+
+pin_project! {
+    /// Future representing an asynchronous read until a delimiter.
+    pub struct ReadUntilFut<'r, IO: ?Sized> {
+        io: &'r mut IO,
+        byte: u8,
+        buf: &'r mut Vec<u8>,
+        read: usize,
+
+        #[pin]
+        _pin: PhantomPinned,
+    }
+}
+
+impl<'r, IO: AsyncBufRead + Unpin + ?Sized> ReadUntilFut<'r, IO> {
+    pub(crate) fn new(io: &'r mut IO, byte: u8, buf: &'r mut Vec<u8>) -> Self {
+        Self {
+            io,
+            byte,
+            buf,
+            read: 0,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<IO: AsyncBufRead + Unpin + ?Sized> Future for ReadUntilFut<'_, IO> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pinned = self.project();
+
+        read_until_internal(
+            Pin::new(pinned.io),
+            cx,
+            *pinned.byte,
+            pinned.buf,
+            pinned.read,
+        )
+    }
+}
+
+pin_project! {
+    /// Future representing an asynchronous read of a line.
+    ///
+    /// Bytes read before the future is dropped are lost.
+    pub struct ReadLineFut<'r, IO: ?Sized> {
+        io: &'r mut IO,
+        buf: &'r mut String,
+        bytes: Vec<u8>,
+        read: usize,
+
+        #[pin]
+        _pin: PhantomPinned,
+    }
+}
+
+impl<'r, IO: AsyncBufRead + Unpin + ?Sized> ReadLineFut<'r, IO> {
+    pub(crate) fn new(io: &'r mut IO, buf: &'r mut String) -> Self {
+        Self {
+            io,
+            buf,
+            bytes: Vec::new(),
+            read: 0,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<IO: AsyncBufRead + Unpin + ?Sized> Future for ReadLineFut<'_, IO> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pinned = self.project();
+
+        let read =
+            match read_until_internal(Pin::new(pinned.io), cx, b'\n', pinned.bytes, pinned.read) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Ready(Ok(read)) => read,
+            };
+
+        match std::str::from_utf8(pinned.bytes) {
+            Ok(line) => {
+                pinned.buf.push_str(line);
+                pinned.bytes.clear();
+                Poll::Ready(Ok(read))
+            }
+
+            Err(_) => {
+                pinned.bytes.clear();
+                Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                )))
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future representing an asynchronous read until EOF.
+    pub struct ReadToEndFut<'r, IO: ?Sized> {
+        io: &'r mut IO,
+        buf: &'r mut Vec<u8>,
+        read: usize,
+
+        #[pin]
+        _pin: PhantomPinned,
+    }
+}
+
+impl<'r, IO: AsyncRead + Unpin + ?Sized> ReadToEndFut<'r, IO> {
+    pub(crate) fn new(io: &'r mut IO, buf: &'r mut Vec<u8>) -> Self {
+        Self {
+            io,
+            buf,
+            read: 0,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<IO: AsyncRead + Unpin + ?Sized> Future for ReadToEndFut<'_, IO> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pinned = self.project();
+
+        loop {
+            let len = pinned.buf.len();
+            pinned.buf.resize(len + 4096, 0);
+
+            let res = Pin::new(&mut **pinned.io).poll_read(cx, &mut pinned.buf[len..]);
+            let read = match res {
+                Poll::Ready(Ok(read)) => read,
+                Poll::Ready(Err(e)) => {
+                    pinned.buf.truncate(len);
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Pending => {
+                    pinned.buf.truncate(len);
+                    return Poll::Pending;
+                }
+            };
+
+            pinned.buf.truncate(len + read);
+            *pinned.read += read;
+
+            if read == 0 {
+                return Poll::Ready(Ok(mem::replace(pinned.read, 0)));
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future representing an asynchronous write of a whole buffer.
+    ///
+    /// Not cancel safe: part of the buffer may be written before it is dropped.
+    pub struct WriteAllFut<'w, IO: ?Sized> {
+        io: &'w mut IO,
+        buf: &'w [u8],
+
+        #[pin]
+        _pin: PhantomPinned,
+    }
+}
+
+impl<'w, IO: AsyncWrite + Unpin + ?Sized> WriteAllFut<'w, IO> {
+    pub(crate) fn new(io: &'w mut IO, buf: &'w [u8]) -> Self {
+        Self {
+            io,
+            buf,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<IO: AsyncWrite + Unpin + ?Sized> Future for WriteAllFut<'_, IO> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pinned = self.project();
+
+        while !pinned.buf.is_empty() {
+            let written = match Pin::new(&mut **pinned.io).poll_write(cx, pinned.buf) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(written)) => written,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            *pinned.buf = &pinned.buf[written..];
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+pin_project! {
+    /// Future representing an asynchronous seek.
+    pub struct SeekFut<'s, IO: ?Sized> {
+        io: &'s mut IO,
+        pos: SeekFrom,
+
+        #[pin]
+        _pin: PhantomPinned,
+    }
+}
+
+impl<'s, IO: AsyncSeek + Unpin + ?Sized> SeekFut<'s, IO> {
+    pub(crate) fn new(io: &'s mut IO, pos: SeekFrom) -> Self {
+        Self {
+            io,
+            pos,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+impl<IO: AsyncSeek + Unpin + ?Sized> Future for SeekFut<'_, IO> {
+    type Output = io::Result<u64>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pinned = self.project();
+
+        Pin::new(&mut **pinned.io).poll_seek(cx, *pinned.pos)
     }
 }
@@ -1,6 +1,5 @@
 use crate::reactor::reactor::Direction;
 use log::debug;
-use mio::Token;
 use mio::event::Event;
 use std::panic;
 use std::task::Waker;
@@ -39,13 +38,24 @@ impl WakerList {
         }
     }
 
-    fn put(&mut self, waker: Waker) {
+    // Returns whether the list overflowed.
+    fn put(&mut self, waker: Waker) -> bool {
+        // Waking the listed tasks early is harmless,
+        // they poll again and attach a new waker if still pending.
+        let overflowed = self.full();
+        if overflowed {
+            self.wake_all();
+        }
+
         self.cursor += 1;
         self.blk[self.cursor].write(waker);
+
+        overflowed
     }
 
     fn full(&self) -> bool {
-        self.cursor == WAKER_AMNT
+        // The first slot is never used.
+        self.cursor == WAKER_AMNT - 1
     }
 
     fn elements(&self) -> usize {
@@ -53,6 +63,15 @@ impl WakerList {
     }
 }
 
+impl Drop for WakerList {
+    fn drop(&mut self) {
+        for slot in &mut self.blk[1..=self.cursor] {
+            // Safety: slots up to the cursor are initialized.
+            unsafe { slot.assume_init_drop() };
+        }
+    }
+}
+
 /// Contains wakers.
 pub struct Wakers {
     rd: WakerList,
@@ -60,7 +79,7 @@ pub struct Wakers {
 }
 
 impl Wakers {
-    pub(crate) fn put(&mut self, waker: &Waker, dir: Direction) {
+    pub(crate) fn put(&mut self, waker: &Waker, dir: Direction) -> bool {
         let list = match dir {
             Direction::Read => &mut self.rd,
             Direction::Write => &mut self.wr,
@@ -109,17 +128,15 @@ impl Wakers {
 /// Represents a connection between a waker and the reactor
 pub struct IoSource {
     wakers: Wakers,
-    token: Token,
 }
 
 impl IoSource {
-    pub fn new(token: usize) -> IoSource {
+    pub fn new() -> IoSource {
         IoSource {
             wakers: Wakers {
                 rd: WakerList::new(),
                 wr: WakerList::new(),
             },
-            token: Token(token),
         }
     }
 
@@ -138,7 +155,9 @@ impl IoSource {
         // todo: handle closing and stuff
     }
 
-    pub(crate) fn put(&mut self, waker: &Waker, dir: Direction) {
+    /// Stores a waker, returns whether the list for `dir` was full
+    /// and had its wakers woken early to make room.
+    pub(crate) fn put(&mut self, waker: &Waker, dir: Direction) -> bool {
         self.wakers.put(waker, dir)
     }
 }
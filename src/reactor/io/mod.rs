@@ -1,8 +1,24 @@
 mod io_source;
 pub(crate) use io_source::IoSource;
 
+#[cfg(unix)]
+mod fd_source;
+#[cfg(unix)]
+pub(crate) use fd_source::{FdSource, set_nonblocking};
+
 mod net;
-pub use net::TcpStream;
+pub use net::{Accept, TcpListener, TcpStream};
+
+mod buffered;
+pub use buffered::*;
+
+mod duplex;
+pub use duplex::{DuplexStream, duplex};
+
+#[cfg(unix)]
+mod stdio;
+#[cfg(unix)]
+pub use stdio::{Stderr, Stdin, Stdout, stderr, stdin, stdout};
 
 mod traits;
 pub use traits::*;
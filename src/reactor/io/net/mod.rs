@@ -1,3 +1,5 @@
+mod tcp_listener;
 mod tcp_stream;
 
+pub use tcp_listener::{Accept, TcpListener};
 pub use tcp_stream::TcpStream;
@@ -0,0 +1,135 @@
+use crate::io::TcpStream;
+use crate::reactor::reactor::Direction;
+use crate::runtime::{Executor, ExecutorHandle};
+
+use mio::{Interest, Token};
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// TCP socket listening for connections.
+pub struct TcpListener {
+    io: mio::net::TcpListener,
+    handle: Arc<ExecutorHandle>,
+    token: Token,
+}
+
+impl TcpListener {
+    /// Binds a new listener to `addr`.
+    pub fn bind(addr: &str) -> io::Result<TcpListener> {
+        let address = match addr.parse() {
+            Ok(o) => o,
+            Err(_e) => return Err(io::Error::new(io::ErrorKind::NotFound, "invalid address")),
+        };
+
+        TcpListener::from_mio(mio::net::TcpListener::bind(address)?)
+    }
+
+    pub fn from_std(listener: std::net::TcpListener) -> io::Result<TcpListener> {
+        listener.set_nonblocking(true)?;
+        TcpListener::from_mio(mio::net::TcpListener::from_std(listener))
+    }
+
+    fn from_mio(mut listener: mio::net::TcpListener) -> io::Result<TcpListener> {
+        let handle = Executor::get();
+        let result = handle.reactor_fn(|r| r.register(&mut listener, Interest::READABLE));
+
+        Ok(TcpListener {
+            io: listener,
+            handle,
+            token: Token(result?),
+        })
+    }
+
+    /// Returns the address the listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Accepts a new connection.
+    pub fn accept(&self) -> Accept<'_> {
+        Accept { listener: self }
+    }
+
+    /// Polls for a new connection.
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+        match self.io.accept() {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Poll::Ready(Err(e)),
+            Ok((io, addr)) => return Poll::Ready(TcpStream::from_mio(io).map(|s| (s, addr))),
+        }
+
+        self.handle
+            .reactor_fn(|r| r.attach_waker(cx, self.token, Direction::Read));
+
+        // A connection might have arrived before the waker was attached,
+        // its event would be lost.
+        match self.io.accept() {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
+            Ok((io, addr)) => Poll::Ready(TcpStream::from_mio(io).map(|s| (s, addr))),
+        }
+    }
+}
+
+/// Future returned by `TcpListener::accept`.
+pub struct Accept<'l> {
+    listener: &'l TcpListener,
+}
+
+impl Future for Accept<'_> {
+    type Output = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.listener.poll_accept(cx)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::TcpListener;
+    use crate::Executor;
+    use crate::io::{AsyncReadExt, AsyncWriteExt};
+    use std::io::{Read, Write};
+    use std::thread;
+
+    #[test]
+    fn accept_connections() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+
+            let client = thread::spawn(move || {
+                for n in 0..3u8 {
+                    let mut stream = std::net::TcpStream::connect(addr).unwrap();
+                    stream.write_all(&[n]).unwrap();
+
+                    let mut buf = [0u8; 1];
+                    stream.read_exact(&mut buf).unwrap();
+                    assert_eq!(buf[0], n + 1);
+                }
+            });
+
+            for n in 0..3u8 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 1];
+                stream.read(&mut buf).await.unwrap();
+                assert_eq!(buf[0], n);
+
+                stream.write(&[n + 1]).await.unwrap();
+            }
+
+            client.join().unwrap();
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+}
@@ -91,7 +91,10 @@ impl TcpStream {
 
     pub fn from_std(tcp: std::net::TcpStream) -> io::Result<Self> {
         tcp.set_nonblocking(true)?;
-        let mut sock = mio::net::TcpStream::from_std(tcp);
+        TcpStream::from_mio(mio::net::TcpStream::from_std(tcp))
+    }
+
+    pub(crate) fn from_mio(mut sock: mio::net::TcpStream) -> io::Result<Self> {
         let handle = Executor::get();
         let result =
             handle.reactor_fn(|r| r.register(&mut sock, Interest::READABLE | Interest::WRITABLE));
@@ -113,10 +116,10 @@ impl TokenBearer for TcpStream {
 impl AsyncRead for TcpStream {
     /// Read x amount of bytes from this socket.
     /// It's asynchronous woo!!
-    fn poll_read<'a>(
+    fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-        buf: &'a mut [u8],
+        buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
         handle_async_read!(self.io, buf, cx, self.token, self.handle)
     }
@@ -125,20 +128,20 @@ impl AsyncRead for TcpStream {
 impl AsyncRead for &TcpStream {
     /// Read x amount of bytes from this socket.
     /// It's asynchronous woo!!
-    fn poll_read<'a>(
+    fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-        buf: &'a mut [u8],
+        buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
         handle_async_read!(self.io, buf, cx, self.token, self.handle)
     }
 }
 
 impl AsyncWrite for TcpStream {
-    fn poll_write<'w>(
+    fn poll_write(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-        buf: &'w [u8],
+        buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         handle_async_write!(self.io, buf, cx, self.token, self.handle)
     }
@@ -149,10 +152,10 @@ impl AsyncWrite for TcpStream {
 }
 
 impl AsyncWrite for &TcpStream {
-    fn poll_write<'w>(
+    fn poll_write(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-        buf: &'w [u8],
+        buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         handle_async_write!(self.io, buf, cx, self.token, self.handle)
     }
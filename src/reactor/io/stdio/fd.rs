@@ -0,0 +1,284 @@
+use crate::io::FdSource;
+use crate::reactor::Direction;
+use crate::runtime::blocking::BlockingTask;
+use crate::runtime::{Executor, ExecutorHandle};
+
+use mio::Interest;
+
+use std::cmp;
+use std::fs;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, BorrowedFd, RawFd};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// Biggest chunk of data moved in one blocking operation.
+const MAX_BUF: usize = 64 * 1024;
+
+/// Descriptors put into non-blocking mode, with the amount of handles
+/// relying on it and the flags to restore once there are none left.
+static NONBLOCKING: Mutex<Vec<(RawFd, usize, libc::c_int)>> = Mutex::new(Vec::new());
+
+/// Keeps a descriptor in non-blocking mode while alive.
+///
+/// The mode belongs to the open file, which is shared with the parent process,
+/// so it is restored as soon as possible.
+struct NonBlocking(RawFd);
+
+impl NonBlocking {
+    fn acquire(fd: RawFd) -> io::Result<NonBlocking> {
+        let mut fds = NONBLOCKING.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(entry) = fds.iter_mut().find(|entry| entry.0 == fd) {
+            entry.1 += 1;
+            return Ok(NonBlocking(fd));
+        }
+
+        // Safety: plain syscalls, results are checked.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        fds.push((fd, 1, flags));
+        Ok(NonBlocking(fd))
+    }
+}
+
+impl Drop for NonBlocking {
+    fn drop(&mut self) {
+        let mut fds = NONBLOCKING.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(idx) = fds.iter().position(|entry| entry.0 == self.0) else {
+            return;
+        };
+
+        fds[idx].1 -= 1;
+        if fds[idx].1 == 0 {
+            let (fd, _, flags) = fds.swap_remove(idx);
+            // Safety: plain syscall, nothing to do if it fails.
+            unsafe { libc::fcntl(fd, libc::F_SETFL, flags) };
+        }
+    }
+}
+
+/// Blocking operation performed on the descriptor.
+enum Operation {
+    Read(io::Result<usize>),
+    Write(io::Result<()>),
+}
+
+enum State {
+    // No operation in flight, holds the buffer.
+    Idle(Option<Vec<u8>>),
+
+    // Operation running on the blocking pool, which hands back the buffer.
+    Busy(BlockingTask<(Operation, Vec<u8>)>),
+}
+
+/// Descriptor driven through the blocking pool.
+///
+/// Writes are done in the background one after another,
+/// so they reach the descriptor in order.
+struct Blocking {
+    file: Arc<fs::File>,
+    handle: Arc<ExecutorHandle>,
+    state: State,
+
+    // Read position inside the buffer.
+    pos: usize,
+
+    // Error of a background write, not yet reported.
+    last_write_err: Option<io::Error>,
+}
+
+impl Blocking {
+    /// Waits for the operation in flight, if any.
+    fn poll_complete(&mut self, cx: &mut Context<'_>) -> Poll<Option<Operation>> {
+        let task = match self.state {
+            State::Busy(ref mut task) => task,
+            State::Idle(_) => return Poll::Ready(None),
+        };
+
+        let (op, mut buf) = match Pin::new(task).poll(cx) {
+            Poll::Ready(done) => done,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        if !matches!(op, Operation::Read(Ok(_))) {
+            buf.clear();
+        }
+
+        self.pos = 0;
+        self.state = State::Idle(Some(buf));
+
+        Poll::Ready(Some(op))
+    }
+
+    fn take_buffer(&mut self) -> Vec<u8> {
+        match self.state {
+            State::Idle(ref mut buf) => buf.take().expect("stdio buffer missing"),
+            State::Busy(_) => unreachable!("stdio is busy"),
+        }
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, dst: &mut [u8]) -> Poll<io::Result<usize>> {
+        if dst.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            match self.poll_complete(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Operation::Read(Ok(0)))) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Some(Operation::Read(Err(e)))) => return Poll::Ready(Err(e)),
+                Poll::Ready(Some(Operation::Write(Err(e)))) => self.last_write_err = Some(e),
+                Poll::Ready(_) => {}
+            }
+
+            if let State::Idle(Some(ref buf)) = self.state
+                && self.pos < buf.len()
+            {
+                let amnt = cmp::min(buf.len() - self.pos, dst.len());
+                dst[..amnt].copy_from_slice(&buf[self.pos..self.pos + amnt]);
+                self.pos += amnt;
+
+                return Poll::Ready(Ok(amnt));
+            }
+
+            let mut buf = self.take_buffer();
+            let max = cmp::min(dst.len(), MAX_BUF);
+            let file = Arc::clone(&self.file);
+
+            buf.clear();
+            self.state = State::Busy(self.handle.spawn_blocking(move || {
+                buf.resize(max, 0);
+                let res = (&*file).read(&mut buf);
+                buf.truncate(*res.as_ref().unwrap_or(&0));
+
+                (Operation::Read(res), buf)
+            }));
+        }
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, src: &[u8]) -> Poll<io::Result<usize>> {
+        match self.poll_complete(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(Operation::Write(Err(e)))) => return Poll::Ready(Err(e)),
+            Poll::Ready(_) => {}
+        }
+
+        if let Some(e) = self.last_write_err.take() {
+            return Poll::Ready(Err(e));
+        }
+
+        let mut buf = self.take_buffer();
+        let amnt = cmp::min(src.len(), MAX_BUF);
+        let file = Arc::clone(&self.file);
+
+        buf.clear();
+        buf.extend_from_slice(&src[..amnt]);
+        self.state = State::Busy(self.handle.spawn_blocking(move || {
+            let res = (&*file).write_all(&buf);
+            (Operation::Write(res), buf)
+        }));
+
+        Poll::Ready(Ok(amnt))
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.poll_complete(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(Operation::Write(Err(e)))) => return Poll::Ready(Err(e)),
+            Poll::Ready(_) => {}
+        }
+
+        match self.last_write_err.take() {
+            Some(e) => Poll::Ready(Err(e)),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+enum Backend {
+    // Pipes, sockets and terminals, waiting for readiness in the reactor.
+    Evented(FdSource<fs::File>, #[allow(dead_code)] NonBlocking),
+
+    // Regular files and anything else epoll refuses.
+    Blocking(Blocking),
+}
+
+/// Standard stream descriptor, duplicated so the handle owns its copy.
+pub(crate) struct StdioFd {
+    backend: Backend,
+}
+
+impl StdioFd {
+    pub(crate) fn new(fd: BorrowedFd<'_>, interest: Interest) -> io::Result<StdioFd> {
+        let file = fs::File::from(fd.try_clone_to_owned()?);
+
+        let backend = match evented(fd.as_raw_fd(), &file, interest) {
+            Some(evented) => evented,
+            None => Backend::Blocking(Blocking {
+                file: Arc::new(file),
+                handle: Executor::get(),
+                state: State::Idle(Some(Vec::new())),
+                pos: 0,
+                last_write_err: None,
+            }),
+        };
+
+        Ok(StdioFd { backend })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_evented(&self) -> bool {
+        matches!(self.backend, Backend::Evented(..))
+    }
+
+    pub(crate) fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.backend {
+            Backend::Evented(io, _) => io.poll_io(cx, Direction::Read, |mut f| f.read(buf)),
+            Backend::Blocking(blocking) => blocking.poll_read(cx, buf),
+        }
+    }
+
+    pub(crate) fn poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.backend {
+            Backend::Evented(io, _) => io.poll_io(cx, Direction::Write, |mut f| f.write(buf)),
+            Backend::Blocking(blocking) => blocking.poll_write(cx, buf),
+        }
+    }
+
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.backend {
+            Backend::Evented(..) => Poll::Ready(Ok(())),
+            Backend::Blocking(blocking) => blocking.poll_flush(cx),
+        }
+    }
+}
+
+/// Registers `file` in the reactor if its type supports readiness.
+fn evented(fd: RawFd, file: &fs::File, interest: Interest) -> Option<Backend> {
+    // Regular files are always ready, epoll refuses them.
+    if file.metadata().ok()?.is_file() {
+        return None;
+    }
+
+    let guard = NonBlocking::acquire(fd).ok()?;
+    let file = file.try_clone().ok()?;
+
+    match FdSource::new(file, interest) {
+        Ok(io) => Some(Backend::Evented(io, guard)),
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,283 @@
+mod fd;
+
+use crate::io::{AsyncRead, AsyncWrite};
+use fd::StdioFd;
+
+use mio::Interest;
+
+use std::io;
+use std::os::fd::{AsFd, BorrowedFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Capacity of stdout's line buffer.
+const LINE_BUF_SIZE: usize = 1024;
+
+/// Handle to the standard input of the process.
+///
+/// Pipes, sockets and terminals are put into non-blocking mode
+/// and registered in the reactor, other descriptors like regular files
+/// are read on the runtime's blocking threads.
+/// Non-blocking mode is shared with every other user of the descriptor,
+/// including the standard library's blocking handles, and is undone
+/// once the last handle relying on it is dropped.
+pub struct Stdin {
+    fd: StdioFd,
+}
+
+/// Handle to the standard output of the process.
+///
+/// Line buffered: complete lines are written right away,
+/// a trailing partial line stays buffered until a newline or a flush.
+///
+/// Uses the reactor or the blocking threads like `Stdin`.
+pub struct Stdout {
+    fd: StdioFd,
+    buf: Vec<u8>,
+}
+
+/// Handle to the standard error of the process, unbuffered.
+///
+/// Uses the reactor or the blocking threads like `Stdin`.
+pub struct Stderr {
+    fd: StdioFd,
+}
+
+/// Returns a new handle to the standard input.
+///
+/// Must be called from within a runtime.
+/// Every handle owns a copy of the descriptor and its own buffer,
+/// bytes read ahead by one handle are not seen by another.
+pub fn stdin() -> Stdin {
+    Stdin {
+        fd: StdioFd::new(io::stdin().as_fd(), Interest::READABLE)
+            .expect("failed to duplicate stdin"),
+    }
+}
+
+/// Returns a new handle to the standard output.
+///
+/// Must be called from within a runtime.
+/// Writes through one handle are ordered,
+/// writes through different handles might interleave.
+pub fn stdout() -> Stdout {
+    Stdout::new(io::stdout().as_fd()).expect("failed to duplicate stdout")
+}
+
+/// Returns a new handle to the standard error.
+///
+/// Must be called from within a runtime.
+pub fn stderr() -> Stderr {
+    Stderr {
+        fd: StdioFd::new(io::stderr().as_fd(), Interest::WRITABLE)
+            .expect("failed to duplicate stderr"),
+    }
+}
+
+impl Stdout {
+    fn new(fd: BorrowedFd<'_>) -> io::Result<Stdout> {
+        Ok(Stdout {
+            fd: StdioFd::new(fd, Interest::WRITABLE)?,
+            buf: Vec::with_capacity(LINE_BUF_SIZE),
+        })
+    }
+
+    /// Writes out the whole line buffer.
+    fn poll_flush_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut written = 0;
+        let mut res = Ok(());
+
+        while written < self.buf.len() {
+            match self.fd.poll_write(cx, &self.buf[written..]) {
+                Poll::Ready(Ok(0)) => {
+                    res = Err(io::ErrorKind::WriteZero.into());
+                    break;
+                }
+                Poll::Ready(Ok(n)) => written += n,
+                Poll::Ready(Err(e)) => {
+                    res = Err(e);
+                    break;
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        self.buf.drain(..written);
+
+        match res {
+            Ok(()) if !self.buf.is_empty() => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+}
+
+impl AsyncRead for Stdin {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().fd.poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Stdout {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+
+        let newline = match src.iter().rposition(|&b| b == b'\n') {
+            Some(idx) => idx,
+
+            // No complete line, buffer it unless it doesn't fit.
+            None => {
+                if me.buf.len() + src.len() > LINE_BUF_SIZE {
+                    match me.poll_flush_buf(cx) {
+                        Poll::Ready(Ok(())) => {}
+                        other => return other.map(|res| res.map(|_| 0)),
+                    }
+                }
+
+                if src.len() >= LINE_BUF_SIZE {
+                    return me.fd.poll_write(cx, src);
+                }
+
+                me.buf.extend_from_slice(src);
+                return Poll::Ready(Ok(src.len()));
+            }
+        };
+
+        // Lines go out right away, after what was buffered before them.
+        match me.poll_flush_buf(cx) {
+            Poll::Ready(Ok(())) => {}
+            other => return other.map(|res| res.map(|_| 0)),
+        }
+
+        let lines = &src[..=newline];
+        let written = match me.fd.poll_write(cx, lines) {
+            Poll::Ready(Ok(n)) => n,
+            other => return other,
+        };
+
+        if written < lines.len() {
+            return Poll::Ready(Ok(written));
+        }
+
+        let tail = &src[written..];
+        let amnt = tail.len().min(LINE_BUF_SIZE);
+        me.buf.extend_from_slice(&tail[..amnt]);
+
+        Poll::Ready(Ok(written + amnt))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let me = self.get_mut();
+
+        match me.poll_flush_buf(cx) {
+            Poll::Ready(Ok(())) => me.fd.poll_flush(cx),
+            other => other,
+        }
+    }
+}
+
+impl AsyncWrite for Stderr {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().fd.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().fd.poll_flush(cx)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::*;
+    use crate::Executor;
+    use crate::io::{AsyncReadExt, AsyncWriteExt};
+    use std::fs::File;
+    use std::io::Read;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    fn pipe() -> (File, File) {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+        unsafe {
+            (
+                File::from(OwnedFd::from_raw_fd(fds[0])),
+                File::from(OwnedFd::from_raw_fd(fds[1])),
+            )
+        }
+    }
+
+    #[test]
+    fn stdout_line_buffered_pipe() {
+        let (mut rx, tx) = pipe();
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async move {
+            let mut out = Stdout::new(tx.as_fd()).unwrap();
+            assert!(out.fd.is_evented());
+
+            out.write_all(b"partial ").await.unwrap();
+            out.write_all(b"line\nrest").await.unwrap();
+
+            let mut buf = [0u8; 64];
+            let read = rx.read(&mut buf).unwrap();
+            assert_eq!(&buf[..read], b"partial line\n");
+
+            out.flush().await.unwrap();
+            let read = rx.read(&mut buf).unwrap();
+            assert_eq!(&buf[..read], b"rest");
+
+            // Non-blocking mode is undone with the last handle.
+            drop(out);
+            let flags = unsafe { libc::fcntl(tx.as_fd().as_raw_fd(), libc::F_GETFL) };
+            assert_eq!(flags & libc::O_NONBLOCK, 0);
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn regular_file_fallback() {
+        let path = std::env::temp_dir().join(format!("lamp-stdio-{}", std::process::id()));
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async move {
+            let file = File::create(&path).unwrap();
+            let mut out = Stdout::new(file.as_fd()).unwrap();
+            assert!(!out.fd.is_evented());
+
+            for n in 0..100 {
+                out.write_all(format!("{n}\n").as_bytes()).await.unwrap();
+            }
+            out.flush().await.unwrap();
+
+            let expected: String = (0..100).map(|n| format!("{n}\n")).collect();
+            let file = File::open(&path).unwrap();
+            let mut input = Stdin {
+                fd: StdioFd::new(file.as_fd(), Interest::READABLE).unwrap(),
+            };
+
+            let mut read = Vec::new();
+            input.read_to_end(&mut read).await.unwrap();
+            assert_eq!(read, expected.as_bytes());
+
+            std::fs::remove_file(&path).unwrap();
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+}
@@ -0,0 +1,94 @@
+use crate::io::{AsyncRead, Lines, ReadLineFut, ReadUntilFut, Split};
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+macro_rules! buf_read_impl {
+    () => {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+            Pin::new(&mut **self.get_mut()).poll_fill_buf(cx)
+        }
+
+        fn consume(mut self: Pin<&mut Self>, amt: usize) {
+            Pin::new(&mut **self).consume(amt)
+        }
+    };
+}
+
+/// Reader with an internal buffer.
+///
+/// Allows looking at the buffered data without copying it
+/// and is the base for line-oriented reads.
+pub trait AsyncBufRead: AsyncRead {
+    /// Returns the contents of the internal buffer,
+    /// filling it with more data from the inner reader if it is empty.
+    ///
+    /// An empty slice means the reader reached EOF.
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>>;
+
+    /// Marks `amt` bytes of the buffer as read,
+    /// so they are no longer returned by `poll_fill_buf`.
+    fn consume(self: Pin<&mut Self>, amt: usize);
+}
+
+impl<T: AsyncBufRead + Unpin + ?Sized> AsyncBufRead for &mut T {
+    buf_read_impl!();
+}
+
+impl<T: AsyncBufRead + Unpin + ?Sized> AsyncBufRead for Box<T> {
+    buf_read_impl!();
+}
+
+impl AsyncBufRead for &[u8] {
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        Poll::Ready(Ok(*self.get_mut()))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        *self = &self[amt..];
+    }
+}
+
+pub trait AsyncBufReadExt: AsyncBufRead {
+    /// Reads bytes into `buf` until the `byte` delimiter or EOF is reached.
+    ///
+    /// The delimiter is included in `buf`.
+    /// Resolves to the amount of bytes read, 0 means EOF.
+    fn read_until<'r>(&'r mut self, byte: u8, buf: &'r mut Vec<u8>) -> ReadUntilFut<'r, Self>
+    where
+        Self: Unpin,
+    {
+        ReadUntilFut::new(self, byte, buf)
+    }
+
+    /// Reads a line into `buf`, including the trailing newline.
+    ///
+    /// Fails with `InvalidData` if the line is not valid UTF-8,
+    /// leaving `buf` untouched.
+    fn read_line<'r>(&'r mut self, buf: &'r mut String) -> ReadLineFut<'r, Self>
+    where
+        Self: Unpin,
+    {
+        ReadLineFut::new(self, buf)
+    }
+
+    /// Turns the reader into a stream of lines,
+    /// without the trailing `\n` or `\r\n`.
+    fn lines(self) -> Lines<Self>
+    where
+        Self: Sized,
+    {
+        Lines::new(self)
+    }
+
+    /// Turns the reader into a stream of chunks split on `byte`,
+    /// without the delimiter.
+    fn split(self, byte: u8) -> Split<Self>
+    where
+        Self: Sized,
+    {
+        Split::new(self, byte)
+    }
+}
+
+impl<Io: AsyncBufRead + ?Sized> AsyncBufReadExt for Io {}
@@ -1,5 +1,4 @@
-use crate::io::ReadFut;
-use crate::io::TokenBearer;
+use crate::io::{ReadFut, ReadToEndFut};
 use std::io::Result;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -29,12 +28,36 @@ impl<T: AsyncRead + Unpin + ?Sized> AsyncRead for Box<T> {
     read_impl!();
 }
 
+impl AsyncRead for &[u8] {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let amnt = std::cmp::min(self.len(), buf.len());
+        let (head, tail) = self.split_at(amnt);
+
+        buf[..amnt].copy_from_slice(head);
+        *self = tail;
+
+        Poll::Ready(Ok(amnt))
+    }
+}
+
 pub trait AsyncReadExt: AsyncRead {
     fn read<'r>(&'r mut self, buf: &'r mut [u8]) -> ReadFut<'r, Self>
     where
-        Self: Unpin + AsyncRead + TokenBearer,
+        Self: Unpin + AsyncRead,
+    {
+        ReadFut::new(self, buf)
+    }
+
+    /// Reads until EOF, appending everything to `buf`.
+    fn read_to_end<'r>(&'r mut self, buf: &'r mut Vec<u8>) -> ReadToEndFut<'r, Self>
+    where
+        Self: Unpin + AsyncRead,
     {
-        ReadFut::new(self, buf, self.get_token())
+        ReadToEndFut::new(self, buf)
     }
 }
 
@@ -0,0 +1,51 @@
+use crate::io::SeekFut;
+use std::io::{Result, SeekFrom};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+macro_rules! seek_impl {
+    () => {
+        fn poll_seek(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            pos: SeekFrom,
+        ) -> Poll<Result<u64>> {
+            Pin::new(&mut **self).poll_seek(cx, pos)
+        }
+    };
+}
+
+/// I/O object with a cursor which can be moved.
+pub trait AsyncSeek {
+    /// Moves the cursor to `pos`, resolving to the new position from the start.
+    ///
+    /// After returning `Pending` this must be called again with the same `pos`.
+    fn poll_seek(self: Pin<&mut Self>, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>>;
+}
+
+impl<T: AsyncSeek + Unpin + ?Sized> AsyncSeek for &mut T {
+    seek_impl!();
+}
+
+impl<T: AsyncSeek + Unpin + ?Sized> AsyncSeek for Box<T> {
+    seek_impl!();
+}
+
+pub trait AsyncSeekExt: AsyncSeek {
+    fn seek(&mut self, pos: SeekFrom) -> SeekFut<'_, Self>
+    where
+        Self: Unpin,
+    {
+        SeekFut::new(self, pos)
+    }
+
+    /// Returns the current position of the cursor.
+    fn stream_position(&mut self) -> SeekFut<'_, Self>
+    where
+        Self: Unpin,
+    {
+        SeekFut::new(self, SeekFrom::Current(0))
+    }
+}
+
+impl<Io: AsyncSeek + ?Sized> AsyncSeekExt for Io {}
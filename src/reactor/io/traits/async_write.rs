@@ -1,5 +1,4 @@
-use crate::io::TokenBearer;
-use crate::io::{FlushFut, WriteFut};
+use crate::io::{FlushFut, WriteAllFut, WriteFut};
 use std::io::Result;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -37,19 +36,39 @@ impl<T: ?Sized + AsyncWrite + Unpin> AsyncWrite for Box<T> {
     write_impl!();
 }
 
+impl AsyncWrite for Vec<u8> {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.get_mut().extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
 pub trait AsyncWriteExt: AsyncWrite {
     fn write<'w>(&'w mut self, buf: &'w [u8]) -> WriteFut<'w, Self>
     where
-        Self: Unpin + AsyncWrite + TokenBearer,
+        Self: Unpin + AsyncWrite,
+    {
+        WriteFut::new(self, buf)
+    }
+
+    /// Writes the whole buffer, failing with `WriteZero`
+    /// if the writer stops accepting data.
+    fn write_all<'w>(&'w mut self, buf: &'w [u8]) -> WriteAllFut<'w, Self>
+    where
+        Self: Unpin + AsyncWrite,
     {
-        WriteFut::new(self, buf, self.get_token())
+        WriteAllFut::new(self, buf)
     }
 
     fn flush<'w>(&'w mut self) -> FlushFut<'w, Self>
     where
-        Self: Unpin + AsyncWrite + TokenBearer,
+        Self: Unpin + AsyncWrite,
     {
-        FlushFut::new(self, self.get_token())
+        FlushFut::new(self)
     }
 }
 
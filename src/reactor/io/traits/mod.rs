@@ -1,7 +1,11 @@
+mod async_buf_read;
 mod async_read;
+mod async_seek;
 mod async_write;
 mod token_bearer;
 
+pub use async_buf_read::*;
 pub use async_read::*;
+pub use async_seek::*;
 pub use async_write::*;
 pub use token_bearer::TokenBearer;
@@ -1,5 +1,9 @@
 pub mod io;
 mod reactor;
 
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub(crate) mod uring;
+
+pub(crate) use reactor::Direction;
 pub(crate) use reactor::Handle;
 pub(crate) use reactor::Reactor;
@@ -1,9 +1,11 @@
 use crate::io::IoSource;
+use crate::trace::trace_event;
 
 use mio::event::Source;
 use mio::{Events, Interest, Poll, Registry, Token};
 
 use std::io::Result as IoResult;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::task::Context;
 use std::thread;
@@ -11,9 +13,19 @@ use std::fmt;
 
 use slab::Slab;
 
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use crate::reactor::uring::Uring;
+
 use log::{debug, trace};
 
 const SHUTDOWN: Token = Token(usize::MAX);
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+const URING: Token = Token(usize::MAX - 1);
+
+#[cfg(unix)]
+const SIGNAL: Token = Token(usize::MAX - 2);
+
 /// represents the interest of the underlying io.
 pub enum Direction {
     Read,
@@ -29,18 +41,42 @@ impl fmt::Debug for Direction {
     }
 }
 
-/// Represents the global I/O Reactor.
+/// Counters of the reactor, shared with its thread.
+#[derive(Debug, Default)]
+pub(crate) struct ReactorStats {
+    /// Events received for I/O sources.
+    pub(crate) events: AtomicU64,
+
+    /// Events by direction, an event can be both readable and writable.
+    pub(crate) read_events: AtomicU64,
+    pub(crate) write_events: AtomicU64,
+
+    /// Times a source's waker list was full, waking its tasks early.
+    pub(crate) waker_overflows: AtomicU64,
+}
+
+/// Represents the I/O Reactor.
 ///
-/// Only one exists at anytime.
+/// Each runtime owns one, polled on its own thread.
 pub struct Reactor {
     /// Re-usable event pool.
     events: Arc<Mutex<Events>>,
 
+    /// Counters, for the runtime's metrics.
+    stats: Arc<ReactorStats>,
+
     /// Handle
     handle: Arc<Handle>,
 
     /// I/O sources
     sources: Arc<Mutex<Slab<IoSource>>>,
+
+    /// Completion based driver, `None` if the kernel refused to set it up.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    uring: Option<Arc<Uring>>,
+
+    /// Whether the signal pipe is registered.
+    signals: AtomicBool,
 }
 
 /// Handle to the I/O Reactor.
@@ -88,18 +124,57 @@ impl Reactor {
         let r = Reactor {
             sources,
             events,
+            stats: Arc::new(ReactorStats::default()),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            uring: Reactor::setup_uring(&handle.registry),
+            signals: AtomicBool::new(false),
             handle,
         };
         let arc_handle = Arc::clone(&r.handle);
         Ok((r, arc_handle))
     }
 
+    /// Sets up the io_uring driver and registers its descriptor.
+    ///
+    /// Falls back to readiness based I/O if that fails.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    fn setup_uring(registry: &Registry) -> Option<Arc<Uring>> {
+        use log::warn;
+        use mio::unix::SourceFd;
+
+        let uring = match Uring::new() {
+            Ok(uring) => uring,
+            Err(e) => {
+                warn!("io_uring setup failed, falling back to epoll: {e}");
+                return None;
+            }
+        };
+
+        match registry.register(&mut SourceFd(&uring.fd()), URING, Interest::READABLE) {
+            Ok(()) => Some(Arc::new(uring)),
+            Err(e) => {
+                warn!("failed registering io_uring, falling back to epoll: {e}");
+                None
+            }
+        }
+    }
+
+    /// Returns the io_uring driver, `None` if it isn't available.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub(crate) fn uring(&self) -> Option<&Arc<Uring>> {
+        self.uring.as_ref()
+    }
+
     /// Get reference to the Reactor.
     pub fn start(&self) -> IoResult<thread::JoinHandle<()>> {
         // Polling thread
         let arc_events = Arc::clone(&self.events);
         let arc_sources: Arc<Mutex<Slab<IoSource>>> = Arc::clone(&self.sources);
         let handle = Arc::clone(&self.handle);
+        let stats = Arc::clone(&self.stats);
+
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        let uring = self.uring.clone();
 
         let handle = thread::Builder::new()
             .name("IoReactor".to_string())
@@ -127,14 +202,33 @@ impl Reactor {
                                 return;
                             }
 
+                            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+                            URING => {
+                                if let Some(uring) = uring.as_ref() {
+                                    uring.dispatch();
+                                }
+                            }
+
+                            #[cfg(unix)]
+                            SIGNAL => crate::signal::dispatch(),
+
                             _ => {
+                                stats.events.fetch_add(1, Ordering::Relaxed);
+                                if event.is_readable() {
+                                    stats.read_events.fetch_add(1, Ordering::Relaxed);
+                                }
+                                if event.is_writable() {
+                                    stats.write_events.fetch_add(1, Ordering::Relaxed);
+                                }
+
                                 let mut srcs = arc_sources.lock().expect("sources lock in loop failed!");
 
                                 let src = match srcs.get_mut(event.token().0) {
-                                    None => panic!(
-                                        "Received event for token {}, but no such source is present.",
-                                        event.token().0
-                                    ),
+                                    // Deregistered after the event was received.
+                                    None => {
+                                        debug!("event for removed source {}", event.token().0);
+                                        continue;
+                                    }
                                     Some(source) => source,
                                 };
 
@@ -150,6 +244,16 @@ impl Reactor {
         Ok(handle)
     }
 
+    /// Counters of the reactor.
+    pub(crate) fn stats(&self) -> &ReactorStats {
+        &self.stats
+    }
+
+    /// Amount of registered I/O sources.
+    pub(crate) fn sources(&self) -> usize {
+        self.sources.lock().expect("failed source lock").len()
+    }
+
     /// Registers a IO source in the reactor.
     pub fn register(&self, src: &mut impl Source, interest: Interest) -> IoResult<usize> {
         let mut sources = self.sources.lock().expect("failed source lock");
@@ -157,16 +261,49 @@ impl Reactor {
 
         self.handle.registry.register(src, Token(token), interest)?;
 
-        let _ = sources.insert(IoSource::new(token));
+        let _ = sources.insert(IoSource::new());
+        trace_event!(token, ?interest, "source registered");
         Ok(token)
     }
 
     /// Reregisters a IO source in the reactor.
+    #[allow(dead_code)]
     pub fn reregister(&self, src: &mut impl Source, token: usize, intr: Interest) -> IoResult<()> {
         //let sources = Reactor::get().sources.lock().expect("failed sources lock!");
         self.handle.registry.reregister(src, Token(token), intr)
     }
 
+    /// Removes a IO source from the reactor, dropping its wakers.
+    pub fn deregister(&self, src: &mut impl Source, token: usize) -> IoResult<()> {
+        let mut sources = self.sources.lock().expect("failed source lock");
+        let _ = sources.try_remove(token);
+        trace_event!(token, "source deregistered");
+
+        self.handle.registry.deregister(src)
+    }
+
+    /// Registers the signal pipe, once.
+    #[cfg(unix)]
+    pub(crate) fn enable_signals(&self) -> IoResult<()> {
+        use mio::unix::SourceFd;
+
+        if self.signals.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        let fd = crate::signal::pipe_fd()?;
+        let res = self
+            .handle
+            .registry
+            .register(&mut SourceFd(&fd), SIGNAL, Interest::READABLE);
+
+        if res.is_err() {
+            self.signals.store(false, Ordering::Release);
+        }
+
+        res
+    }
+
     pub fn attach_waker(&self, cx: &mut Context<'_>, token: Token, dir: Direction) {
         let mut sources = self.sources.lock().expect("failed sources lock!");
         let src = match sources.get_mut(token.0) {
@@ -174,7 +311,11 @@ impl Reactor {
             None => panic!("Trying to attach waker to an unregistered source!"),
         };
 
-        src.put(cx.waker(), dir);
+        let overflowed = src.put(cx.waker(), dir);
         drop(sources);
+
+        if overflowed {
+            self.stats.waker_overflows.fetch_add(1, Ordering::Relaxed);
+        }
     }
 }
@@ -0,0 +1,218 @@
+// Completion based driver on top of io_uring.
+//
+// The ring's file descriptor is registered in the `Reactor`,
+// which drains the completion queue whenever it becomes readable.
+use io_uring::{IoUring, squeue};
+use log::{debug, warn};
+use slab::Slab;
+
+use std::any::Any;
+use std::future::Future;
+use std::io;
+use std::os::fd::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Amount of submission queue entries.
+const RING_ENTRIES: u32 = 256;
+
+/// State of a submitted operation.
+enum Lifecycle {
+    // Submitted, nobody polled it yet.
+    Submitted,
+
+    // A task waits for the completion.
+    Waiting(Waker),
+
+    // The future was dropped, the data used by the kernel
+    // is kept alive until the operation completes.
+    Ignored(#[allow(dead_code)] Box<dyn Any + Send>),
+
+    // Completed with the result of the cqe.
+    Completed(i32),
+}
+
+struct Inner {
+    ring: IoUring,
+    ops: Slab<Lifecycle>,
+}
+
+/// Handle to the io_uring instance.
+pub(crate) struct Uring {
+    inner: Mutex<Inner>,
+    fd: RawFd,
+}
+
+impl Uring {
+    /// Sets up the ring.
+    ///
+    /// Fails when the kernel does not support io_uring or rejects the setup,
+    /// for example through seccomp or `kernel.io_uring_disabled`.
+    pub(crate) fn new() -> io::Result<Uring> {
+        let ring = IoUring::new(RING_ENTRIES)?;
+        let fd = ring.as_raw_fd();
+
+        Ok(Uring {
+            inner: Mutex::new(Inner {
+                ring,
+                ops: Slab::with_capacity(RING_ENTRIES as usize),
+            }),
+            fd,
+        })
+    }
+
+    pub(crate) fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Submits an operation.
+    ///
+    /// Hands `data` back if the submission failed.
+    ///
+    /// # Safety
+    ///
+    /// Every pointer inside `entry` must stay valid until the operation completes,
+    /// which is ensured by handing the pointed to data over as `data`.
+    pub(crate) unsafe fn submit_op<T: Send + 'static>(
+        self: &Arc<Self>,
+        entry: squeue::Entry,
+        data: T,
+    ) -> Result<Op<T>, (io::Error, T)> {
+        let mut inner = self.inner.lock().unwrap();
+        let key = inner.ops.insert(Lifecycle::Submitted);
+        let entry = entry.user_data(key as u64);
+
+        loop {
+            // Safety: upheld by the caller.
+            if unsafe { inner.ring.submission().push(&entry) }.is_ok() {
+                break;
+            }
+
+            // The queue is full, hand the pending entries to the kernel.
+            if let Err(e) = inner.ring.submit() {
+                inner.ops.remove(key);
+                return Err((e, data));
+            }
+        }
+
+        if let Err(e) = inner.ring.submit() {
+            // The entry is queued and will be submitted with the next one,
+            // so the operation stays valid.
+            warn!("failed submitting io_uring entries: {e}");
+        }
+
+        Ok(Op {
+            uring: Arc::clone(self),
+            key,
+            data: Some(data),
+        })
+    }
+
+    /// Drains the completion queue, waking the waiting tasks.
+    pub(crate) fn dispatch(&self) {
+        let mut wakers = Vec::new();
+        let mut inner = self.inner.lock().unwrap();
+        let Inner { ring, ops } = &mut *inner;
+
+        for cqe in ring.completion() {
+            let key = cqe.user_data() as usize;
+
+            let slot = match ops.get_mut(key) {
+                Some(slot) => slot,
+                None => {
+                    warn!("completion for unknown io_uring operation {key}");
+                    continue;
+                }
+            };
+
+            match std::mem::replace(slot, Lifecycle::Completed(cqe.result())) {
+                Lifecycle::Waiting(waker) => wakers.push(waker),
+                Lifecycle::Ignored(_) => drop(ops.remove(key)),
+                Lifecycle::Submitted | Lifecycle::Completed(_) => {}
+            }
+        }
+
+        drop(inner);
+        debug!("io_uring completions woke {} tasks", wakers.len());
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// Future of a submitted operation.
+///
+/// Resolves to the raw result of the operation and the data handed over on submission.
+pub(crate) struct Op<T: Send + 'static> {
+    uring: Arc<Uring>,
+    key: usize,
+    data: Option<T>,
+}
+
+// The data is handed to the kernel by pointer, but never pinned itself:
+// it lives on the heap or is owned by value, moving `Op` does not move it.
+impl<T: Send + 'static> Unpin for Op<T> {}
+
+impl<T: Send + 'static> Future for Op<T> {
+    type Output = (i32, T);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        let mut inner = me.uring.inner.lock().unwrap();
+        let slot = inner
+            .ops
+            .get_mut(me.key)
+            .expect("io_uring operation missing");
+
+        match slot {
+            Lifecycle::Completed(res) => {
+                let res = *res;
+                inner.ops.remove(me.key);
+                drop(inner);
+
+                let data = me
+                    .data
+                    .take()
+                    .expect("io_uring operation polled after completion");
+                Poll::Ready((res, data))
+            }
+
+            _ => {
+                *slot = Lifecycle::Waiting(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T: Send + 'static> Drop for Op<T> {
+    fn drop(&mut self) {
+        let data = match self.data.take() {
+            Some(data) => data,
+            // Already completed.
+            None => return,
+        };
+
+        let mut inner = self.uring.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = match inner.ops.get_mut(self.key) {
+            Some(slot) => slot,
+            None => return,
+        };
+
+        match slot {
+            Lifecycle::Completed(_) => drop(inner.ops.remove(self.key)),
+            _ => *slot = Lifecycle::Ignored(Box::new(data)),
+        }
+    }
+}
+
+/// Converts the result of a cqe into an `io::Result`.
+pub(crate) fn cqe_result(res: i32) -> io::Result<u32> {
+    if res < 0 {
+        Err(io::Error::from_raw_os_error(-res))
+    } else {
+        Ok(res as u32)
+    }
+}
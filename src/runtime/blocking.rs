@@ -0,0 +1,291 @@
+// Pool of threads for running blocking operations.
+use log::{error, info};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Maximum amount of blocking threads.
+pub(crate) const MAX_BLOCKING_THREADS: usize = 512;
+
+/// How long an idle blocking thread waits for work before exiting.
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct State {
+    // Jobs waiting for a thread.
+    queue: VecDeque<Job>,
+
+    // Threads waiting for a job.
+    idle: usize,
+
+    // Threads alive.
+    threads: usize,
+
+    // Set once the pool is shutting down.
+    shutdown: bool,
+
+    // Handles of all spawned threads, finished ones are pruned on spawn.
+    handles: Vec<JoinHandle<()>>,
+
+    // Used for naming the threads.
+    spawned: usize,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+    max_threads: usize,
+}
+
+/// Threads executing blocking closures off the worker threads.
+///
+/// Threads are spawned on demand, up to `max_threads`,
+/// and exit after being idle for a while.
+pub(crate) struct BlockingPool {
+    shared: Arc<Shared>,
+}
+
+impl BlockingPool {
+    pub(crate) fn new(max_threads: usize) -> BlockingPool {
+        BlockingPool {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    queue: VecDeque::new(),
+                    idle: 0,
+                    threads: 0,
+                    shutdown: false,
+                    handles: Vec::new(),
+                    spawned: 0,
+                }),
+                condvar: Condvar::new(),
+                max_threads,
+            }),
+        }
+    }
+
+    /// Runs `f` on a blocking thread.
+    ///
+    /// The returned future resolves to the output of `f`,
+    /// waking the awaiting task once it is available.
+    /// A panic inside `f` is resumed in the awaiting task.
+    pub(crate) fn spawn<F, T>(&self, f: F) -> BlockingTask<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot {
+            result: None,
+            waker: None,
+        }));
+
+        let completion = Completion {
+            slot: Arc::clone(&slot),
+            done: false,
+        };
+
+        let job = Box::new(move || {
+            let res = panic::catch_unwind(AssertUnwindSafe(f));
+            completion.complete(res);
+        });
+
+        self.schedule(job);
+
+        BlockingTask { slot }
+    }
+
+    fn schedule(&self, job: Job) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        if state.shutdown {
+            // Dropping the job marks its task as cancelled.
+            drop(state);
+            drop(job);
+            return;
+        }
+
+        state.queue.push_back(job);
+
+        if state.idle > 0 {
+            self.shared.condvar.notify_one();
+            return;
+        }
+
+        if state.threads == self.shared.max_threads {
+            return;
+        }
+
+        state.handles.retain(|handle| !handle.is_finished());
+
+        let shared = Arc::clone(&self.shared);
+        let res = thread::Builder::new()
+            .name(format!("lamp-blocking-{}", state.spawned))
+            .spawn(move || blocking_thread(shared));
+
+        match res {
+            Ok(handle) => {
+                state.threads += 1;
+                state.spawned += 1;
+                state.handles.push(handle);
+            }
+
+            // The job stays queued for an already running thread.
+            Err(e) if state.threads > 0 => error!("failed to spawn blocking thread: {e}"),
+            Err(e) => panic!("failed to spawn blocking thread: {e}"),
+        }
+    }
+
+    /// Stops the pool, waiting for the running jobs to finish.
+    ///
+    /// Jobs still in the queue are dropped and their tasks are cancelled.
+    pub(crate) fn shutdown(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.shutdown = true;
+
+        let queue = std::mem::take(&mut state.queue);
+        let handles = std::mem::take(&mut state.handles);
+        drop(state);
+
+        drop(queue);
+        self.shared.condvar.notify_all();
+
+        for handle in handles {
+            // Jobs catch their own panics, this should not fail.
+            let _ = handle.join();
+        }
+
+        info!("blocking pool shut down");
+    }
+
+    /// Amount of threads currently alive.
+    pub(crate) fn threads(&self) -> usize {
+        self.shared.state.lock().unwrap().threads
+    }
+}
+
+fn blocking_thread(shared: Arc<Shared>) {
+    let mut state = shared.state.lock().unwrap();
+
+    loop {
+        if let Some(job) = state.queue.pop_front() {
+            drop(state);
+            job();
+            state = shared.state.lock().unwrap();
+            continue;
+        }
+
+        if state.shutdown {
+            break;
+        }
+
+        state.idle += 1;
+        let (guard, timeout) = shared.condvar.wait_timeout(state, KEEP_ALIVE).unwrap();
+        state = guard;
+        state.idle -= 1;
+
+        if timeout.timed_out() && state.queue.is_empty() {
+            break;
+        }
+    }
+
+    state.threads -= 1;
+}
+
+enum Outcome<T> {
+    Done(thread::Result<T>),
+    Cancelled,
+}
+
+struct Slot<T> {
+    result: Option<Outcome<T>>,
+    waker: Option<Waker>,
+}
+
+/// Sending half of a blocking task,
+/// cancels the task if dropped before completing.
+struct Completion<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+    done: bool,
+}
+
+impl<T> Completion<T> {
+    fn complete(mut self, res: thread::Result<T>) {
+        self.send(Outcome::Done(res));
+        self.done = true;
+    }
+
+    fn send(&self, outcome: Outcome<T>) {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        slot.result = Some(outcome);
+        let waker = slot.waker.take();
+        drop(slot);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.send(Outcome::Cancelled);
+        }
+    }
+}
+
+/// Future resolving to the output of a closure run on the blocking pool.
+///
+/// Resumes the closure's panic if it panicked, and panics if the runtime
+/// shut down before running it.
+pub struct BlockingTask<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> fmt::Debug for BlockingTask<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingTask").finish_non_exhaustive()
+    }
+}
+
+impl<T> BlockingTask<T> {
+    /// Polls for the closure's output, `None` if the runtime shut down before running it.
+    ///
+    /// Resumes the closure's panic if it panicked.
+    pub(crate) fn poll_unless_cancelled(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut slot = self.slot.lock().unwrap();
+
+        match slot.result.take() {
+            Some(Outcome::Done(Ok(val))) => Poll::Ready(Some(val)),
+            Some(Outcome::Done(Err(payload))) => {
+                drop(slot);
+                panic::resume_unwind(payload)
+            }
+            Some(Outcome::Cancelled) => Poll::Ready(None),
+
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        match self.get_mut().poll_unless_cancelled(cx) {
+            Poll::Ready(Some(val)) => Poll::Ready(val),
+            Poll::Ready(None) => panic!("blocking task cancelled, the runtime shut down"),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -53,6 +53,28 @@ impl<T> CxBox<T> {
         }
     }
 
+    /// Swaps the value inside, returning the previous one.
+    ///
+    /// Same as `clean`, this is only safe while calling from one thread.
+    pub(crate) unsafe fn replace(&self, val: Option<T>) -> Option<T> {
+        let prev = if self.bool.swap(false, Ordering::SeqCst) {
+            // Safety:
+            //
+            // The bool was `true`, the data is initialised
+            // and it isn't read again until it is overwritten.
+            Some(unsafe { (*self.data.get()).assume_init_read() })
+        } else {
+            None
+        };
+
+        if let Some(val) = val {
+            unsafe { (*self.data.get()).write(val) };
+            self.bool.store(true, Ordering::SeqCst);
+        }
+
+        prev
+    }
+
     /// This is only safe while calling from one thread.
     /// this performs an un-atomic operation on the data inside
     /// Partial safety is provided by first setting the bool inside to false
@@ -126,6 +148,7 @@ mod tests {
         };
 
         assert!(CX_BOX.set(126).is_ok(), "set failed");
+        assert!(CX_BOX.set(127).is_err(), "set twice");
         assert!(
             CX_BOX.get_ref().is_some_and(|val| val == &126),
             "invalid or missing value"
@@ -0,0 +1,129 @@
+use crate::task::Id;
+use crate::task::task::{Header, NOTIFIED, RUNNING, SCHEDULED};
+
+use std::fmt::{self, Display, Formatter};
+use std::panic::Location;
+use std::sync::atomic::Ordering;
+
+/// Snapshot of a runtime's tasks, obtained from [`Handle::dump`](super::Handle::dump).
+///
+/// Its `Display` impl lists them one per line.
+#[derive(Debug, Clone)]
+pub struct Dump {
+    tasks: Vec<TaskDump>,
+}
+
+impl Dump {
+    pub(crate) fn new(tasks: Vec<TaskDump>) -> Dump {
+        Dump { tasks }
+    }
+
+    /// The live tasks, in no particular order.
+    pub fn tasks(&self) -> &[TaskDump] {
+        &self.tasks
+    }
+}
+
+impl Display for Dump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for task in &self.tasks {
+            writeln!(f, "{task}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What a task was doing when the [`Dump`] was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    /// Waiting to be woken.
+    Idle,
+
+    /// Woken, waiting for a worker to poll it.
+    Scheduled,
+
+    /// Being polled by a worker.
+    Running,
+}
+
+impl Display for TaskState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            TaskState::Idle => "idle",
+            TaskState::Scheduled => "scheduled",
+            TaskState::Running => "running",
+        };
+
+        f.write_str(state)
+    }
+}
+
+/// A task of a [`Dump`].
+#[derive(Debug, Clone)]
+pub struct TaskDump {
+    id: Id,
+    name: Option<String>,
+    state: TaskState,
+    polls: u64,
+    location: &'static Location<'static>,
+}
+
+impl TaskDump {
+    pub(crate) fn new(header: &Header) -> TaskDump {
+        let state = header.state.load(Ordering::Acquire);
+        let state = match state {
+            _ if state & RUNNING != 0 => TaskState::Running,
+            _ if state & (SCHEDULED | NOTIFIED) != 0 => TaskState::Scheduled,
+            _ => TaskState::Idle,
+        };
+
+        TaskDump {
+            id: header.meta.id,
+            name: header.meta.name.as_deref().map(Into::into),
+            state,
+            polls: header.polls.load(Ordering::Relaxed),
+            location: header.meta.location,
+        }
+    }
+
+    /// The task's id.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The name given with [`Builder::name`](crate::task::Builder::name).
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// What the task was doing.
+    pub fn state(&self) -> TaskState {
+        self.state
+    }
+
+    /// Amount of times its future was polled.
+    pub fn polls(&self) -> u64 {
+        self.polls
+    }
+
+    /// Where it was spawned.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl Display for TaskDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "task {}", self.id)?;
+        if let Some(name) = self.name() {
+            write!(f, " {name:?}")?;
+        }
+
+        write!(
+            f,
+            ": {}, {} polls, spawned at {}",
+            self.state, self.polls, self.location
+        )
+    }
+}
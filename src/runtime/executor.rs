@@ -1,48 +1,78 @@
 use crate::reactor::{Handle, Reactor};
 use crate::task::handle::TaskHandle;
 use crate::task::note::Note;
-use crate::task::task::Task;
-use log::{error, info};
+use crate::task::task::{Meta, Scheduler, Task};
+use crate::trace::trace_event;
+use log::{debug, error, info};
 use slab::Slab;
 use std::cell::{Cell, UnsafeCell};
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
 use std::mem::MaybeUninit;
 use std::panic;
 use std::ptr::addr_of_mut;
-use std::sync::{Arc, OnceLock, RwLock, Weak, atomic::AtomicBool, atomic::Ordering, mpsc};
-use std::thread::JoinHandle;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock, Weak, mpsc};
+use std::task::{Poll, Waker};
+use std::thread::{self, JoinHandle};
 use std::thread_local;
+use std::time::{Duration, Instant};
 
-use super::threads::ThreadPool;
+use super::blocking::{BlockingPool, BlockingTask, MAX_BLOCKING_THREADS};
+use super::dump::{Dump, TaskDump};
+use super::threads::{ThreadPool, WorkerStats};
 
 use super::cx_box::CxBox;
 
 thread_local! {
     static EXEC: CxBox<Weak<ExecutorHandle>> = CxBox::new();
-}
 
-struct ChannelPair<T> {
-    s: mpsc::Sender<T>,
-    r: mpsc::Receiver<T>,
+    // Set on threads driving a runtime, workers and `block_on`.
+    static DRIVING: Cell<bool> = const { Cell::new(false) };
 }
 
-unsafe impl<T> Send for ChannelPair<T> {}
-unsafe impl<T> Sync for ChannelPair<T> {}
+/// Marks the current thread as driving a runtime, until dropped.
+pub(crate) struct Driving(());
 
-impl<T> ChannelPair<T> {
-    fn new() -> ChannelPair<T> {
-        let (s, r) = mpsc::channel();
+impl Driving {
+    /// Returns `None` if the thread already drives one.
+    pub(crate) fn enter() -> Option<Driving> {
+        match DRIVING.with(|cell| cell.replace(true)) {
+            true => None,
+            false => Some(Driving(())),
+        }
+    }
+}
 
-        ChannelPair { s, r }
+impl Drop for Driving {
+    fn drop(&mut self) {
+        DRIVING.with(|cell| cell.set(false));
     }
 }
 
+/// Tells a worker to stop.
+const STOP: u64 = u64::MAX;
+
+/// Id of the task polled by `block_on`.
+const MAIN: u64 = u64::MAX - 1;
+
+/// Tells a worker to cancel every task left in the storage.
+const CANCEL_ALL: u64 = u64::MAX - 2;
+
+/// Id given to tasks spawned after the shutdown started, which never run.
+const REJECTED: u64 = u64::MAX - 3;
+
+/// How often a draining shutdown checks whether the tasks have finished.
+const DRAIN_TICK: Duration = Duration::from_millis(10);
+
+/// Shared state of a runtime.
+///
+/// Every runtime has its own workers, reactor and blocking pool,
+/// the handle spawns onto this one from any thread.
 pub struct ExecutorHandle {
     // Task queue
     storage: RwLock<Slab<Task>>,
 
-    // Channels for the main thread.
-    chan: ChannelPair<Note>,
-
     // Might be useful later
     // Handle to reactor
     #[allow(dead_code)]
@@ -53,6 +83,24 @@ pub struct ExecutorHandle {
 
     // I/O Reactor
     reactor: Reactor,
+
+    // Threads for blocking operations
+    blocking: BlockingPool,
+
+    // Set once the runtime starts shutting down, new spawns are rejected.
+    closed: AtomicBool,
+
+    // Tasks cancelled by the shutdown, either rejected or dropped by a worker.
+    cancelled: AtomicUsize,
+
+    // Tasks spawned since the runtime started, rejected ones excluded.
+    spawned: AtomicU64,
+
+    // Tasks which completed on a worker, including the aborted ones.
+    completed: AtomicU64,
+
+    // Tasks which a worker found aborted through their handle.
+    aborted: AtomicUsize,
 }
 
 unsafe impl Sync for ExecutorHandle {}
@@ -72,6 +120,265 @@ impl ExecutorHandle {
     {
         function(unsafe { &*self.pool.get() })
     }
+
+    /// Spawns a future onto this runtime.
+    ///
+    /// Unlike [`Executor::spawn`], this works from any thread, including ones
+    /// belonging to another runtime.
+    #[track_caller]
+    pub fn spawn<F>(self: &Arc<Self>, f: F) -> TaskHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_named(f, None)
+    }
+
+    /// Spawns a future onto this runtime, naming its task.
+    #[track_caller]
+    pub(crate) fn spawn_named<F>(
+        self: &Arc<Self>,
+        f: F,
+        name: Option<&str>,
+    ) -> TaskHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let meta = Meta::new(name);
+        let scheduler = Scheduler::Runtime(Arc::downgrade(self));
+
+        // Checked under the lock, so that nothing is stored once the shutdown took over.
+        let mut storage = self.storage.write().unwrap();
+        if self.closed.load(Ordering::SeqCst) {
+            drop(storage);
+
+            // The runtime is shutting down, the future is dropped right away.
+            let (task, _, handle) = Task::new(f, REJECTED, scheduler, meta);
+            task.cancel();
+            self.cancelled.fetch_add(1, Ordering::SeqCst);
+
+            return handle;
+        }
+
+        let num = storage.vacant_key();
+
+        let (task, note, handle) = Task::new(f, num as u64, scheduler, meta);
+        storage.insert(task);
+        drop(storage);
+
+        self.spawned.fetch_add(1, Ordering::Relaxed);
+
+        self.schedule(note);
+
+        handle
+    }
+
+    /// Makes this runtime the current one on this thread,
+    /// until the guard is dropped.
+    ///
+    /// [`Executor::spawn`] and the I/O types use the current runtime.
+    pub fn enter(self: &Arc<Self>) -> EnterGuard {
+        // Safety:
+        //
+        // The thread local is only ever accessed from its own thread.
+        let prev = EXEC.with(|cell| unsafe { cell.replace(Some(Arc::downgrade(self))) });
+
+        EnterGuard {
+            prev,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Sends a woken task to a worker.
+    pub(crate) fn schedule(self: &Arc<Self>, note: Note) {
+        // Fails once the workers stopped, nobody is left to poll the task.
+        if self.pool_fn(|pool| pool.deploy(note)).is_err() {
+            debug!("note after the workers stopped (id: {})", note.0);
+        }
+    }
+
+    /// Amount of worker threads.
+    pub(crate) fn num_workers(self: &Arc<Self>) -> usize {
+        self.pool_fn(|pool| pool.workers())
+    }
+
+    /// Amount of tasks in the storage.
+    pub(crate) fn live_tasks(&self) -> usize {
+        self.storage.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Amount of threads alive in the blocking pool.
+    pub(crate) fn blocking_threads(&self) -> usize {
+        self.blocking.threads()
+    }
+
+    /// Amount of tasks spawned since the runtime started.
+    pub(crate) fn spawned_tasks(&self) -> u64 {
+        self.spawned.load(Ordering::Relaxed)
+    }
+
+    /// Amount of tasks which completed on a worker.
+    pub(crate) fn completed_tasks(&self) -> u64 {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// Notes sent to the workers and not handled yet, summed over the workers.
+    pub(crate) fn queued_notes(self: &Arc<Self>) -> u64 {
+        self.pool_fn(|pool| pool.stats().map(WorkerStats::queue_depth).sum())
+    }
+
+    /// Reads the counters of a worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such worker.
+    pub(crate) fn worker_stats<F, T>(self: &Arc<Self>, worker: usize, f: F) -> T
+    where
+        F: FnOnce(&WorkerStats) -> T,
+    {
+        self.pool_fn(|pool| match pool.stats().nth(worker) {
+            Some(stats) => f(stats),
+            None => panic!("no worker at index {worker}"),
+        })
+    }
+
+    /// Takes a snapshot of the live tasks.
+    pub(crate) fn dump(&self) -> Dump {
+        let storage = self.storage.read().unwrap_or_else(|e| e.into_inner());
+        let tasks = storage
+            .iter()
+            .map(|(_, task)| TaskDump::new(task.header()))
+            .collect();
+
+        Dump::new(tasks)
+    }
+
+    /// Runs a blocking closure on the blocking pool.
+    pub(crate) fn spawn_blocking<F, T>(&self, f: F) -> BlockingTask<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.blocking.spawn(f)
+    }
+
+    /// Rejects the spawns from now on.
+    fn close(&self) {
+        let _storage = self.storage.write().unwrap_or_else(|e| e.into_inner());
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Lets the tasks run until they are all done or the deadline passes,
+    /// then cancels the remaining ones and stops every thread.
+    fn finish(self: &Arc<Self>, timeout: Duration, reactor: JoinHandle<()>) -> ShutdownReport {
+        let deadline = Instant::now() + timeout;
+
+        // Workers count the tasks while removing them, under the lock.
+        let snapshot = || {
+            let storage = self.storage.read().unwrap_or_else(|e| e.into_inner());
+            let completed = self.completed.load(Ordering::Relaxed);
+            (storage.len(), completed, self.aborted.load(Ordering::SeqCst))
+        };
+        let (_, completed, aborted) = snapshot();
+
+        while snapshot().0 != 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            thread::sleep((deadline - now).min(DRAIN_TICK));
+        }
+
+        // Removed tasks are counted as completed, the aborted ones included.
+        let (_, now_completed, now_aborted) = snapshot();
+        let aborted = now_aborted - aborted;
+        let completed = (now_completed - completed) as usize - aborted;
+
+        self.pool_fn(|pool| {
+            let _ = pool.deploy(Note(CANCEL_ALL));
+            pool.broadcast(Note(STOP));
+            let _ = pool.join();
+        });
+
+        // I/O and blocking work may be needed by the destructors, so they go last.
+        let _ = self.handle.shutdown();
+        self.blocking.shutdown();
+        drop(reactor);
+
+        ShutdownReport {
+            completed,
+            cancelled: self.cancelled.load(Ordering::SeqCst) + aborted,
+        }
+    }
+
+    /// Cancels every task in the storage, dropping their futures.
+    fn cancel_all(&self) {
+        let tasks: Vec<Task> = self
+            .storage
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain()
+            .collect();
+
+        // The lock is released first, as the destructors might try to spawn.
+        for task in &tasks {
+            task.cancel();
+        }
+
+        self.cancelled.fetch_add(tasks.len(), Ordering::SeqCst);
+        info!("cancelled {} tasks", tasks.len());
+    }
+}
+
+/// How many tasks a shutdown let finish and how many it cancelled.
+///
+/// Returned by [`Executor::shutdown_timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownReport {
+    completed: usize,
+    cancelled: usize,
+}
+
+impl ShutdownReport {
+    /// Tasks that completed while the runtime was shutting down.
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+
+    /// Tasks whose future was dropped before completing,
+    /// including the ones spawned after the shutdown started.
+    pub fn cancelled(&self) -> usize {
+        self.cancelled
+    }
+}
+
+/// Guard returned by [`Executor::enter`] and [`ExecutorHandle::enter`].
+///
+/// Restores the previously current runtime when dropped.
+pub struct EnterGuard {
+    prev: Option<Weak<ExecutorHandle>>,
+
+    // Must be dropped on the thread it was created on.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Debug for EnterGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnterGuard").finish_non_exhaustive()
+    }
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        let prev = self.prev.take();
+
+        // Safety:
+        //
+        // The guard is not `Send`, we are on the thread that created it.
+        let _ = EXEC.with(|cell| unsafe { cell.replace(prev) });
+    }
 }
 
 pub enum RtState {
@@ -90,15 +397,19 @@ pub struct Executor {
 
 impl Executor {
     fn new_base(amnt: usize) -> Executor {
-        let chan = ChannelPair::new();
         let (reactor, handle) = Reactor::new().expect("failed to create reactor");
 
         let handle = Arc::new(ExecutorHandle {
             storage: RwLock::new(Slab::with_capacity(4096)),
-            chan,
             handle,
             pool: UnsafeCell::new(ThreadPool::new(amnt)),
             reactor,
+            blocking: BlockingPool::new(MAX_BLOCKING_THREADS),
+            closed: AtomicBool::new(false),
+            cancelled: AtomicUsize::new(0),
+            spawned: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            aborted: AtomicUsize::new(0),
         });
 
         Executor {
@@ -109,15 +420,12 @@ impl Executor {
 
     pub fn new(amnt: usize) -> Executor {
         let mut runtime = Executor::new_base(amnt);
-        EXEC.with(|cell| {
-            cell.set(Arc::downgrade(&runtime.handle))
-                .expect("failed setting global handle")
-        });
+        let rt = Arc::downgrade(&runtime.handle);
 
         unsafe {
             let amnt = (*runtime.handle.pool.get()).amount;
             (*runtime.handle.pool.get())
-                .start(amnt, thread_function)
+                .start(amnt, thread_function, rt)
                 .expect("failed to start thread pool");
         };
 
@@ -133,6 +441,29 @@ impl Executor {
         runtime
     }
 
+    /// Obtains a handle to this runtime.
+    pub fn handle(&self) -> super::Handle {
+        super::Handle::new(Arc::clone(&self.handle))
+    }
+
+    /// Makes this runtime the current one on this thread, until the guard is dropped.
+    ///
+    /// Needed to create I/O types outside of `block_on`.
+    pub fn enter(&self) -> EnterGuard {
+        self.handle.enter()
+    }
+
+    /// Obtains the current runtime, if there is one still running.
+    pub(crate) fn try_get() -> Option<Arc<ExecutorHandle>> {
+        EXEC.with(|cell| cell.get_ref().and_then(Weak::upgrade))
+    }
+
+    /// Obtains the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics outside of a runtime's context: its workers, `block_on` or
+    /// [`Executor::enter`].
     #[inline]
     pub fn get() -> Arc<ExecutorHandle> {
         EXEC.with(
@@ -143,54 +474,36 @@ impl Executor {
         )
     }
 
+    #[track_caller]
     pub fn block_on<F>(&mut self, f: F) -> Result<(), RtState>
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        let exec = Executor::get();
+        let _guard = self.handle.enter();
+        let _driving = Driving::enter();
 
-        let task = Task::new_alone(f, u64::MAX - 1, exec.chan.s.clone());
+        // The main task is polled here, its notes come back through this channel.
+        let (sender, receiver) = mpsc::channel();
+        let meta = Meta::new(Some("main"));
+        let (task, _, handle) = Task::new(f, MAIN, Scheduler::Channel(sender), meta);
 
         let mut state = RtState::Good;
 
-        loop {
-            let out = panic::catch_unwind(|| task.poll());
-
-            let ready = match out {
-                // As the `Err` can contain the panic payload
-                // we ignore it.
-                // with debug assertions, we will attempt to `dbg!` it.
-                Err(_err) => {
-                    error!("main task panicked");
-                    state = RtState::MainTaskPanicked;
-
-                    #[cfg(debug_assertions)]
-                    let _ = dbg!(_err.downcast::<String>());
-
-                    break;
-                }
-
-                Ok(ready) => ready,
-            };
-
-            if ready {
-                break;
-            };
-
-            'inner: loop {
-                let n = exec.chan.r.recv().expect("receiver failed at block_on");
+        while !task.poll() {
+            // The task holds a sender, this can't fail.
+            let n = receiver.recv().expect("receiver failed at block_on");
+            info!("woke up main task, notif id: {}", n.0);
+        }
 
-                if n.0 != u64::MAX - 1 {
-                    self.handle.pool_fn(|pool| {
-                        let _ = pool.deploy(n);
-                    });
-                } else {
-                    break 'inner;
-                }
+        // As the error can contain the panic payload we ignore it.
+        // with debug assertions, we will attempt to `dbg!` it.
+        if let Poll::Ready(Err(_err)) = handle.poll_join(Waker::noop()) {
+            error!("main task panicked");
+            state = RtState::MainTaskPanicked;
 
-                info!("woke up thread, notif id: {}", n.0);
-            }
+            #[cfg(debug_assertions)]
+            let _ = dbg!(_err);
         }
 
         match state {
@@ -200,46 +513,65 @@ impl Executor {
         }
     }
 
-    pub fn shutdown(mut self) {
-        let exec = Executor::get();
-        let _ = exec.handle.shutdown();
-        exec.pool_fn(|pool| {
-            let _ = pool.broadcast(Note(u64::MAX));
-            let _ = pool.join();
-        });
+    /// Shuts the runtime down, cancelling every task that is still running.
+    ///
+    /// Same as [`Executor::shutdown_timeout`] with a zero timeout.
+    pub fn shutdown(self) {
+        let _ = self.shutdown_timeout(Duration::ZERO);
+    }
+
+    /// Shuts the runtime down, giving the spawned tasks up to `timeout` to finish.
+    ///
+    /// Tasks spawned after the shutdown started are rejected right away: their future
+    /// is dropped and their handle fails. The tasks still running at the deadline are cancelled,
+    /// their futures are dropped on the worker threads, so their destructors run.
+    pub fn shutdown_timeout(self, timeout: Duration) -> ShutdownReport {
+        let exec = Arc::clone(&self.handle);
+        exec.close();
 
         // Safety:
         //
         // Nobody has the handle as we have consumed the runtime.
-        unsafe { self.reactor_handle.assume_init_drop() };
+        let reactor = unsafe { self.reactor_handle.assume_init_read() };
+        exec.finish(timeout, reactor)
+    }
+
+    /// Shuts the runtime down without waiting for anything.
+    ///
+    /// New spawns are rejected, the remaining tasks are cancelled and the threads
+    /// are stopped in the background, after this returns.
+    pub fn shutdown_background(self) {
+        let exec = Arc::clone(&self.handle);
+        exec.close();
 
         // Safety:
         //
-        // Everything was shutdown, no other thread might accidentally execute this.
-        unsafe { EXEC.with(|c| c.clean()) };
+        // Nobody has the handle as we have consumed the runtime.
+        let reactor = unsafe { self.reactor_handle.assume_init_read() };
+        let spawned = thread::Builder::new()
+            .name("lamp-shutdown".into())
+            .spawn(move || {
+                let _ = exec.finish(Duration::ZERO, reactor);
+            });
+
+        if let Err(err) = spawned {
+            error!("failed to spawn the shutdown thread: {err}");
+        }
     }
 
-    /// Spawn a future onto the Runtime.
+    /// Spawn a future onto the current Runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics outside of a runtime's context, see [`Executor::get`].
     #[inline]
+    #[track_caller]
     pub fn spawn<F>(f: F) -> TaskHandle<F::Output>
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        let exec = Executor::get();
-
-        let mut storage = exec.storage.write().unwrap();
-        let num = storage.vacant_key();
-
-        let (task, note, handle) = Task::new(f, num as u64, exec.chan.s.clone());
-        storage.insert(task);
-        drop(storage);
-
-        // If it fails, something terrible happened.
-        exec.pool_fn(|pool| pool.deploy(note))
-            .expect("failed to send note to runtime");
-
-        handle
+        Executor::get().spawn(f)
     }
 }
 
@@ -250,10 +582,35 @@ impl std::ops::Drop for Executor {
 fn thread_function(
     rt_weak: Weak<ExecutorHandle>,
     r: mpsc::Receiver<Note>,
-    boolean: Arc<AtomicBool>,
+    stats: Arc<WorkerStats>,
 ) {
-    while let Ok(n) = r.recv() {
-        boolean.store(true, Ordering::SeqCst);
+    // Lets tasks running on this worker reach the runtime.
+    EXEC.with(|cell| {
+        cell.set(rt_weak.clone())
+            .expect("failed setting worker's global handle")
+    });
+    let _driving = Driving::enter();
+
+    loop {
+        let n = match r.try_recv() {
+            Ok(n) => n,
+
+            // Nothing to do, the worker parks until the next note.
+            Err(mpsc::TryRecvError::Empty) => {
+                stats.parks.fetch_add(1, Ordering::Relaxed);
+                trace_event!("worker parked");
+
+                match r.recv() {
+                    Ok(n) => n,
+                    Err(_) => break,
+                }
+            }
+
+            Err(mpsc::TryRecvError::Disconnected) => break,
+        };
+
+        stats.occupied.store(true, Ordering::SeqCst);
+        let started = Instant::now();
 
         let rt = match rt_weak.upgrade() {
             // The runtime has been dropped.
@@ -262,21 +619,237 @@ fn thread_function(
             Some(rt) => rt,
         };
 
-        if n.0 == u64::MAX {
+        if n.0 == STOP {
             break;
         }
 
-        let storage = rt.storage.read().unwrap();
-        let task = storage.get(n.0 as usize).unwrap();
-        let ready = task.poll();
-        drop(storage);
+        handle_note(&rt, n, &stats);
+
+        let busy = started.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+        stats.busy_nanos.fetch_add(busy, Ordering::Relaxed);
+        stats.handled.fetch_add(1, Ordering::Relaxed);
+        stats.occupied.store(false, Ordering::SeqCst);
+    }
+}
 
-        if ready {
-            let mut st = rt.storage.write().unwrap();
+// Handles a note received by a worker, other than `STOP`.
+fn handle_note(rt: &ExecutorHandle, n: Note, stats: &WorkerStats) {
+    if n.0 == CANCEL_ALL {
+        rt.cancel_all();
+        return;
+    }
+
+    // The lock is released before polling, so that the task can spawn.
+    let storage = rt.storage.read().unwrap();
+    let task = match storage.get(n.0 as usize) {
+        Some(task) => task.share(),
+
+        // Woken after it already completed, e.g. by a stale I/O waker.
+        None => {
+            debug!("note for finished task (id: {})", n.0);
+            return;
+        }
+    };
+    drop(storage);
+
+    stats.polls.fetch_add(1, Ordering::Relaxed);
+    if task.poll() {
+        let mut st = rt.storage.write().unwrap();
+
+        // The slot may have been reused if the task got cancelled.
+        if st.get(n.0 as usize).is_some_and(|t| t.is(task)) {
             let _ = st.remove(n.0 as usize);
+            rt.completed.fetch_add(1, Ordering::Relaxed);
+            if task.was_cancelled() {
+                rt.aborted.fetch_add(1, Ordering::SeqCst);
+            }
             info!("removed task (id: {})", n.0);
         }
+    }
+    task.ref_destroy();
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::Executor;
+    use crate::runtime::Handle;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    // Sets the flag when dropped.
+    struct Guard(Arc<AtomicBool>);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn shutdown_drops_pending_tasks() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let guard = Guard(Arc::clone(&dropped));
+
+        let mut exec = Executor::new(1);
+        let _ = exec.block_on(async move {
+            drop(Executor::spawn(async move {
+                let _guard = guard;
+                std::future::pending::<()>().await;
+            }));
+        });
+
+        let report = exec.shutdown_timeout(Duration::from_millis(20));
+        assert_eq!(report.completed(), 0);
+        assert_eq!(report.cancelled(), 1);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn shutdown_lets_tasks_finish() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&finished);
+
+        let mut exec = Executor::new(1);
+        let _ = exec.block_on(async move {
+            drop(Executor::spawn(async move {
+                Handle::current()
+                    .spawn_blocking(|| thread::sleep(Duration::from_millis(50)))
+                    .await;
+                flag.store(true, Ordering::SeqCst);
+            }));
+        });
+
+        let report = exec.shutdown_timeout(Duration::from_secs(5));
+        assert_eq!(report.completed(), 1);
+        assert_eq!(report.cancelled(), 0);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn shutdown_counts_aborted_tasks_as_cancelled() {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let mut exec = Executor::new(1);
+        let _ = exec.block_on(async move {
+            let handle = Executor::spawn(std::future::pending::<()>());
+            tx.send(handle).unwrap();
+        });
+
+        // Aborted while the shutdown waits for the tasks to finish.
+        let handle = rx.recv().unwrap();
+        let aborter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            handle.abort();
+        });
+
+        let report = exec.shutdown_timeout(Duration::from_secs(5));
+        aborter.join().unwrap();
+
+        assert_eq!(report.completed(), 0);
+        assert_eq!(report.cancelled(), 1);
+    }
+
+    #[test]
+    fn shutdown_rejects_new_spawns() {
+        struct Respawn;
+
+        impl Drop for Respawn {
+            fn drop(&mut self) {
+                drop(Executor::spawn(async {}));
+            }
+        }
+
+        let mut exec = Executor::new(1);
+        let _ = exec.block_on(async {
+            drop(Executor::spawn(async {
+                let _respawn = Respawn;
+                std::future::pending::<()>().await;
+            }));
+        });
+
+        // The pending task, then the one its destructor tried to spawn.
+        let report = exec.shutdown_timeout(Duration::ZERO);
+        assert_eq!(report.cancelled(), 2);
+    }
+
+    #[test]
+    fn shutdown_background_cancels_tasks() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let guard = Guard(Arc::clone(&dropped));
+
+        let mut exec = Executor::new(1);
+        let _ = exec.block_on(async move {
+            drop(Executor::spawn(async move {
+                let _guard = guard;
+                std::future::pending::<()>().await;
+            }));
+        });
+        exec.shutdown_background();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !dropped.load(Ordering::SeqCst) {
+            assert!(Instant::now() < deadline, "task was never dropped");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn two_runtimes_on_one_thread() {
+        let mut first = Executor::new(1);
+        let mut second = Executor::new(1);
+        let handle = second.handle();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let _ = first.block_on(async move {
+            let value = handle.spawn(async { 21 }).await;
+            tx.send(value * 2).unwrap();
+        });
+        assert_eq!(rx.recv().unwrap(), 42);
+
+        let _ = second.block_on(async { Executor::spawn(async {}).await });
+
+        first.shutdown();
+        second.shutdown();
+    }
+
+    #[test]
+    fn spawn_from_another_thread() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            drop(handle.spawn(async move {
+                let inner = Executor::spawn(async { 7 }).await;
+                tx.send(inner).unwrap();
+            }));
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 7);
+        exec.shutdown();
+    }
+
+    #[test]
+    fn enter_restores_previous_runtime() {
+        let first = Executor::new(1);
+        let second = Executor::new(1);
+
+        {
+            let _outer = first.enter();
+            {
+                let _inner = second.enter();
+                assert!(Arc::ptr_eq(&Executor::get(), second.handle().inner()));
+            }
+            assert!(Arc::ptr_eq(&Executor::get(), first.handle().inner()));
+        }
+        assert!(std::panic::catch_unwind(Executor::get).is_err());
 
-        boolean.store(false, Ordering::SeqCst)
+        first.shutdown();
+        second.shutdown();
     }
 }
@@ -0,0 +1,243 @@
+use super::handle::Handle;
+use super::metrics::RuntimeMetrics;
+use crate::io::{AsyncReadExt, AsyncWriteExt, TcpListener, TcpStream};
+
+use log::debug;
+
+use std::fmt::{Display, Write};
+use std::io;
+
+/// Content type of the rendered metrics.
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Longest request head accepted, requests for the metrics are tiny.
+const MAX_REQUEST: usize = 8 * 1024;
+
+/// Renders the metrics in the OpenMetrics text format.
+pub(crate) fn render(metrics: &RuntimeMetrics) -> String {
+    let mut out = String::new();
+    let workers = 0..metrics.num_workers();
+
+    counter(&mut out, "lamp_tasks_spawned", "Tasks spawned.").sample("", metrics.spawned_tasks());
+    counter(&mut out, "lamp_tasks_completed", "Tasks completed.")
+        .sample("", metrics.completed_tasks());
+    gauge(&mut out, "lamp_tasks_live", "Tasks not completed yet.").sample("", metrics.live_tasks());
+
+    let mut polls = counter(&mut out, "lamp_worker_polls", "Task polls by worker.");
+    for w in workers.clone() {
+        polls.sample(&worker(w), metrics.worker_polls(w));
+    }
+
+    let mut parks = counter(&mut out, "lamp_worker_parks", "Times a worker parked.");
+    for w in workers.clone() {
+        parks.sample(&worker(w), metrics.worker_park_count(w));
+    }
+
+    let mut busy = counter(
+        &mut out,
+        "lamp_worker_busy_seconds",
+        "Time a worker was busy.",
+    );
+    for w in workers.clone() {
+        busy.sample(&worker(w), metrics.worker_busy_duration(w).as_secs_f64());
+    }
+
+    let mut depth = gauge(
+        &mut out,
+        "lamp_worker_queue_depth",
+        "Notes queued for a worker.",
+    );
+    for w in workers {
+        depth.sample(&worker(w), metrics.worker_local_queue_depth(w));
+    }
+
+    counter(
+        &mut out,
+        "lamp_reactor_events",
+        "Reactor events by direction.",
+    )
+    .sample("direction=\"read\"", metrics.reactor_read_events())
+    .sample("direction=\"write\"", metrics.reactor_write_events());
+    gauge(&mut out, "lamp_io_sources", "I/O sources in the reactor.")
+        .sample("", metrics.registered_sources());
+    counter(&mut out, "lamp_waker_overflows", "Full waker lists.")
+        .sample("", metrics.waker_overflows());
+    gauge(
+        &mut out,
+        "lamp_blocking_threads",
+        "Threads of the blocking pool.",
+    )
+    .sample("", metrics.blocking_threads());
+
+    out.push_str("# EOF\n");
+    out
+}
+
+fn worker(worker: usize) -> String {
+    format!("worker=\"{worker}\"")
+}
+
+fn counter<'a>(out: &'a mut String, name: &str, help: &str) -> Family<'a> {
+    Family::new(out, name, "counter", help, format!("{name}_total"))
+}
+
+fn gauge<'a>(out: &'a mut String, name: &str, help: &str) -> Family<'a> {
+    Family::new(out, name, "gauge", help, name.to_string())
+}
+
+/// Metric family being written, its samples follow its metadata.
+struct Family<'a> {
+    out: &'a mut String,
+    sample: String,
+}
+
+impl<'a> Family<'a> {
+    fn new(out: &'a mut String, name: &str, kind: &str, help: &str, sample: String) -> Self {
+        // Writing to a `String` can't fail.
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        let _ = writeln!(out, "# HELP {name} {help}");
+
+        Family { out, sample }
+    }
+
+    fn sample(&mut self, labels: &str, value: impl Display) -> &mut Self {
+        let name = &self.sample;
+        let _ = match labels {
+            "" => writeln!(self.out, "{name} {value}"),
+            _ => writeln!(self.out, "{name}{{{labels}}} {value}"),
+        };
+
+        self
+    }
+}
+
+/// Answers the connections of `listener`, see [`Handle::serve_metrics`].
+pub(crate) async fn serve(handle: &Handle, listener: TcpListener) -> io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let server = handle.clone();
+
+        // Each connection gets its own task, so that a client which
+        // never finishes its request doesn't hold up the others.
+        drop(handle.spawn(async move {
+            if let Err(e) = respond(&server, &mut stream).await {
+                debug!("failed answering a metrics request: {e}");
+            }
+        }));
+    }
+}
+
+async fn respond(handle: &Handle, stream: &mut TcpStream) -> io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+
+    // Only the head matters, the body of a request is ignored.
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST {
+            return reply(
+                stream,
+                "431 Request Header Fields Too Large",
+                "text/plain",
+                "",
+            )
+            .await;
+        }
+
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+
+        head.extend_from_slice(&buf[..n]);
+    }
+
+    let line = head.split(|&b| b == b'\r').next().unwrap_or_default();
+    let mut parts = line.split(|&b| b == b' ');
+
+    match (parts.next(), parts.next()) {
+        (Some(b"GET"), Some(b"/metrics")) => {
+            let body = handle.render_openmetrics();
+            reply(stream, "200 OK", CONTENT_TYPE, &body).await
+        }
+
+        (Some(b"GET"), _) => reply(stream, "404 Not Found", "text/plain", "not found\n").await,
+        _ => reply(stream, "405 Method Not Allowed", "text/plain", "").await,
+    }
+}
+
+async fn reply(stream: &mut TcpStream, status: &str, kind: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {kind}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use crate::Executor;
+    use crate::io::TcpListener;
+
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    #[test]
+    fn renders_openmetrics() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+
+        handle.block_on(async { handle.spawn(async {}).await });
+        let text = handle.render_openmetrics();
+
+        assert!(text.contains("# TYPE lamp_tasks_spawned counter\n"));
+        assert!(text.contains("\nlamp_tasks_spawned_total 1\n"));
+        assert!(text.contains("\nlamp_worker_polls_total{worker=\"0\"} "));
+        assert!(text.contains("\nlamp_reactor_events_total{direction=\"read\"} "));
+        assert!(text.contains("\nlamp_reactor_events_total{direction=\"write\"} "));
+        assert!(text.contains("\nlamp_io_sources 0\n"));
+        assert!(text.ends_with("\n# EOF\n"));
+
+        exec.shutdown();
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+
+        let listener = {
+            let _guard = handle.enter();
+            TcpListener::bind("127.0.0.1:0").unwrap()
+        };
+        let addr = listener.local_addr().unwrap();
+
+        let server = handle.clone();
+        handle.spawn(async move { server.serve_metrics(listener).await });
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        // Connected without sending anything, it must not hold up the scrapes.
+        let idle = TcpStream::connect(addr).unwrap();
+
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: application/openmetrics-text"));
+        assert!(response.contains("\r\n\r\n# TYPE lamp_tasks_spawned counter\n"));
+        assert!(response.contains("\nlamp_io_sources "));
+        assert!(response.ends_with("# EOF\n"));
+
+        assert!(get("/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        drop(idle);
+        exec.shutdown();
+    }
+}
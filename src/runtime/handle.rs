@@ -0,0 +1,283 @@
+use super::blocking::BlockingTask;
+use super::dump::Dump;
+use super::executor::{self, EnterGuard, Executor, ExecutorHandle};
+use super::metrics::RuntimeMetrics;
+use crate::task::handle::TaskHandle;
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Handle to a runtime.
+///
+/// Cheap to clone, and usable from any thread, even after moving it
+/// to a thread which doesn't belong to the runtime.
+///
+/// # Examples
+///
+/// ```ignore
+/// let exec = Executor::new(2);
+/// let handle = exec.handle();
+///
+/// std::thread::spawn(move || {
+///     let out = handle.block_on(async { handle.spawn(async { 1 }).await });
+///     assert_eq!(out, 1);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Handle {
+    inner: Arc<ExecutorHandle>,
+}
+
+impl Handle {
+    pub(crate) fn new(inner: Arc<ExecutorHandle>) -> Handle {
+        Handle { inner }
+    }
+
+    /// Obtains a handle to the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics outside of a runtime's context, see [`Executor::get`].
+    pub fn current() -> Handle {
+        Handle::new(Executor::get())
+    }
+
+    /// Obtains a handle to the current runtime, if there is one.
+    pub fn try_current() -> Option<Handle> {
+        Executor::try_get().map(Handle::new)
+    }
+
+    /// Spawns a future onto the runtime.
+    #[track_caller]
+    pub fn spawn<F>(&self, f: F) -> TaskHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.inner.spawn(f)
+    }
+
+    /// Runs a blocking closure on the runtime's blocking pool.
+    ///
+    /// Awaiting the returned future resumes the closure's panic, if it panicked.
+    pub fn spawn_blocking<F, T>(&self, f: F) -> BlockingTask<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.inner.spawn_blocking(f)
+    }
+
+    /// Runs a future to completion on the current thread, inside the runtime's context.
+    ///
+    /// The future itself is polled by this thread, the tasks it spawns
+    /// run on the runtime's workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from a thread driving a runtime, like a worker
+    /// or inside [`Executor::block_on`], as it would block it.
+    pub fn block_on<F: Future>(&self, f: F) -> F::Output {
+        let _driving = executor::Driving::enter()
+            .expect("cannot block on a future from a thread driving a runtime");
+        let _guard = self.enter();
+
+        let waker = Waker::from(Arc::new(Unparker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut f = pin!(f);
+
+        loop {
+            match crate::task::budget(|| f.as_mut().poll(&mut cx)) {
+                Poll::Ready(out) => return out,
+
+                // Spurious wake ups only cause another poll.
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Makes the runtime the current one on this thread, until the guard is dropped.
+    pub fn enter(&self) -> EnterGuard {
+        self.inner.enter()
+    }
+
+    /// Obtains the runtime's metrics.
+    pub fn metrics(&self) -> RuntimeMetrics {
+        RuntimeMetrics::new(Arc::clone(&self.inner))
+    }
+
+    /// Renders the runtime's metrics in the OpenMetrics text format.
+    ///
+    /// Covers the task, worker and reactor counters of [`RuntimeMetrics`],
+    /// under names prefixed by `lamp_`.
+    #[cfg(feature = "metrics-exporter")]
+    pub fn render_openmetrics(&self) -> String {
+        super::exporter::render(&self.metrics())
+    }
+
+    /// Serves [`render_openmetrics`](Handle::render_openmetrics) over HTTP,
+    /// answering `GET /metrics` on the connections accepted by `listener`.
+    ///
+    /// Runs until accepting a connection fails, so it is usually spawned
+    /// as its own task.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let listener = TcpListener::bind("127.0.0.1:9000")?;
+    /// let handle = Handle::current();
+    /// handle.spawn(async move { Handle::current().serve_metrics(listener).await });
+    /// ```
+    #[cfg(feature = "metrics-exporter")]
+    pub async fn serve_metrics(&self, listener: crate::io::TcpListener) -> std::io::Result<()> {
+        super::exporter::serve(self, listener).await
+    }
+
+    /// Lists the tasks spawned onto the runtime which didn't complete yet.
+    ///
+    /// The snapshot isn't atomic, tasks keep running while it is taken.
+    pub fn dump(&self) -> Dump {
+        self.inner.dump()
+    }
+
+    pub(crate) fn inner(&self) -> &Arc<ExecutorHandle> {
+        &self.inner
+    }
+}
+
+impl Debug for Handle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle").finish_non_exhaustive()
+    }
+}
+
+// Wakes up the thread blocking on a future.
+struct Unparker(Thread);
+
+impl Wake for Unparker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::Handle;
+    use crate::Executor;
+
+    use std::panic::AssertUnwindSafe;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn block_on_from_another_thread() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+
+        let out = thread::spawn(move || {
+            handle.block_on(async {
+                let task = Handle::current().spawn(async { 20 });
+                let blocking = Handle::current().spawn_blocking(|| 22);
+
+                task.await + blocking.await
+            })
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(out, 42);
+        exec.shutdown();
+    }
+
+    #[test]
+    fn block_on_inside_runtime_panics() {
+        let mut exec = Executor::new(1);
+        let handle = exec.handle();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let _ = exec.block_on(async move {
+            let block = AssertUnwindSafe(|| handle.block_on(async {}));
+            let res = std::panic::catch_unwind(block);
+            tx.send(res.is_err()).unwrap();
+        });
+
+        assert!(rx.recv().unwrap());
+        exec.shutdown();
+    }
+
+    #[test]
+    fn clones_share_the_runtime() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+        let clone = handle.clone();
+
+        assert!(Handle::try_current().is_none());
+        {
+            let _guard = clone.enter();
+            let current = Handle::current();
+            assert!(Arc::ptr_eq(current.inner(), handle.inner()));
+        }
+
+        assert_eq!(handle.metrics().num_workers(), 1);
+        exec.shutdown();
+    }
+
+    #[test]
+    fn dump_lists_named_tasks() {
+        use crate::runtime::{TaskDump, TaskState};
+        use crate::task::Builder;
+
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+
+        let task = Builder::new()
+            .name("conn-42")
+            .spawn_on(std::future::pending::<()>(), &handle);
+        let line = line!() - 1;
+
+        // Waits for the worker to be done polling it.
+        let dump = loop {
+            let dump = handle.dump();
+            let polled = |task: &TaskDump| task.polls() > 0 && task.state() == TaskState::Idle;
+            if dump.tasks().iter().any(polled) {
+                break dump;
+            }
+            thread::yield_now();
+        };
+
+        let [dumped] = dump.tasks() else {
+            panic!("expected one task, got {dump}");
+        };
+        assert_eq!(dumped.id(), task.id());
+        assert_eq!(dumped.name(), Some("conn-42"));
+        assert_eq!(dumped.location().file(), file!());
+        assert_eq!(dumped.location().line(), line);
+
+        exec.shutdown();
+    }
+
+    #[test]
+    fn task_ids_are_unique() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+
+        let a = handle.spawn(async { crate::task::id() });
+        let b = handle.spawn(async { crate::task::id() });
+        let (a_id, b_id) = (a.id(), b.id());
+
+        let (a_out, b_out) = handle.block_on(async { (a.await, b.await) });
+        assert_eq!((a_out, b_out), (a_id, b_id));
+        assert_ne!(a_id, b_id);
+
+        exec.shutdown();
+    }
+}
@@ -0,0 +1,229 @@
+use super::executor::ExecutorHandle;
+
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Metrics of a runtime, obtained from [`Handle::metrics`](super::Handle::metrics).
+///
+/// Every method reads the current value, nothing is cached. Reading them only
+/// loads atomic counters, or briefly takes a lock for the task and source counts,
+/// so scraping them often is cheap.
+///
+/// The per-worker methods take the index of a worker, from `0` to
+/// [`num_workers`](RuntimeMetrics::num_workers) excluded, and panic past that.
+pub struct RuntimeMetrics {
+    inner: Arc<ExecutorHandle>,
+}
+
+impl RuntimeMetrics {
+    pub(crate) fn new(inner: Arc<ExecutorHandle>) -> RuntimeMetrics {
+        RuntimeMetrics { inner }
+    }
+
+    /// Amount of worker threads.
+    pub fn num_workers(&self) -> usize {
+        self.inner.num_workers()
+    }
+
+    /// Amount of times the worker polled a task.
+    pub fn worker_polls(&self, worker: usize) -> u64 {
+        self.inner
+            .worker_stats(worker, |stats| stats.polls.load(Ordering::Relaxed))
+    }
+
+    /// Time the worker spent handling notes, polling tasks for the most part.
+    pub fn worker_busy_duration(&self, worker: usize) -> Duration {
+        let nanos = self
+            .inner
+            .worker_stats(worker, |stats| stats.busy_nanos.load(Ordering::Relaxed));
+
+        Duration::from_nanos(nanos)
+    }
+
+    /// Amount of times the worker ran out of notes and parked.
+    pub fn worker_park_count(&self, worker: usize) -> u64 {
+        self.inner
+            .worker_stats(worker, |stats| stats.parks.load(Ordering::Relaxed))
+    }
+
+    /// Amount of notes waiting in the worker's own queue.
+    pub fn worker_local_queue_depth(&self, worker: usize) -> usize {
+        let depth = self.inner.worker_stats(worker, |stats| stats.queue_depth());
+
+        depth.try_into().unwrap_or(usize::MAX)
+    }
+
+    /// Amount of notes waiting across all the workers.
+    ///
+    /// There is no queue shared by the workers, each note goes straight to
+    /// the queue of one worker, so this is the sum of their local queue depths.
+    pub fn global_queue_depth(&self) -> usize {
+        self.inner.queued_notes().try_into().unwrap_or(usize::MAX)
+    }
+
+    /// Amount of spawned tasks which haven't completed yet.
+    pub fn live_tasks(&self) -> usize {
+        self.inner.live_tasks()
+    }
+
+    /// Amount of tasks spawned since the runtime started.
+    pub fn spawned_tasks(&self) -> u64 {
+        self.inner.spawned_tasks()
+    }
+
+    /// Amount of tasks which completed on a worker, aborted ones included.
+    ///
+    /// Tasks cancelled by the runtime's shutdown aren't counted.
+    pub fn completed_tasks(&self) -> u64 {
+        self.inner.completed_tasks()
+    }
+
+    /// Amount of threads alive in the blocking pool.
+    pub fn blocking_threads(&self) -> usize {
+        self.inner.blocking_threads()
+    }
+
+    /// Amount of events the reactor received for I/O sources.
+    pub fn reactor_events(&self) -> u64 {
+        self.inner
+            .reactor_fn(|reactor| reactor.stats().events.load(Ordering::Relaxed))
+    }
+
+    /// Amount of readable events the reactor received.
+    pub fn reactor_read_events(&self) -> u64 {
+        self.inner
+            .reactor_fn(|reactor| reactor.stats().read_events.load(Ordering::Relaxed))
+    }
+
+    /// Amount of writable events the reactor received.
+    pub fn reactor_write_events(&self) -> u64 {
+        self.inner
+            .reactor_fn(|reactor| reactor.stats().write_events.load(Ordering::Relaxed))
+    }
+
+    /// Amount of times an I/O source had too many tasks waiting on it,
+    /// which were then woken early to make room.
+    pub fn waker_overflows(&self) -> u64 {
+        self.inner
+            .reactor_fn(|reactor| reactor.stats().waker_overflows.load(Ordering::Relaxed))
+    }
+
+    /// Amount of I/O sources registered in the reactor.
+    pub fn registered_sources(&self) -> usize {
+        self.inner.reactor_fn(|reactor| reactor.sources())
+    }
+}
+
+impl Debug for RuntimeMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeMetrics")
+            .field("num_workers", &self.num_workers())
+            .field("live_tasks", &self.live_tasks())
+            .field("spawned_tasks", &self.spawned_tasks())
+            .field("reactor_events", &self.reactor_events())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use crate::Executor;
+    use crate::io::{AsyncReadExt, AsyncWriteExt, TcpListener, TcpStream};
+
+    use std::panic::AssertUnwindSafe;
+    use std::sync::mpsc;
+
+    #[test]
+    fn counts_tasks_and_polls() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+        let metrics = handle.metrics();
+
+        handle.block_on(async {
+            for i in 0..4 {
+                assert_eq!(handle.spawn(async move { i }).await, i);
+            }
+        });
+
+        // The workers are done updating the counters once joined.
+        exec.shutdown();
+
+        assert_eq!(metrics.num_workers(), 1);
+        assert_eq!(metrics.spawned_tasks(), 4);
+        assert!(metrics.worker_polls(0) >= 4);
+        assert!(metrics.worker_busy_duration(0) > std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn counts_queued_notes() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+        let metrics = handle.metrics();
+
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        handle.block_on(async {
+            // Keeps the only worker busy, so the next note has to wait.
+            let blocker = handle.spawn(async move {
+                started_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+            });
+            started_rx.recv().unwrap();
+
+            let queued = handle.spawn(async {});
+
+            // The note being handled counts until the worker is done with it.
+            assert_eq!(metrics.worker_local_queue_depth(0), 2);
+            assert_eq!(metrics.global_queue_depth(), 2);
+
+            release_tx.send(()).unwrap();
+            blocker.await;
+            queued.await;
+        });
+
+        exec.shutdown();
+    }
+
+    #[test]
+    fn unknown_worker_panics() {
+        let exec = Executor::new(1);
+        let metrics = exec.handle().metrics();
+
+        let polls = AssertUnwindSafe(|| metrics.worker_polls(1));
+        let res = std::panic::catch_unwind(polls);
+        assert!(res.is_err());
+
+        exec.shutdown();
+    }
+
+    #[test]
+    fn counts_reactor_events() {
+        let exec = Executor::new(1);
+        let handle = exec.handle();
+        let metrics = handle.metrics();
+
+        handle.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            assert_eq!(metrics.registered_sources(), 1);
+
+            let client = handle.spawn(async move {
+                let mut stream = TcpStream::new(&addr.to_string()).unwrap();
+                stream.write_all(b"ping").await.unwrap();
+            });
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 4];
+            client.await;
+            let n = stream.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"ping");
+        });
+
+        assert!(metrics.reactor_events() > 0);
+        exec.shutdown();
+    }
+}
@@ -1,6 +1,21 @@
 #[allow(unused_imports)]
 pub(crate) mod executor;
-pub use executor::{Executor, ExecutorHandle};
+pub use executor::{EnterGuard, Executor, ExecutorHandle, ShutdownReport};
+
+pub(crate) mod blocking;
+pub use blocking::BlockingTask;
+
+mod dump;
+pub use dump::{Dump, TaskDump, TaskState};
+
+#[cfg(feature = "metrics-exporter")]
+mod exporter;
+
+mod handle;
+pub use handle::Handle;
+
+mod metrics;
+pub use metrics::RuntimeMetrics;
 
 pub(crate) mod threads;
 
@@ -1,4 +1,4 @@
-use crate::runtime::{Executor, ExecutorHandle};
+use crate::runtime::ExecutorHandle;
 use log::{error, info, warn};
 use slab::Slab;
 use std::cell::Cell;
@@ -8,14 +8,41 @@ use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::{Arc, Weak, atomic, atomic::Ordering, mpsc};
 use std::thread::{self, available_parallelism};
 
-type ThreadFn<T> = fn(Weak<ExecutorHandle>, mpsc::Receiver<T>, Arc<AtomicBool>);
+type ThreadFn<T> = fn(Weak<ExecutorHandle>, mpsc::Receiver<T>, Arc<WorkerStats>);
 
-pub(crate) struct WorkerThread<T: Send + 'static> {
+/// Counters of a worker, shared with its thread.
+#[derive(Debug, Default)]
+pub(crate) struct WorkerStats {
     // Is it currently occupied
-    occupied: Arc<AtomicBool>,
+    pub(crate) occupied: AtomicBool,
+
+    // Notes pushed to the worker, and handled by it.
+    pub(crate) received: AtomicU64,
+    pub(crate) handled: AtomicU64,
+
+    // Tasks polled.
+    pub(crate) polls: AtomicU64,
+
+    // Time spent handling notes.
+    pub(crate) busy_nanos: AtomicU64,
+
+    // Times it waited for a note, with an empty queue.
+    pub(crate) parks: AtomicU64,
+}
+
+impl WorkerStats {
+    /// Notes waiting in the worker's queue.
+    pub(crate) fn queue_depth(&self) -> u64 {
+        let handled = self.handled.load(Ordering::Relaxed);
+        self.received
+            .load(Ordering::Relaxed)
+            .saturating_sub(handled)
+    }
+}
 
-    // How much work has been done
-    tasks_received: AtomicU64,
+pub(crate) struct WorkerThread<T: Send + 'static> {
+    // How much work has been done, and whether it is occupied.
+    stats: Arc<WorkerStats>,
 
     // Channel Pair
     sender: mpsc::Sender<T>,
@@ -37,8 +64,7 @@ pub(crate) struct WorkerThread<T: Send + 'static> {
 impl<T: Send + 'static> Debug for WorkerThread<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("WorkerThread")
-            .field("occupied", &self.occupied)
-            .field("tasks_received", &self.tasks_received)
+            .field("stats", &self.stats)
             .field("sender", &self.sender)
             .field("receiver", &self.receiver)
             .field("func", &self.func)
@@ -50,11 +76,9 @@ impl<T: Send + 'static> Debug for WorkerThread<T> {
 impl<T: Send + 'static> WorkerThread<T> {
     pub(crate) fn new(func: ThreadFn<T>, rt: Weak<ExecutorHandle>) -> Self {
         let (sender, receiver) = mpsc::channel();
-        let arc_bool = Arc::new(AtomicBool::new(false));
 
         Self {
-            occupied: arc_bool,
-            tasks_received: AtomicU64::new(0),
+            stats: Arc::new(WorkerStats::default()),
             sender,
             receiver: Some(receiver),
             handle: Cell::new(None),
@@ -65,7 +89,7 @@ impl<T: Send + 'static> WorkerThread<T> {
     }
 
     pub(crate) fn start(&mut self) -> io::Result<()> {
-        let clone = Arc::clone(&self.occupied);
+        let clone = Arc::clone(&self.stats);
         let func = self.func.clone();
         let receiver = self.receiver.take().unwrap();
         let rt = self.rt.clone();
@@ -77,7 +101,7 @@ impl<T: Send + 'static> WorkerThread<T> {
     }
 
     pub(crate) fn push(&self, notif: T) -> Result<(), mpsc::SendError<T>> {
-        self.tasks_received.fetch_add(1, Ordering::Relaxed);
+        self.stats.received.fetch_add(1, Ordering::Relaxed);
         self.sender.send(notif)
     }
 
@@ -97,13 +121,13 @@ impl<T: Send + 'static> WorkerThread<T> {
     #[allow(dead_code)]
     pub(crate) fn rebuild(&mut self) {
         let (sender, receiver) = mpsc::channel();
-        let clone = Arc::clone(&self.occupied);
+        self.stats = Arc::new(WorkerStats::default());
+
+        let clone = Arc::clone(&self.stats);
         let func = self.func.clone();
         let rt = self.rt.clone();
 
         self.sender = sender;
-        self.tasks_received = AtomicU64::new(0);
-        self.occupied.swap(false, atomic::Ordering::SeqCst);
 
         let handle = Some(thread::spawn(move || func(rt, receiver, clone)));
         self.handle.set(handle);
@@ -159,16 +183,19 @@ impl<Notif: Send + 'static> ThreadPool<Notif> {
             amount,
         }
     }
-    pub(crate) fn start(&mut self, amnt: usize, f: ThreadFn<Notif>) -> io::Result<()> {
-        let rt = Executor::get();
-
+    pub(crate) fn start(
+        &mut self,
+        amnt: usize,
+        f: ThreadFn<Notif>,
+        rt: Weak<ExecutorHandle>,
+    ) -> io::Result<()> {
         loop {
             if self.workers.len() == amnt {
                 break;
             }
 
             info!("creating thread");
-            let mut th = WorkerThread::new(f, Arc::downgrade(&rt.clone()));
+            let mut th = WorkerThread::new(f, rt.clone());
 
             if th.start().is_err() {
                 // returns a pool with a reduced size
@@ -185,6 +212,16 @@ impl<Notif: Send + 'static> ThreadPool<Notif> {
         Ok(())
     }
 
+    /// Amount of workers in the pool.
+    pub(crate) fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Counters of each worker, in order.
+    pub(crate) fn stats(&self) -> impl Iterator<Item = &WorkerStats> {
+        self.workers.iter().map(|(_, worker)| &*worker.stats)
+    }
+
     /// Deploys a task to a chosen worker.
     pub(crate) fn deploy(&self, n: Notif) -> Result<(), mpsc::SendError<Notif>> {
         let mut chosen = 0;
@@ -200,9 +237,9 @@ impl<Notif: Send + 'static> ThreadPool<Notif> {
 
         self.workers
             .iter()
-            .filter(|(_k, v)| !v.occupied.load(atomic::Ordering::Relaxed))
+            .filter(|(_k, v)| !v.stats.occupied.load(atomic::Ordering::Relaxed))
             .for_each(|(k, v)| {
-                let received = v.tasks_received.load(Ordering::Relaxed);
+                let received = v.stats.received.load(Ordering::Relaxed);
                 match count > received {
                     true => {
                         count = received;
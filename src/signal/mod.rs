@@ -0,0 +1,24 @@
+//! Asynchronous signal handling.
+//!
+//! Signal handlers are installed process wide on first use and stay installed.
+//! They only notify the reactor through a pipe, so any amount of listeners,
+//! on any runtime, can wait for the same signal.
+//!
+//! Deliveries are coalesced: a listener receiving a signal means it was delivered
+//! at least once since the listener last received it, or since it was created.
+
+mod registry;
+pub mod unix;
+
+pub(crate) use registry::{Listener, dispatch, pipe_fd, watch_orphans};
+
+use std::io;
+
+/// Waits for the next SIGINT, usually sent by pressing ctrl-c.
+///
+/// Once called, the default behavior of terminating the process
+/// is replaced for the rest of the program.
+pub async fn ctrl_c() -> io::Result<()> {
+    unix::signal(unix::SignalKind::interrupt())?.recv().await;
+    Ok(())
+}
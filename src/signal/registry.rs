@@ -0,0 +1,261 @@
+// Process wide signal handling.
+//
+// The handler only flags the signal as pending and writes into a pipe.
+// Reactors register the reading end and, once it becomes readable,
+// bump the generation of every pending signal and wake its listeners.
+use crate::runtime::Executor;
+
+use log::debug;
+
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+/// Amount of signal numbers tracked, covers the real-time signals on Linux.
+const SLOTS: usize = 65;
+
+/// Signals which can't be caught, or mustn't be since returning from their handler
+/// resumes the faulting instruction.
+const FORBIDDEN: [libc::c_int; 7] = [
+    libc::SIGKILL,
+    libc::SIGSTOP,
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGSEGV,
+    libc::SIGBUS,
+    libc::SIGTRAP,
+];
+
+struct Slot {
+    // Set by the handler, cleared on dispatch.
+    pending: AtomicBool,
+
+    // Incremented on every dispatch of a pending signal.
+    generation: AtomicU64,
+
+    // Whether the handler is installed.
+    installed: Mutex<bool>,
+
+    // Listeners waiting for the next generation.
+    wakers: Mutex<Vec<Waker>>,
+}
+
+struct Globals {
+    sender: UnixStream,
+    receiver: UnixStream,
+    slots: Vec<Slot>,
+}
+
+static GLOBALS: OnceLock<Globals> = OnceLock::new();
+
+fn globals() -> io::Result<&'static Globals> {
+    if let Some(globals) = GLOBALS.get() {
+        return Ok(globals);
+    }
+
+    let (sender, receiver) = UnixStream::pair()?;
+    sender.set_nonblocking(true)?;
+    receiver.set_nonblocking(true)?;
+
+    let slots = (0..SLOTS)
+        .map(|_| Slot {
+            pending: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            installed: Mutex::new(false),
+            wakers: Mutex::new(Vec::new()),
+        })
+        .collect();
+
+    // Losing the race drops the spare pipe.
+    Ok(GLOBALS.get_or_init(|| Globals {
+        sender,
+        receiver,
+        slots,
+    }))
+}
+
+/// Returns the reading end of the signal pipe.
+pub(crate) fn pipe_fd() -> io::Result<RawFd> {
+    Ok(globals()?.receiver.as_raw_fd())
+}
+
+extern "C" fn handler(signal: libc::c_int) {
+    let Some(globals) = GLOBALS.get() else {
+        return;
+    };
+
+    let errno = errno();
+
+    if let Some(slot) = globals.slots.get(signal as usize) {
+        slot.pending.store(true, Ordering::SeqCst);
+    }
+
+    // A full pipe already guarantees a wake up.
+    let _ = (&globals.sender).write(&[1]);
+
+    set_errno(errno);
+}
+
+fn errno() -> libc::c_int {
+    io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+fn set_errno(errno: libc::c_int) {
+    // Safety: writes the calling thread's errno.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe {
+        *libc::__errno_location() = errno
+    };
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe {
+        *libc::__error() = errno
+    };
+}
+
+/// Installs the handler for `signal`, once.
+fn install(globals: &Globals, signal: libc::c_int) -> io::Result<()> {
+    let slot = match globals.slots.get(signal as usize) {
+        Some(slot) if signal > 0 => slot,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid signal",
+            ));
+        }
+    };
+
+    if FORBIDDEN.contains(&signal) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("signal {signal} can't be handled"),
+        ));
+    }
+
+    let mut installed = slot.installed.lock().unwrap();
+    if *installed {
+        return Ok(());
+    }
+
+    // Safety: the action is fully initialized and the handler is async-signal-safe.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+
+        if libc::sigaction(signal, &action, std::ptr::null_mut()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    *installed = true;
+    Ok(())
+}
+
+/// Drains the signal pipe and wakes the listeners of every pending signal.
+///
+/// Called by the reactor.
+pub(crate) fn dispatch() {
+    let Some(globals) = GLOBALS.get() else {
+        return;
+    };
+
+    let mut buf = [0u8; 64];
+    loop {
+        match (&globals.receiver).read(&mut buf) {
+            Ok(0) => break,
+            Ok(_) => continue,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+
+    for (signal, slot) in globals.slots.iter().enumerate() {
+        if !slot.pending.swap(false, Ordering::SeqCst) {
+            continue;
+        }
+
+        slot.generation.fetch_add(1, Ordering::SeqCst);
+        let wakers = std::mem::take(&mut *slot.wakers.lock().unwrap());
+        debug!("signal {signal} woke {} listeners", wakers.len());
+
+        for waker in wakers {
+            waker.wake();
+        }
+
+        // Dropped children are reaped as they exit, see `watch_orphans`.
+        if signal == libc::SIGCHLD as usize {
+            crate::process::reap_orphans();
+        }
+    }
+}
+
+/// Installs the SIGCHLD handler, and makes the current runtime's reactor,
+/// if there is one, reap the dropped children whenever it is delivered.
+pub(crate) fn watch_orphans() -> io::Result<()> {
+    install(globals()?, libc::SIGCHLD)?;
+
+    match Executor::try_get() {
+        Some(rt) => rt.reactor_fn(|r| r.enable_signals()),
+        None => Ok(()),
+    }
+}
+
+/// Receives notifications of a signal.
+///
+/// Deliveries happening while no one polls are coalesced into one.
+pub(crate) struct Listener {
+    slot: &'static Slot,
+    seen: u64,
+}
+
+impl Listener {
+    /// Starts listening for `signal`, installing its handler
+    /// and making the current runtime's reactor watch the signal pipe.
+    pub(crate) fn new(signal: libc::c_int) -> io::Result<Listener> {
+        let globals = globals()?;
+        install(globals, signal)?;
+        Executor::get().reactor_fn(|r| r.enable_signals())?;
+
+        let slot = &globals.slots[signal as usize];
+
+        Ok(Listener {
+            slot,
+            seen: slot.generation.load(Ordering::SeqCst),
+        })
+    }
+
+    /// Resolves once the signal was delivered since the last time it resolved,
+    /// or since the listener was created.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.consume() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self.slot.wakers.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        drop(wakers);
+
+        // Dispatched before the waker was added.
+        match self.consume() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+
+    fn consume(&mut self) -> bool {
+        let generation = self.slot.generation.load(Ordering::SeqCst);
+        if generation == self.seen {
+            return false;
+        }
+
+        self.seen = generation;
+        true
+    }
+}
@@ -0,0 +1,182 @@
+//! Unix specific signal handling.
+
+use super::Listener;
+
+use futures::Stream;
+
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Kind of a signal, wraps its number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignalKind(libc::c_int);
+
+impl SignalKind {
+    /// Signal with the raw number `signum`.
+    pub const fn from_raw(signum: libc::c_int) -> SignalKind {
+        SignalKind(signum)
+    }
+
+    /// Returns the raw signal number.
+    pub const fn as_raw_value(&self) -> libc::c_int {
+        self.0
+    }
+
+    /// SIGALRM, a timer set by `alarm` expired.
+    pub const fn alarm() -> SignalKind {
+        SignalKind(libc::SIGALRM)
+    }
+
+    /// SIGCHLD, a child process exited or stopped.
+    pub const fn child() -> SignalKind {
+        SignalKind(libc::SIGCHLD)
+    }
+
+    /// SIGHUP, the terminal was closed, daemons commonly reload on it.
+    pub const fn hangup() -> SignalKind {
+        SignalKind(libc::SIGHUP)
+    }
+
+    /// SIGINT, an interrupt from the terminal, usually ctrl-c.
+    pub const fn interrupt() -> SignalKind {
+        SignalKind(libc::SIGINT)
+    }
+
+    /// SIGPIPE, a write to a pipe without a reader.
+    pub const fn pipe() -> SignalKind {
+        SignalKind(libc::SIGPIPE)
+    }
+
+    /// SIGQUIT, a quit request from the terminal.
+    pub const fn quit() -> SignalKind {
+        SignalKind(libc::SIGQUIT)
+    }
+
+    /// SIGTERM, a request to terminate.
+    pub const fn terminate() -> SignalKind {
+        SignalKind(libc::SIGTERM)
+    }
+
+    /// SIGUSR1, meaning is up to the application.
+    pub const fn user_defined1() -> SignalKind {
+        SignalKind(libc::SIGUSR1)
+    }
+
+    /// SIGUSR2, meaning is up to the application.
+    pub const fn user_defined2() -> SignalKind {
+        SignalKind(libc::SIGUSR2)
+    }
+
+    /// SIGWINCH, the terminal window was resized.
+    pub const fn window_change() -> SignalKind {
+        SignalKind(libc::SIGWINCH)
+    }
+}
+
+/// Creates a listener for signals of `kind`.
+///
+/// Installs the signal's handler, replacing its default behavior
+/// for the rest of the program.
+/// Must be called from within a runtime, whose reactor starts watching for signals.
+///
+/// Fails for signals which can't be handled, like SIGKILL or SIGSEGV.
+pub fn signal(kind: SignalKind) -> io::Result<Signal> {
+    Ok(Signal {
+        listener: Listener::new(kind.0)?,
+    })
+}
+
+/// Listener for a signal, created by `signal`.
+///
+/// Also a `Stream` of deliveries which never ends.
+pub struct Signal {
+    listener: Listener,
+}
+
+impl Signal {
+    /// Waits for the next delivery of the signal.
+    ///
+    /// Resolves right away if the signal was delivered since
+    /// the last `recv` resolved, so deliveries in between aren't lost.
+    pub async fn recv(&mut self) {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls for the next delivery of the signal.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.listener.poll_recv(cx)
+    }
+}
+
+impl Stream for Signal {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.get_mut().poll_recv(cx).map(Some)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::*;
+    use crate::Executor;
+    use std::time::Duration;
+
+    #[test]
+    fn multiple_listeners() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let mut one = signal(SignalKind::user_defined1()).unwrap();
+            let mut two = signal(SignalKind::user_defined1()).unwrap();
+
+            // Delivered before anyone waits.
+            unsafe { libc::raise(libc::SIGUSR1) };
+            one.recv().await;
+            two.recv().await;
+
+            let waiter = Executor::spawn(async move {
+                one.recv().await;
+                one
+            });
+
+            std::thread::spawn(|| {
+                std::thread::sleep(Duration::from_millis(50));
+                unsafe { libc::kill(libc::getpid(), libc::SIGUSR1) };
+            });
+
+            two.recv().await;
+            waiter.await;
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn coalesced_and_forbidden() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let mut sig = signal(SignalKind::user_defined2()).unwrap();
+
+            unsafe { libc::raise(libc::SIGUSR2) };
+            unsafe { libc::raise(libc::SIGUSR2) };
+            sig.recv().await;
+
+            // Both deliveries were dispatched together.
+            std::thread::sleep(Duration::from_millis(50));
+            let waker = futures::task::noop_waker();
+            assert!(sig.poll_recv(&mut Context::from_waker(&waker)).is_pending());
+
+            let err = signal(SignalKind::from_raw(libc::SIGKILL)).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+}
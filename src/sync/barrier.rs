@@ -0,0 +1,86 @@
+use super::watch;
+
+use std::fmt;
+use std::sync::Mutex;
+
+/// Lets a fixed amount of tasks wait for each other.
+///
+/// Every call to `wait` suspends until `n` tasks are waiting, then all of them
+/// continue. The barrier can be reused afterwards.
+pub struct Barrier {
+    n: usize,
+    state: Mutex<State>,
+
+    // Carries the generation, bumped when every task arrived.
+    tx: watch::Sender<u64>,
+    rx: watch::Receiver<u64>,
+}
+
+struct State {
+    arrived: usize,
+    generation: u64,
+}
+
+/// Result of `Barrier::wait`.
+#[derive(Debug, Clone, Copy)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Returns whether this task was the last one to arrive.
+    ///
+    /// Exactly one task per round is the leader.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl Barrier {
+    /// Creates a barrier for `n` tasks, 0 being treated as 1.
+    pub fn new(n: usize) -> Barrier {
+        let (tx, rx) = watch::channel(0);
+
+        Barrier {
+            n: n.max(1),
+            state: Mutex::new(State {
+                arrived: 0,
+                generation: 0,
+            }),
+            tx,
+            rx,
+        }
+    }
+
+    /// Waits for `n` tasks to call `wait`.
+    ///
+    /// Not cancel safe: a dropped future still counts as arrived.
+    pub async fn wait(&self) -> BarrierWaitResult {
+        let generation = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.arrived += 1;
+
+            if state.arrived == self.n {
+                state.arrived = 0;
+                state.generation += 1;
+                self.tx.send_replace(state.generation);
+
+                return BarrierWaitResult(true);
+            }
+
+            state.generation
+        };
+
+        let mut rx = self.rx.clone();
+        while *rx.borrow_and_update() == generation {
+            // The sender lives as long as the barrier.
+            let _ = rx.changed().await;
+        }
+
+        BarrierWaitResult(false)
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier").field("n", &self.n).finish()
+    }
+}
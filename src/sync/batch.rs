@@ -0,0 +1,261 @@
+// Semaphore with a FIFO queue of waiters, the base of every lock in this module.
+//
+// A waiter is only granted its permits once every waiter queued before it was,
+// even if enough permits are available for it earlier.
+use super::{AcquireError, TryAcquireError};
+use crate::task::poll_budgeted;
+
+use slab::Slab;
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+struct Waiter {
+    needed: usize,
+    granted: bool,
+    waker: Option<Waker>,
+}
+
+struct State {
+    permits: usize,
+    closed: bool,
+    waiters: Slab<Waiter>,
+
+    // Keys of the waiters not granted yet, in arrival order.
+    queue: VecDeque<usize>,
+}
+
+impl State {
+    /// Grants permits to the waiters at the front of the queue,
+    /// returning the wakers to call once the lock is released.
+    fn grant(&mut self) -> Vec<Waker> {
+        let mut wakers = Vec::new();
+
+        while let Some(&key) = self.queue.front() {
+            let waiter = &mut self.waiters[key];
+            if waiter.needed > self.permits {
+                break;
+            }
+
+            self.permits -= waiter.needed;
+            waiter.granted = true;
+            wakers.extend(waiter.waker.take());
+            self.queue.pop_front();
+        }
+
+        wakers
+    }
+}
+
+pub(crate) struct Semaphore {
+    state: Mutex<State>,
+}
+
+impl Semaphore {
+    /// Most permits a semaphore can hold.
+    pub(crate) const MAX_PERMITS: usize = usize::MAX >> 3;
+
+    pub(crate) const fn new(permits: usize) -> Semaphore {
+        assert!(permits <= Semaphore::MAX_PERMITS, "too many permits");
+
+        Semaphore {
+            state: Mutex::new(State {
+                permits,
+                closed: false,
+                waiters: Slab::new(),
+                queue: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Never panics while holding the lock.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn available_permits(&self) -> usize {
+        self.lock().permits
+    }
+
+    /// Adds `n` permits, handing them to the waiters first.
+    pub(crate) fn release(&self, n: usize) {
+        if n == 0 {
+            return;
+        }
+
+        let mut state = self.lock();
+        assert!(
+            state.permits + n <= Semaphore::MAX_PERMITS,
+            "too many permits"
+        );
+
+        state.permits += n;
+        let wakers = state.grant();
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Fails the pending and future acquisitions.
+    pub(crate) fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+
+        let wakers: Vec<Waker> = state
+            .waiters
+            .iter_mut()
+            .filter_map(|(_, waiter)| waiter.waker.take())
+            .collect();
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    pub(crate) fn try_acquire(&self, n: usize) -> Result<(), TryAcquireError> {
+        let mut state = self.lock();
+
+        if state.closed {
+            return Err(TryAcquireError::Closed);
+        }
+
+        // Waiters in the queue go first.
+        if !state.queue.is_empty() || state.permits < n {
+            return Err(TryAcquireError::NoPermits);
+        }
+
+        state.permits -= n;
+        Ok(())
+    }
+
+    pub(crate) fn acquire(&self, n: usize) -> Acquire<'_> {
+        // Never granted, it would hold back the whole queue forever.
+        assert!(n <= Semaphore::MAX_PERMITS, "too many permits");
+
+        Acquire {
+            semaphore: self,
+            needed: n,
+            key: None,
+        }
+    }
+}
+
+/// Future acquiring permits.
+///
+/// Dropping it gives up its place in the queue,
+/// returning the permits if they were already granted.
+pub(crate) struct Acquire<'s> {
+    semaphore: &'s Semaphore,
+    needed: usize,
+
+    // Position in the waiters, once queued.
+    key: Option<usize>,
+}
+
+impl Future for Acquire<'_> {
+    type Output = Result<(), AcquireError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+
+        poll_budgeted(cx, |cx| me.poll_acquire(cx))
+    }
+}
+
+impl Acquire<'_> {
+    fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), AcquireError>> {
+        let mut state = self.semaphore.lock();
+
+        let key = match self.key {
+            Some(key) => key,
+
+            None => {
+                if state.closed {
+                    return Poll::Ready(Err(AcquireError(())));
+                }
+
+                if state.queue.is_empty() && state.permits >= self.needed {
+                    state.permits -= self.needed;
+                    return Poll::Ready(Ok(()));
+                }
+
+                let key = state.waiters.insert(Waiter {
+                    needed: self.needed,
+                    granted: false,
+                    waker: Some(cx.waker().clone()),
+                });
+
+                state.queue.push_back(key);
+                self.key = Some(key);
+
+                return Poll::Pending;
+            }
+        };
+
+        let closed = state.closed;
+        let waiter = &mut state.waiters[key];
+
+        if waiter.granted {
+            state.waiters.remove(key);
+            self.key = None;
+            return Poll::Ready(Ok(()));
+        }
+
+        if closed {
+            drop(state);
+            self.cancel();
+            return Poll::Ready(Err(AcquireError(())));
+        }
+
+        match waiter.waker {
+            Some(ref waker) if waker.will_wake(cx.waker()) => {}
+            _ => waiter.waker = Some(cx.waker().clone()),
+        }
+
+        Poll::Pending
+    }
+
+    /// Leaves the queue, returning granted permits.
+    fn cancel(&mut self) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+
+        let mut state = self.semaphore.lock();
+        let waiter = state.waiters.remove(key);
+
+        let wakers = if waiter.granted {
+            state.permits += waiter.needed;
+            state.grant()
+        } else {
+            let pos = state.queue.iter().position(|&k| k == key);
+            state
+                .queue
+                .remove(pos.expect("waiter missing from the queue"));
+
+            // It might have held back the ones behind it.
+            state.grant()
+        };
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
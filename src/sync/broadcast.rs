@@ -0,0 +1,403 @@
+//! Multi-producer, multi-consumer channel where every receiver sees every message.
+//!
+//! The channel keeps the last `capacity` messages. A receiver falling further behind
+//! misses the oldest ones, and is told how many with `RecvError::Lagged`.
+
+use crate::task::poll_budgeted;
+
+use futures::Stream;
+use slab::Slab;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+pub mod error {
+    //! Errors of the broadcast channel.
+
+    use std::error::Error;
+    use std::fmt;
+
+    /// Every receiver was dropped, the value is handed back.
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SendError<T>(pub T);
+
+    impl<T> fmt::Debug for SendError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SendError").finish_non_exhaustive()
+        }
+    }
+
+    impl<T> fmt::Display for SendError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "channel closed")
+        }
+    }
+
+    impl<T> Error for SendError<T> {}
+
+    /// Error of `Receiver::recv`.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum RecvError {
+        /// Every sender was dropped and all messages were received.
+        Closed,
+
+        /// The receiver fell behind and missed this many messages.
+        ///
+        /// The next receive returns the oldest message still kept.
+        Lagged(u64),
+    }
+
+    impl fmt::Display for RecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RecvError::Closed => write!(f, "channel closed"),
+                RecvError::Lagged(amnt) => write!(f, "channel lagged by {amnt}"),
+            }
+        }
+    }
+
+    impl Error for RecvError {}
+
+    /// Error of `Receiver::try_recv`.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum TryRecvError {
+        /// No new message was sent.
+        Empty,
+
+        /// Every sender was dropped and all messages were received.
+        Closed,
+
+        /// The receiver fell behind and missed this many messages.
+        Lagged(u64),
+    }
+
+    impl fmt::Display for TryRecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TryRecvError::Empty => write!(f, "channel empty"),
+                TryRecvError::Closed => write!(f, "channel closed"),
+                TryRecvError::Lagged(amnt) => write!(f, "channel lagged by {amnt}"),
+            }
+        }
+    }
+
+    impl Error for TryRecvError {}
+}
+
+use error::{RecvError, SendError, TryRecvError};
+
+struct State<T> {
+    // The last `capacity` messages, `buffer[0]` is at position `head`.
+    buffer: VecDeque<T>,
+    head: u64,
+
+    senders: usize,
+    receivers: usize,
+
+    // One slot per receiver, holding its waker while it waits.
+    wakers: Slab<Option<Waker>>,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn new_receiver(self: &Arc<Self>, state: &mut State<T>) -> Receiver<T> {
+        state.receivers += 1;
+
+        Receiver {
+            shared: Arc::clone(self),
+            next: state.head + state.buffer.len() as u64,
+            key: state.wakers.insert(None),
+        }
+    }
+}
+
+fn wake_all<T>(mut state: MutexGuard<'_, State<T>>) {
+    let wakers: Vec<Waker> = state
+        .wakers
+        .iter_mut()
+        .filter_map(|(_, w)| w.take())
+        .collect();
+    drop(state);
+
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// Creates a channel keeping the last `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(
+        capacity > 0,
+        "broadcast channel capacity must be greater than 0"
+    );
+
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            buffer: VecDeque::with_capacity(capacity),
+            head: 0,
+            senders: 1,
+            receivers: 0,
+            wakers: Slab::new(),
+        }),
+        capacity,
+    });
+
+    let rx = shared.new_receiver(&mut shared.lock());
+    (Sender { shared }, rx)
+}
+
+/// Sending half of a broadcast channel, cloned for every producer.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receiving half of a broadcast channel.
+///
+/// Only sees messages sent after it was created.
+/// Also a `Stream` of the receive results, ending once the channel is closed.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+
+    // Position of the next message to receive.
+    next: u64,
+
+    // Slot in `State::wakers`.
+    key: usize,
+}
+
+impl<T> Sender<T> {
+    /// Sends `value` to every receiver, returning how many there are.
+    ///
+    /// Fails if there are no receivers, handing `value` back.
+    /// Never waits, the oldest message is dropped if the channel is full.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let mut state = self.shared.lock();
+        if state.receivers == 0 {
+            return Err(SendError(value));
+        }
+
+        let receivers = state.receivers;
+        state.buffer.push_back(value);
+        let dropped = if state.buffer.len() > self.shared.capacity {
+            state.head += 1;
+            state.buffer.pop_front()
+        } else {
+            None
+        };
+
+        wake_all(state);
+        drop(dropped);
+
+        Ok(receivers)
+    }
+
+    /// Creates a receiver seeing the messages sent from now on.
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.shared.new_receiver(&mut self.shared.lock())
+    }
+
+    /// Returns the amount of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.lock().receivers
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.shared.lock().senders += 1;
+        Sender {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            wake_all(state);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("receivers", &self.receiver_count())
+            .finish()
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Waits for the next message.
+    ///
+    /// Cancel safe: no message is lost if the future is dropped.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls for the next message.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        poll_budgeted(cx, |cx| match self.try_recv_inner(Some(cx)) {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+            Err(TryRecvError::Lagged(amnt)) => Poll::Ready(Err(RecvError::Lagged(amnt))),
+        })
+    }
+
+    /// Takes the next message if one was sent, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.try_recv_inner(None)
+    }
+
+    // Registers the waker of `cx` when no message is available.
+    fn try_recv_inner(&mut self, cx: Option<&mut Context<'_>>) -> Result<T, TryRecvError> {
+        let mut state = self.shared.lock();
+
+        if self.next < state.head {
+            let missed = state.head - self.next;
+            self.next = state.head;
+            return Err(TryRecvError::Lagged(missed));
+        }
+
+        let offset = (self.next - state.head) as usize;
+        if let Some(value) = state.buffer.get(offset) {
+            self.next += 1;
+            return Ok(value.clone());
+        }
+
+        if state.senders == 0 {
+            return Err(TryRecvError::Closed);
+        }
+
+        if let Some(cx) = cx {
+            state.wakers[self.key] = Some(cx.waker().clone());
+        }
+
+        Err(TryRecvError::Empty)
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Creates a receiver seeing the messages sent from now on.
+    pub fn resubscribe(&self) -> Receiver<T> {
+        self.shared.new_receiver(&mut self.shared.lock())
+    }
+
+    /// Returns the amount of messages sent but not yet received.
+    pub fn len(&self) -> usize {
+        let state = self.shared.lock();
+        let tail = state.head + state.buffer.len() as u64;
+
+        (tail - self.next.max(state.head)) as usize
+    }
+
+    /// Returns whether every message sent was received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone> Stream for Receiver<T> {
+    type Item = Result<T, RecvError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut().poll_recv(cx) {
+            Poll::Ready(Err(RecvError::Closed)) => Poll::Ready(None),
+            Poll::Ready(res) => Poll::Ready(Some(res)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receivers -= 1;
+        state.wakers.remove(self.key);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    #[test]
+    fn every_receiver_gets_every_message() {
+        let (tx, mut rx1) = channel(4);
+        let mut rx2 = tx.subscribe();
+
+        assert_eq!(tx.send(1).unwrap(), 2);
+        assert_eq!(tx.send(2).unwrap(), 2);
+
+        block_on(async {
+            assert_eq!(rx1.recv().await, Ok(1));
+            assert_eq!(rx1.recv().await, Ok(2));
+            assert_eq!(rx2.recv().await, Ok(1));
+        });
+
+        // Only messages sent after subscribing are seen.
+        let mut late = rx2.resubscribe();
+        assert_eq!(late.try_recv(), Err(TryRecvError::Empty));
+
+        drop(tx);
+        assert_eq!(rx2.try_recv(), Ok(2));
+        assert_eq!(rx2.try_recv(), Err(TryRecvError::Closed));
+        assert_eq!(block_on(rx1.recv()), Err(RecvError::Closed));
+    }
+
+    #[test]
+    fn lagging_receiver() {
+        let (tx, mut rx) = channel(2);
+
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(rx.try_recv(), Ok(3));
+
+        drop(tx);
+        let rest: Vec<_> = block_on(rx.collect());
+        assert_eq!(rest, vec![Ok(4)]);
+    }
+
+    #[test]
+    fn send_without_receivers() {
+        let (tx, rx) = channel(1);
+        drop(rx);
+
+        assert_eq!(tx.send(7), Err(SendError(7)));
+        assert_eq!(tx.receiver_count(), 0);
+    }
+}
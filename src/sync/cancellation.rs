@@ -0,0 +1,182 @@
+use super::notify::{Notified, Notify};
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+/// Token signalling cancellation to the tasks holding a clone.
+///
+/// Child tokens are cancelled along with their parent,
+/// cancelling a child leaves the parent alone.
+#[derive(Clone)]
+pub struct CancellationToken {
+    node: Arc<Node>,
+}
+
+// Children point to their parent, so cancelling needs no list of children
+// and dropping a child needs no cleanup.
+struct Node {
+    cancelled: AtomicBool,
+    notify: Notify,
+    parent: Option<Arc<Node>>,
+}
+
+impl Node {
+    fn ancestry(&self) -> impl Iterator<Item = &Node> {
+        std::iter::successors(Some(self), |node| node.parent.as_deref())
+    }
+}
+
+/// Future of `CancellationToken::cancelled`.
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitForCancellationFuture<'t> {
+    token: &'t CancellationToken,
+
+    // One per node up to the root.
+    notified: Vec<Notified<'t>>,
+}
+
+/// Cancels its token when dropped, unless disarmed.
+#[must_use = "the token is cancelled right away if unused"]
+pub struct DropGuard {
+    token: Option<CancellationToken>,
+}
+
+impl CancellationToken {
+    /// Creates a token without a parent.
+    pub fn new() -> CancellationToken {
+        CancellationToken {
+            node: Arc::new(Node {
+                cancelled: AtomicBool::new(false),
+                notify: Notify::new(),
+                parent: None,
+            }),
+        }
+    }
+
+    /// Creates a token cancelled along with this one.
+    pub fn child_token(&self) -> CancellationToken {
+        CancellationToken {
+            node: Arc::new(Node {
+                cancelled: AtomicBool::new(false),
+                notify: Notify::new(),
+                parent: Some(Arc::clone(&self.node)),
+            }),
+        }
+    }
+
+    /// Cancels the token and its children, waking the tasks waiting for it.
+    pub fn cancel(&self) {
+        if !self.node.cancelled.swap(true, Ordering::AcqRel) {
+            self.node.notify.notify_waiters();
+        }
+    }
+
+    /// Returns whether the token or one of its parents was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.node
+            .ancestry()
+            .any(|node| node.cancelled.load(Ordering::Acquire))
+    }
+
+    /// Waits for the token to be cancelled.
+    pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+        WaitForCancellationFuture {
+            token: self,
+            notified: self
+                .node
+                .ancestry()
+                .map(|node| node.notify.notified())
+                .collect(),
+        }
+    }
+
+    /// Runs `fut` until the token is cancelled, returning `None` if it was.
+    pub async fn run_until_cancelled<F: Future>(&self, fut: F) -> Option<F::Output> {
+        let mut fut = std::pin::pin!(fut);
+        let mut cancelled = self.cancelled();
+
+        std::future::poll_fn(|cx| {
+            if Pin::new(&mut cancelled).poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+
+            fut.as_mut().poll(cx).map(Some)
+        })
+        .await
+    }
+
+    /// Returns a guard cancelling the token when dropped.
+    pub fn drop_guard(self) -> DropGuard {
+        DropGuard { token: Some(self) }
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> CancellationToken {
+        CancellationToken::new()
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl Future for WaitForCancellationFuture<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+
+        if this.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        // Only `notify_waiters` is used, a notification means cancellation.
+        // Those sent after creating the futures are never missed.
+        for notified in &mut this.notified {
+            if Pin::new(notified).poll(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl fmt::Debug for WaitForCancellationFuture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitForCancellationFuture")
+            .finish_non_exhaustive()
+    }
+}
+
+impl DropGuard {
+    /// Gives the token back without cancelling it.
+    pub fn disarm(mut self) -> CancellationToken {
+        self.token.take().expect("token taken before drop")
+    }
+}
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            token.cancel();
+        }
+    }
+}
+
+impl fmt::Debug for DropGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropGuard")
+            .field("token", &self.token)
+            .finish()
+    }
+}
@@ -0,0 +1,459 @@
+//! Synchronization primitives for tasks.
+//!
+//! Unlike their `std::sync` counterparts, waiting for them suspends the task
+//! instead of blocking the worker thread, and their guards can be held across `.await`.
+//! Waiters are served in FIFO order.
+//!
+//! The `oneshot`, `mpsc`, `broadcast` and `watch` modules provide channels
+//! for passing values between tasks.
+//!
+//! `Notify`, `Barrier`, `OnceCell` and `CancellationToken` cover event signalling,
+//! lazy initialization and shutdown propagation.
+
+mod barrier;
+mod batch;
+pub mod broadcast;
+mod cancellation;
+pub mod mpsc;
+mod mutex;
+mod notify;
+mod once_cell;
+pub mod oneshot;
+mod rwlock;
+mod semaphore;
+pub mod watch;
+
+pub use barrier::{Barrier, BarrierWaitResult};
+pub use cancellation::{CancellationToken, DropGuard, WaitForCancellationFuture};
+
+pub use mutex::{Mutex, MutexGuard, OwnedMutexGuard};
+pub use notify::{Notified, Notify};
+pub use once_cell::OnceCell;
+pub use rwlock::{
+    OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+pub use semaphore::{OwnedSemaphorePermit, Semaphore, SemaphorePermit};
+
+use std::error::Error;
+use std::fmt;
+
+/// Error of acquiring permits from a closed `Semaphore`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcquireError(());
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "semaphore closed")
+    }
+}
+
+impl Error for AcquireError {}
+
+/// Error of `Semaphore::try_acquire`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore was closed.
+    Closed,
+
+    /// Not enough permits were available.
+    NoPermits,
+}
+
+impl fmt::Display for TryAcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryAcquireError::Closed => write!(f, "semaphore closed"),
+            TryAcquireError::NoPermits => write!(f, "no permits available"),
+        }
+    }
+}
+
+impl Error for TryAcquireError {}
+
+/// Error of the `try_*` locking methods, the lock was not free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryLockError(());
+
+impl fmt::Display for TryLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lock held or waited for")
+    }
+}
+
+impl Error for TryLockError {}
+
+/// Error of `OnceCell::set`, handing the value back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetError<T> {
+    /// The cell was already set.
+    AlreadyInitialized(T),
+
+    /// An initializer was running.
+    Initializing(T),
+}
+
+impl<T> fmt::Display for SetError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::AlreadyInitialized(_) => write!(f, "cell already initialized"),
+            SetError::Initializing(_) => write!(f, "cell being initialized"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for SetError<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::task::noop_waker;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::thread;
+
+    fn poll_once<F: Future>(fut: std::pin::Pin<&mut F>) -> Poll<F::Output> {
+        let waker = noop_waker();
+        fut.poll(&mut Context::from_waker(&waker))
+    }
+
+    // Polls each future once with the calling task's waker, none may be ready.
+    async fn poll_pending<F: Future + Unpin>(futures: &mut [F]) {
+        std::future::poll_fn(|cx| {
+            for fut in futures.iter_mut() {
+                assert!(std::pin::Pin::new(fut).poll(cx).is_pending());
+            }
+
+            Poll::Ready(())
+        })
+        .await
+    }
+
+    #[test]
+    fn mutex_lock_and_try_lock() {
+        let mutex = Mutex::new(1);
+
+        block_on(async {
+            let mut guard = mutex.lock().await;
+            *guard += 1;
+            assert!(mutex.try_lock().is_err());
+            drop(guard);
+
+            assert_eq!(*mutex.try_lock().unwrap(), 2);
+        });
+
+        assert_eq!(mutex.into_inner(), 2);
+    }
+
+    #[test]
+    fn owned_guards_across_threads() {
+        let mutex = Arc::new(Mutex::new(0));
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mutex = Arc::clone(&mutex);
+                thread::spawn(move || {
+                    block_on(async {
+                        for _ in 0..10 {
+                            let mut guard = Arc::clone(&mutex).lock_owned().await;
+                            *guard += 1;
+                        }
+                    })
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(*mutex.try_lock().unwrap(), 40);
+    }
+
+    #[test]
+    fn semaphore_fifo() {
+        let sem = Semaphore::new(0);
+
+        let mut many = pin!(sem.acquire_many(2));
+        let mut one = pin!(sem.acquire());
+        assert!(poll_once(many.as_mut()).is_pending());
+        assert!(poll_once(one.as_mut()).is_pending());
+
+        // The first waiter holds back the second one.
+        sem.add_permits(1);
+        assert!(poll_once(one.as_mut()).is_pending());
+        assert!(sem.try_acquire().is_err());
+
+        sem.add_permits(1);
+        let permit = match poll_once(many.as_mut()) {
+            Poll::Ready(permit) => permit.unwrap(),
+            Poll::Pending => panic!("permits not granted"),
+        };
+        assert_eq!(permit.num_permits(), 2);
+        assert!(poll_once(one.as_mut()).is_pending());
+
+        drop(permit);
+        assert!(poll_once(one.as_mut()).is_ready());
+    }
+
+    #[test]
+    fn cancelled_acquire_releases_its_place() {
+        let sem = Semaphore::new(1);
+        let permit = sem.try_acquire().unwrap();
+
+        {
+            // Gives up its place before being granted.
+            let mut first = pin!(sem.acquire());
+            assert!(poll_once(first.as_mut()).is_pending());
+        }
+
+        {
+            // Granted but dropped before being polled again.
+            let mut second = pin!(sem.acquire());
+            assert!(poll_once(second.as_mut()).is_pending());
+            drop(permit);
+            assert_eq!(sem.available_permits(), 0);
+        }
+
+        assert_eq!(sem.available_permits(), 1);
+        assert!(sem.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn semaphore_close() {
+        let sem = Arc::new(Semaphore::new(0));
+
+        let mut waiting = pin!(Arc::clone(&sem).acquire_owned());
+        assert!(poll_once(waiting.as_mut()).is_pending());
+
+        sem.close();
+        assert!(matches!(poll_once(waiting.as_mut()), Poll::Ready(Err(_))));
+        assert_eq!(sem.try_acquire().unwrap_err(), TryAcquireError::Closed);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many permits")]
+    fn acquire_past_max_permits_panics() {
+        let sem = Semaphore::new(0);
+        let _ = poll_once(pin!(sem.acquire_many(Semaphore::MAX_PERMITS + 1)));
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn many_acquires_in_one_task() {
+        let mut exec = crate::Executor::new(1);
+
+        // Each waiter holds a clone of the task's waker, more than fit in a byte.
+        let res = exec.block_on(async {
+            let sem = Semaphore::new(0);
+            let mut waiters: Vec<_> = (0..300).map(|_| Box::pin(sem.acquire())).collect();
+            poll_pending(&mut waiters).await;
+
+            sem.add_permits(300);
+            for waiter in waiters {
+                drop(waiter.await.unwrap());
+            }
+        });
+
+        assert!(res.is_ok());
+        exec.shutdown();
+    }
+
+    #[test]
+    fn rwlock_readers_and_writers() {
+        let lock = RwLock::new(5);
+
+        let first = lock.try_read().unwrap();
+        let second = lock.try_read().unwrap();
+        assert_eq!(*first + *second, 10);
+
+        let mut write = pin!(lock.write());
+        assert!(poll_once(write.as_mut()).is_pending());
+
+        // Readers arriving after a waiting writer queue behind it.
+        assert!(lock.try_read().is_err());
+        let mut late_read = pin!(lock.read());
+        assert!(poll_once(late_read.as_mut()).is_pending());
+
+        drop((first, second));
+        let mut guard = match poll_once(write.as_mut()) {
+            Poll::Ready(guard) => guard,
+            Poll::Pending => panic!("writer not granted"),
+        };
+        *guard = 6;
+        assert!(poll_once(late_read.as_mut()).is_pending());
+
+        drop(guard);
+        match poll_once(late_read.as_mut()) {
+            Poll::Ready(guard) => assert_eq!(*guard, 6),
+            Poll::Pending => panic!("reader not granted"),
+        }
+    }
+
+    #[test]
+    fn notify_one_stores_a_permit() {
+        let notify = Notify::new();
+
+        // Nobody waits, the next waiter goes through.
+        notify.notify_one();
+        notify.notify_one();
+        assert!(poll_once(pin!(notify.notified())).is_ready());
+        assert!(poll_once(pin!(notify.notified())).is_pending());
+
+        let mut first = pin!(notify.notified());
+        let mut second = pin!(notify.notified());
+        assert!(poll_once(first.as_mut()).is_pending());
+        assert!(poll_once(second.as_mut()).is_pending());
+
+        notify.notify_one();
+        assert!(poll_once(second.as_mut()).is_pending());
+        assert!(poll_once(first.as_mut()).is_ready());
+    }
+
+    #[test]
+    fn notify_waiters_and_dropped_waiter() {
+        let notify = Notify::new();
+
+        // Created before the call, so woken even though never polled.
+        let early = notify.notified();
+        let mut waiting = pin!(notify.notified());
+        assert!(poll_once(waiting.as_mut()).is_pending());
+
+        notify.notify_waiters();
+        assert!(poll_once(waiting.as_mut()).is_ready());
+        assert!(poll_once(pin!(early)).is_ready());
+        assert!(poll_once(pin!(notify.notified())).is_pending());
+
+        {
+            let mut picked = pin!(notify.notified());
+            assert!(poll_once(picked.as_mut()).is_pending());
+            notify.notify_one();
+        }
+
+        // The notification of the dropped waiter was stored.
+        assert!(poll_once(pin!(notify.notified())).is_ready());
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn many_notified_in_one_task() {
+        let mut exec = crate::Executor::new(1);
+
+        let res = exec.block_on(async {
+            let notify = Notify::new();
+            let mut waiters: Vec<_> = (0..300).map(|_| Box::pin(notify.notified())).collect();
+            poll_pending(&mut waiters).await;
+
+            notify.notify_waiters();
+            for waiter in waiters {
+                waiter.await;
+            }
+        });
+
+        assert!(res.is_ok());
+        exec.shutdown();
+    }
+
+    #[test]
+    fn barrier_releases_together() {
+        let barrier = Arc::new(Barrier::new(3));
+
+        let threads: Vec<_> = (0..3)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    block_on(async {
+                        let first = barrier.wait().await.is_leader();
+                        let second = barrier.wait().await.is_leader();
+                        first as u8 + second as u8
+                    })
+                })
+            })
+            .collect();
+
+        let leaders: u8 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+        assert_eq!(leaders, 2);
+    }
+
+    #[test]
+    fn once_cell_init() {
+        let cell = OnceCell::new();
+
+        block_on(async {
+            let res = cell.get_or_try_init(|| async { Err::<u8, _>(()) }).await;
+            assert!(res.is_err());
+            assert!(!cell.initialized());
+
+            assert_eq!(*cell.get_or_init(|| async { 1 }).await, 1);
+            assert_eq!(*cell.get_or_init(|| async { 2 }).await, 1);
+        });
+
+        assert_eq!(cell.set(3), Err(SetError::AlreadyInitialized(3)));
+        assert_eq!(cell.into_inner(), Some(1));
+    }
+
+    #[test]
+    fn once_cell_waits_for_initializer() {
+        let cell = OnceCell::new();
+
+        let mut first = pin!(cell.get_or_init(|| async {
+            crate::sync::tests::yield_once().await;
+            1
+        }));
+        assert!(poll_once(first.as_mut()).is_pending());
+
+        let mut second = pin!(cell.get_or_init(|| async { 2 }));
+        assert!(poll_once(second.as_mut()).is_pending());
+        assert_eq!(cell.set(3), Err(SetError::Initializing(3)));
+
+        assert!(matches!(poll_once(first.as_mut()), Poll::Ready(1)));
+        assert!(matches!(poll_once(second.as_mut()), Poll::Ready(1)));
+    }
+
+    async fn yield_once() {
+        let mut yielded = false;
+        std::future::poll_fn(|_| {
+            if yielded {
+                return Poll::Ready(());
+            }
+
+            yielded = true;
+            Poll::Pending
+        })
+        .await
+    }
+
+    #[test]
+    fn cancellation_reaches_children() {
+        let root = CancellationToken::new();
+        let child = root.child_token();
+        let grandchild = child.child_token();
+        let other = root.child_token();
+
+        let mut waiting = pin!(grandchild.cancelled());
+        assert!(poll_once(waiting.as_mut()).is_pending());
+
+        child.cancel();
+        assert!(poll_once(waiting.as_mut()).is_ready());
+        assert!(grandchild.is_cancelled());
+        assert!(!root.is_cancelled() && !other.is_cancelled());
+
+        let waiter = {
+            let other = other.clone();
+            thread::spawn(move || block_on(other.cancelled()))
+        };
+
+        drop(root.drop_guard());
+        waiter.join().unwrap();
+        assert!(other.is_cancelled());
+
+        let token = CancellationToken::new();
+        assert!(!token.drop_guard().disarm().is_cancelled());
+        assert_eq!(
+            block_on(CancellationToken::new().run_until_cancelled(async { 5 })),
+            Some(5)
+        );
+    }
+}
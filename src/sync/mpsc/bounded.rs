@@ -0,0 +1,221 @@
+use super::chan::Chan;
+use super::error::{SendError, TryRecvError, TrySendError};
+use crate::sync::TryAcquireError;
+
+use futures::Stream;
+
+use std::fmt;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Creates a channel holding up to `buffer` messages.
+///
+/// Senders wait for room once it is full, in the order they started waiting.
+///
+/// # Panics
+///
+/// Panics if `buffer` is 0.
+pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(buffer > 0, "mpsc channel capacity must be greater than 0");
+
+    let chan = Arc::new(Chan::new(Some(buffer)));
+    (
+        Sender {
+            chan: Arc::clone(&chan),
+        },
+        Receiver { chan },
+    )
+}
+
+/// Sending half of a bounded channel, cloned for every producer.
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+/// Receiving half of a bounded channel.
+///
+/// Also a `Stream` of the messages, ending once every sender is gone.
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+/// Reserved room in the channel, for sending a message without waiting.
+///
+/// The room is given back if dropped without sending.
+pub struct Permit<'s, T> {
+    chan: &'s Chan<T>,
+}
+
+impl<T> Sender<T> {
+    /// Sends `value`, waiting for room in the channel.
+    ///
+    /// Fails if the receiver was closed, handing `value` back.
+    /// Cancel safe: dropping the future gives up its place in the queue.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.reserve().await {
+            Ok(permit) => {
+                permit.send(value);
+                Ok(())
+            }
+            Err(_) => Err(SendError(value)),
+        }
+    }
+
+    /// Sends `value` if there is room, without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match self.try_reserve() {
+            Ok(permit) => {
+                permit.send(value);
+                Ok(())
+            }
+            Err(TrySendError::Full(())) => Err(TrySendError::Full(value)),
+            Err(TrySendError::Closed(())) => Err(TrySendError::Closed(value)),
+        }
+    }
+
+    /// Waits for room in the channel, reserving it.
+    pub async fn reserve(&self) -> Result<Permit<'_, T>, SendError<()>> {
+        match self.chan.semaphore().acquire(1).await {
+            Ok(()) => Ok(Permit { chan: &self.chan }),
+            Err(_) => Err(SendError(())),
+        }
+    }
+
+    /// Reserves room in the channel if there is some, without waiting.
+    pub fn try_reserve(&self) -> Result<Permit<'_, T>, TrySendError<()>> {
+        match self.chan.semaphore().try_acquire(1) {
+            Ok(()) => Ok(Permit { chan: &self.chan }),
+            Err(TryAcquireError::NoPermits) => Err(TrySendError::Full(())),
+            Err(TryAcquireError::Closed) => Err(TrySendError::Closed(())),
+        }
+    }
+
+    /// Returns whether the receiver was closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.chan.is_closed()
+    }
+
+    /// Waits for the receiver to be closed or dropped.
+    pub async fn closed(&self) {
+        poll_fn(|cx| self.chan.poll_closed(cx)).await
+    }
+
+    /// Returns the amount of messages which can be sent without waiting.
+    pub fn capacity(&self) -> usize {
+        self.chan.semaphore().available_permits()
+    }
+
+    /// Returns the capacity the channel was created with.
+    pub fn max_capacity(&self) -> usize {
+        self.chan.max_capacity()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.chan.add_sender();
+        Sender {
+            chan: Arc::clone(&self.chan),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.chan.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Permit<'_, T> {
+    /// Sends `value` using the reserved room.
+    pub fn send(self, value: T) {
+        let chan = self.chan;
+        std::mem::forget(self);
+
+        // The receiver closed in the meantime, the value is dropped.
+        if chan.push(value).is_err() {
+            chan.semaphore().release(1);
+        }
+    }
+}
+
+impl<T> Drop for Permit<'_, T> {
+    fn drop(&mut self) {
+        self.chan.semaphore().release(1);
+    }
+}
+
+impl<T> fmt::Debug for Permit<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permit").finish_non_exhaustive()
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Waits for the next message.
+    ///
+    /// Resolves to `None` once the channel is empty
+    /// and every sender is gone or the receiver was closed.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls for the next message.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.chan.poll_recv(cx)
+    }
+
+    /// Takes the next message if one is queued, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.chan.try_recv()
+    }
+
+    /// Closes the channel, failing later sends.
+    ///
+    /// Queued messages can still be received.
+    pub fn close(&mut self) {
+        self.chan.close()
+    }
+
+    /// Returns the amount of queued messages.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns whether no message is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.chan.close_and_drain();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("len", &self.len())
+            .finish()
+    }
+}
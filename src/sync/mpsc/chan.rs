@@ -0,0 +1,182 @@
+// State shared by both flavors of the channel.
+//
+// Bounded channels take a permit of `capacity` for every queued message,
+// which the receiver gives back when taking it out.
+use super::error::TryRecvError;
+use crate::sync::batch::Semaphore;
+use crate::task::poll_budgeted;
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    rx_closed: bool,
+    rx_waker: Option<Waker>,
+
+    // Senders waiting for the receiver to close.
+    closed_wakers: Vec<Waker>,
+}
+
+pub(super) struct Chan<T> {
+    state: Mutex<State<T>>,
+
+    // `None` for unbounded channels.
+    capacity: Option<(Semaphore, usize)>,
+}
+
+impl<T> Chan<T> {
+    pub(super) fn new(capacity: Option<usize>) -> Chan<T> {
+        Chan {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                senders: 1,
+                rx_closed: false,
+                rx_waker: None,
+                closed_wakers: Vec::new(),
+            }),
+            capacity: capacity.map(|cap| (Semaphore::new(cap), cap)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(super) fn semaphore(&self) -> &Semaphore {
+        &self.capacity.as_ref().expect("unbounded channel").0
+    }
+
+    pub(super) fn max_capacity(&self) -> usize {
+        self.capacity.as_ref().expect("unbounded channel").1
+    }
+
+    /// Queues `value`, the caller holds a permit for bounded channels.
+    ///
+    /// Hands `value` back if the receiver is closed.
+    pub(super) fn push(&self, value: T) -> Result<(), T> {
+        let mut state = self.lock();
+        if state.rx_closed {
+            return Err(value);
+        }
+
+        state.queue.push_back(value);
+        let waker = state.rx_waker.take();
+        drop(state);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn add_sender(&self) {
+        self.lock().senders += 1;
+    }
+
+    pub(super) fn drop_sender(&self) {
+        let mut state = self.lock();
+        state.senders -= 1;
+
+        if state.senders > 0 {
+            return;
+        }
+
+        let waker = state.rx_waker.take();
+        drop(state);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    pub(super) fn is_closed(&self) -> bool {
+        self.lock().rx_closed
+    }
+
+    pub(super) fn poll_closed(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.lock();
+        if state.rx_closed {
+            return Poll::Ready(());
+        }
+
+        if !state.closed_wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.closed_wakers.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+
+    /// Takes a message, giving its permit back.
+    pub(super) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.lock();
+
+        match state.queue.pop_front() {
+            Some(value) => {
+                drop(state);
+                self.release();
+                Ok(value)
+            }
+
+            None if state.senders == 0 || state.rx_closed => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    pub(super) fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        poll_budgeted(cx, |cx| {
+            let mut state = self.lock();
+
+            if let Some(value) = state.queue.pop_front() {
+                drop(state);
+                self.release();
+                return Poll::Ready(Some(value));
+            }
+
+            if state.senders == 0 || state.rx_closed {
+                return Poll::Ready(None);
+            }
+
+            state.rx_waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+    }
+
+    fn release(&self) {
+        if let Some((semaphore, _)) = &self.capacity {
+            semaphore.release(1);
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    /// Fails later sends, queued messages can still be received.
+    pub(super) fn close(&self) {
+        let mut state = self.lock();
+        state.rx_closed = true;
+        let wakers = std::mem::take(&mut state.closed_wakers);
+        drop(state);
+
+        // Fails the senders waiting for capacity.
+        if let Some((semaphore, _)) = &self.capacity {
+            semaphore.close();
+        }
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Closes the channel and drops the queued messages.
+    pub(super) fn close_and_drain(&self) {
+        self.close();
+
+        let queue = std::mem::take(&mut self.lock().queue);
+        drop(queue);
+    }
+}
@@ -0,0 +1,94 @@
+//! Multi-producer, single-consumer channels.
+//!
+//! `channel` creates a bounded channel, whose senders wait for room once it is full.
+//! `unbounded_channel` creates one whose senders never wait.
+
+mod bounded;
+mod chan;
+pub mod error;
+mod unbounded;
+
+pub use bounded::{Permit, Receiver, Sender, channel};
+pub use unbounded::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+#[cfg(test)]
+mod tests {
+    use super::error::{TryRecvError, TrySendError};
+    use super::*;
+    use futures::StreamExt;
+    use futures::executor::block_on;
+    use futures::task::noop_waker;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll};
+
+    fn poll_once<F: Future>(fut: std::pin::Pin<&mut F>) -> Poll<F::Output> {
+        let waker = noop_waker();
+        fut.poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn bounded_backpressure() {
+        let (tx, mut rx) = channel(2);
+
+        block_on(async {
+            tx.send(1).await.unwrap();
+            tx.send(2).await.unwrap();
+        });
+        assert_eq!(tx.capacity(), 0);
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+
+        {
+            let mut send = pin!(tx.send(3));
+            assert!(poll_once(send.as_mut()).is_pending());
+
+            // Taking a message out makes room for the waiting sender.
+            assert_eq!(rx.try_recv(), Ok(1));
+            assert!(matches!(poll_once(send.as_mut()), Poll::Ready(Ok(()))));
+        }
+
+        assert_eq!(tx.try_reserve().unwrap_err(), TrySendError::Full(()));
+        assert_eq!(rx.len(), 2);
+
+        drop(tx);
+        let rest: Vec<_> = block_on(rx.collect());
+        assert_eq!(rest, vec![2, 3]);
+    }
+
+    #[test]
+    fn bounded_close() {
+        let (tx, mut rx) = channel(1);
+        let permit = block_on(tx.reserve()).unwrap();
+
+        rx.close();
+        assert!(tx.is_closed());
+        block_on(tx.closed());
+        assert!(block_on(tx.send(1)).is_err());
+
+        // Reserved room can still be used, but the message is dropped.
+        permit.send(2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(block_on(rx.recv()), None);
+    }
+
+    #[test]
+    fn unbounded_many_senders() {
+        let (tx, mut rx) = unbounded_channel();
+        let other = tx.clone();
+
+        tx.send(1).unwrap();
+        other.send(2).unwrap();
+        assert_eq!(rx.try_recv(), Ok(1));
+
+        drop(tx);
+        assert_eq!(block_on(rx.recv()), Some(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        drop(other);
+        assert_eq!(block_on(rx.recv()), None);
+
+        let (tx, rx) = unbounded_channel::<()>();
+        drop(rx);
+        assert!(tx.send(()).is_err());
+    }
+}
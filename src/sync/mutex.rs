@@ -0,0 +1,153 @@
+use super::TryLockError;
+use super::batch::Semaphore;
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Mutual exclusion lock whose guard can be held across `.await`.
+///
+/// Tasks get the lock in the order they started waiting for it.
+pub struct Mutex<T: ?Sized> {
+    semaphore: Semaphore,
+    data: UnsafeCell<T>,
+}
+
+// Safety: the semaphore hands out access to the data to one guard at a time.
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+/// Guard of a locked `Mutex`, unlocks it when dropped.
+#[must_use = "the mutex is unlocked right away if unused"]
+pub struct MutexGuard<'m, T: ?Sized> {
+    lock: &'m Mutex<T>,
+}
+
+/// Guard of a locked `Arc<Mutex>`, keeping the mutex alive.
+#[must_use = "the mutex is unlocked right away if unused"]
+pub struct OwnedMutexGuard<T: ?Sized> {
+    lock: Arc<Mutex<T>>,
+}
+
+// Safety: the guard gives out `&T` when shared.
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for OwnedMutexGuard<T> {}
+
+impl<T> Mutex<T> {
+    /// Creates an unlocked mutex.
+    pub const fn new(data: T) -> Mutex<T> {
+        Mutex {
+            semaphore: Semaphore::new(1),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the mutex, returning the data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Waits for the lock.
+    ///
+    /// Cancel safe: dropping the future gives up its place in the queue.
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        // The semaphore is never closed.
+        let _ = self.semaphore.acquire(1).await;
+        MutexGuard { lock: self }
+    }
+
+    /// Takes the lock if it is free and nobody waits for it.
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T>, TryLockError> {
+        match self.semaphore.try_acquire(1) {
+            Ok(()) => Ok(MutexGuard { lock: self }),
+            Err(_) => Err(TryLockError(())),
+        }
+    }
+
+    /// Waits for the lock, returning a guard which keeps the mutex alive.
+    pub async fn lock_owned(self: Arc<Self>) -> OwnedMutexGuard<T> {
+        let _ = self.semaphore.acquire(1).await;
+        OwnedMutexGuard { lock: self }
+    }
+
+    /// Takes the lock if it is free, returning a guard which keeps the mutex alive.
+    pub fn try_lock_owned(self: Arc<Self>) -> Result<OwnedMutexGuard<T>, TryLockError> {
+        match self.semaphore.try_acquire(1) {
+            Ok(()) => Ok(OwnedMutexGuard { lock: self }),
+            Err(_) => Err(TryLockError(())),
+        }
+    }
+
+    /// Returns a mutable reference to the data, no locking needed.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Mutex<T> {
+        Mutex::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(data: T) -> Mutex<T> {
+        Mutex::new(data)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(_) => d.field("data", &format_args!("<locked>")),
+        };
+
+        d.finish()
+    }
+}
+
+macro_rules! guard_impls {
+    ($([$($gen: tt)*] $ty: ty),*) => {$(
+        impl<$($gen)*> Deref for $ty {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                // Safety: the guard holds the only permit.
+                unsafe { &*self.lock.data.get() }
+            }
+        }
+
+        impl<$($gen)*> DerefMut for $ty {
+            fn deref_mut(&mut self) -> &mut T {
+                // Safety: the guard holds the only permit.
+                unsafe { &mut *self.lock.data.get() }
+            }
+        }
+
+        impl<$($gen)*> Drop for $ty {
+            fn drop(&mut self) {
+                self.lock.semaphore.release(1);
+            }
+        }
+
+        impl<$($gen)*> fmt::Debug for $ty where T: fmt::Debug {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&**self, f)
+            }
+        }
+    )*};
+}
+
+guard_impls!(['m, T: ?Sized] MutexGuard<'m, T>, [T: ?Sized] OwnedMutexGuard<T>);
+
+impl<T: ?Sized> OwnedMutexGuard<T> {
+    /// Returns the mutex the guard belongs to.
+    pub fn mutex(&self) -> &Arc<Mutex<T>> {
+        &self.lock
+    }
+}
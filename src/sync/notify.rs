@@ -0,0 +1,224 @@
+use slab::Slab;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+/// Wakes up tasks waiting for an event.
+///
+/// `notify_one` wakes the oldest waiter, or lets the next one through right away
+/// if nobody is waiting. `notify_waiters` wakes everybody waiting, without
+/// affecting later waiters.
+pub struct Notify {
+    state: Mutex<State>,
+}
+
+struct State {
+    // Stored `notify_one` for the next waiter.
+    permit: bool,
+
+    // Bumped by `notify_waiters`.
+    generation: u64,
+
+    waiters: Slab<Waiter>,
+    queue: VecDeque<usize>,
+}
+
+struct Waiter {
+    waker: Option<Waker>,
+    notified: Option<Notification>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Notification {
+    One,
+    All,
+}
+
+impl State {
+    // Hands a `notify_one` to the oldest waiter, or stores it.
+    fn notify_one(&mut self) -> Option<Waker> {
+        if let Some(key) = self.queue.pop_front() {
+            let waiter = &mut self.waiters[key];
+            waiter.notified = Some(Notification::One);
+            return waiter.waker.take();
+        }
+
+        self.permit = true;
+        None
+    }
+}
+
+/// Future of `Notify::notified`.
+///
+/// Dropping it after being picked by `notify_one` passes the notification on.
+#[must_use = "futures do nothing unless polled"]
+pub struct Notified<'n> {
+    notify: &'n Notify,
+    state: Waiting,
+}
+
+enum Waiting {
+    // Not polled yet, holding the generation at creation.
+    Init(u64),
+    Queued(usize),
+    Done,
+}
+
+impl Notify {
+    /// Creates a `Notify` without a stored notification.
+    pub const fn new() -> Notify {
+        Notify {
+            state: Mutex::new(State {
+                permit: false,
+                generation: 0,
+                waiters: Slab::new(),
+                queue: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Waits for a notification.
+    ///
+    /// The future sees `notify_waiters` calls made after its creation, even before
+    /// being polled, while `notify_one` only picks it once it was polled.
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            state: Waiting::Init(self.lock().generation),
+        }
+    }
+
+    /// Wakes the oldest waiter.
+    ///
+    /// If nobody is waiting, the next call to `notified` completes right away.
+    /// Notifications don't add up, at most one is stored.
+    pub fn notify_one(&self) {
+        let waker = self.lock().notify_one();
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Wakes every current waiter, nothing is stored for later ones.
+    pub fn notify_waiters(&self) {
+        let mut state = self.lock();
+        state.generation += 1;
+
+        let mut wakers = Vec::with_capacity(state.queue.len());
+        while let Some(key) = state.queue.pop_front() {
+            let waiter = &mut state.waiters[key];
+            waiter.notified = Some(Notification::All);
+            wakers.extend(waiter.waker.take());
+        }
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Notify {
+        Notify::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("waiters", &self.lock().queue.len())
+            .finish()
+    }
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let mut state = this.notify.lock();
+
+        match this.state {
+            Waiting::Init(generation) => {
+                if state.generation != generation || state.permit {
+                    // Woken by `notify_waiters`, the permit is left for the next one.
+                    if state.generation == generation {
+                        state.permit = false;
+                    }
+
+                    this.state = Waiting::Done;
+                    return Poll::Ready(());
+                }
+
+                let key = state.waiters.insert(Waiter {
+                    waker: Some(cx.waker().clone()),
+                    notified: None,
+                });
+                state.queue.push_back(key);
+                this.state = Waiting::Queued(key);
+
+                Poll::Pending
+            }
+
+            Waiting::Queued(key) => {
+                let waiter = &mut state.waiters[key];
+                if waiter.notified.is_some() {
+                    state.waiters.remove(key);
+                    this.state = Waiting::Done;
+                    return Poll::Ready(());
+                }
+
+                match &mut waiter.waker {
+                    Some(waker) if waker.will_wake(cx.waker()) => {}
+                    waker => *waker = Some(cx.waker().clone()),
+                }
+
+                Poll::Pending
+            }
+
+            Waiting::Done => Poll::Ready(()),
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Waiting::Queued(key) = self.state else {
+            return;
+        };
+
+        let mut state = self.notify.lock();
+        let waiter = state.waiters.remove(key);
+
+        let waker = match waiter.notified {
+            None => {
+                state.queue.retain(|&k| k != key);
+                None
+            }
+
+            Some(Notification::One) => state.notify_one(),
+            Some(Notification::All) => None,
+        };
+        drop(state);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl fmt::Debug for Notified<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notified").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,129 @@
+use super::Semaphore;
+use super::SetError;
+
+use std::fmt;
+use std::future::Future;
+use std::sync::OnceLock;
+
+/// Cell written once, possibly by an async initializer.
+///
+/// Concurrent `get_or_init` calls run one initializer at a time,
+/// the others wait for its value.
+/// If an initializer fails or is cancelled, the next waiter runs its own.
+pub struct OnceCell<T> {
+    value: OnceLock<T>,
+
+    // Held while running an initializer.
+    init: Semaphore,
+}
+
+impl<T> OnceCell<T> {
+    /// Creates an empty cell.
+    pub const fn new() -> OnceCell<T> {
+        OnceCell {
+            value: OnceLock::new(),
+            init: Semaphore::new(1),
+        }
+    }
+
+    /// Creates a cell holding `value` if it is `Some`.
+    pub fn new_with(value: Option<T>) -> OnceCell<T> {
+        let cell = OnceCell::new();
+        if let Some(value) = value {
+            let _ = cell.value.set(value);
+        }
+
+        cell
+    }
+
+    /// Returns the value if the cell is set.
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    /// Returns the value mutably if the cell is set.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.get_mut()
+    }
+
+    /// Returns whether the cell is set.
+    pub fn initialized(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// Sets the cell to `value` if it is empty and not being initialized.
+    pub fn set(&self, value: T) -> Result<(), SetError<T>> {
+        if self.initialized() {
+            return Err(SetError::AlreadyInitialized(value));
+        }
+
+        let Ok(_permit) = self.init.try_acquire() else {
+            return Err(SetError::Initializing(value));
+        };
+
+        self.value.set(value).map_err(SetError::AlreadyInitialized)
+    }
+
+    /// Returns the value, running `init` to set it if the cell is empty.
+    pub async fn get_or_init<F, Fut>(&self, init: F) -> &T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        match self
+            .get_or_try_init(|| async { Ok::<T, std::convert::Infallible>(init().await) })
+            .await
+        {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns the value, running `init` to set it if the cell is empty.
+    ///
+    /// An error of `init` leaves the cell empty.
+    pub async fn get_or_try_init<E, F, Fut>(&self, init: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+
+        // The semaphore is never closed.
+        let _permit = self.init.acquire().await.ok();
+
+        // Set by the initializer we waited for.
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+
+        let value = init().await?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Takes the value out, leaving the cell empty.
+    pub fn take(&mut self) -> Option<T> {
+        self.value.take()
+    }
+
+    /// Returns the value if the cell is set.
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> OnceCell<T> {
+        OnceCell::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnceCell")
+            .field("value", &self.get())
+            .finish()
+    }
+}
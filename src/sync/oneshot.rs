@@ -0,0 +1,258 @@
+//! Channel sending a single value.
+
+use crate::task::poll_budgeted;
+
+use std::future::{Future, poll_fn};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+pub mod error {
+    //! Errors of the oneshot channel.
+
+    use std::error::Error;
+    use std::fmt;
+
+    /// The sender was dropped without sending a value.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RecvError(pub(super) ());
+
+    impl fmt::Display for RecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "channel closed")
+        }
+    }
+
+    impl Error for RecvError {}
+
+    /// Error of `Receiver::try_recv`.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum TryRecvError {
+        /// No value was sent yet.
+        Empty,
+
+        /// The sender was dropped without sending a value.
+        Closed,
+    }
+
+    impl fmt::Display for TryRecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TryRecvError::Empty => write!(f, "channel empty"),
+                TryRecvError::Closed => write!(f, "channel closed"),
+            }
+        }
+    }
+
+    impl Error for TryRecvError {}
+}
+
+use error::{RecvError, TryRecvError};
+
+struct State<T> {
+    value: Option<T>,
+
+    // The sender is gone, by sending or being dropped.
+    tx_done: bool,
+
+    // The receiver was closed or dropped.
+    rx_closed: bool,
+
+    rx_waker: Option<Waker>,
+    tx_waker: Option<Waker>,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Creates a channel for sending a single value.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            value: None,
+            tx_done: false,
+            rx_closed: false,
+            rx_waker: None,
+            tx_waker: None,
+        }),
+    });
+
+    (
+        Sender {
+            shared: Some(Arc::clone(&shared)),
+        },
+        Receiver { shared },
+    )
+}
+
+/// Sends the value, consumed by `send`.
+pub struct Sender<T> {
+    // Taken on send.
+    shared: Option<Arc<Shared<T>>>,
+}
+
+/// Receives the value by being awaited.
+///
+/// Resolves to an error if the sender was dropped without sending.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends `value`, handing it back if the receiver is closed.
+    pub fn send(mut self, value: T) -> Result<(), T> {
+        let shared = self.shared.take().expect("sender used after send");
+        let mut state = shared.lock();
+
+        if state.rx_closed {
+            state.tx_done = true;
+            return Err(value);
+        }
+
+        state.value = Some(value);
+        state.tx_done = true;
+        let waker = state.rx_waker.take();
+        drop(state);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the receiver was closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared
+            .as_ref()
+            .is_none_or(|shared| shared.lock().rx_closed)
+    }
+
+    /// Waits for the receiver to be closed or dropped.
+    pub async fn closed(&mut self) {
+        poll_fn(|cx| self.poll_closed(cx)).await
+    }
+
+    /// Polls for the receiver to be closed or dropped.
+    pub fn poll_closed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let Some(shared) = self.shared.as_ref() else {
+            return Poll::Ready(());
+        };
+
+        let mut state = shared.lock();
+        if state.rx_closed {
+            return Poll::Ready(());
+        }
+
+        state.tx_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let Some(shared) = self.shared.take() else {
+            return;
+        };
+
+        let mut state = shared.lock();
+        state.tx_done = true;
+        let waker = state.rx_waker.take();
+        drop(state);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Takes the value if it was sent, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.lock();
+
+        match state.value.take() {
+            Some(value) => Ok(value),
+            None if state.tx_done => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Closes the channel, making later sends fail.
+    ///
+    /// A value sent before can still be received.
+    pub fn close(&mut self) {
+        let mut state = self.shared.lock();
+        state.rx_closed = true;
+        let waker = state.tx_waker.take();
+        drop(state);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_budgeted(cx, |cx| {
+            let mut state = self.shared.lock();
+
+            if let Some(value) = state.value.take() {
+                return Poll::Ready(Ok(value));
+            }
+
+            if state.tx_done {
+                return Poll::Ready(Err(RecvError(())));
+            }
+
+            state.rx_waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::thread;
+
+    #[test]
+    fn send_and_receive() {
+        let (tx, rx) = channel();
+        let sender = thread::spawn(move || tx.send(7).unwrap());
+
+        assert_eq!(block_on(rx).unwrap(), 7);
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn dropped_ends() {
+        let (tx, mut rx) = channel::<u8>();
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        drop(tx);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+
+        let (mut tx, rx) = channel();
+        assert!(!tx.is_closed());
+        drop(rx);
+        block_on(tx.closed());
+        assert_eq!(tx.send(1), Err(1));
+    }
+}
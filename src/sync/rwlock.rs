@@ -0,0 +1,209 @@
+use super::TryLockError;
+use super::batch::Semaphore;
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Most readers holding the lock at once, a writer takes all of their permits.
+const MAX_READS: usize = u32::MAX as usize >> 3;
+
+/// Reader-writer lock whose guards can be held across `.await`.
+///
+/// Readers and writers get the lock in the order they started waiting for it,
+/// so a waiting writer holds back the readers arriving after it.
+pub struct RwLock<T: ?Sized> {
+    semaphore: Semaphore,
+    data: UnsafeCell<T>,
+}
+
+// Safety: the semaphore hands out either shared access to many readers
+// or exclusive access to one writer.
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+/// Shared access to a `RwLock`, released when dropped.
+#[must_use = "the lock is released right away if unused"]
+pub struct RwLockReadGuard<'l, T: ?Sized> {
+    lock: &'l RwLock<T>,
+}
+
+/// Exclusive access to a `RwLock`, released when dropped.
+#[must_use = "the lock is released right away if unused"]
+pub struct RwLockWriteGuard<'l, T: ?Sized> {
+    lock: &'l RwLock<T>,
+}
+
+/// Shared access to an `Arc<RwLock>`, keeping the lock alive.
+#[must_use = "the lock is released right away if unused"]
+pub struct OwnedRwLockReadGuard<T: ?Sized> {
+    lock: Arc<RwLock<T>>,
+}
+
+/// Exclusive access to an `Arc<RwLock>`, keeping the lock alive.
+#[must_use = "the lock is released right away if unused"]
+pub struct OwnedRwLockWriteGuard<T: ?Sized> {
+    lock: Arc<RwLock<T>>,
+}
+
+// Safety: read guards only give out `&T`, write guards give out `&T` when shared.
+unsafe impl<T: ?Sized + Sync> Send for RwLockReadGuard<'_, T> {}
+unsafe impl<T: ?Sized + Sync> Sync for RwLockReadGuard<'_, T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLockWriteGuard<'_, T> {}
+unsafe impl<T: ?Sized + Send + Sync> Send for OwnedRwLockReadGuard<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for OwnedRwLockReadGuard<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for OwnedRwLockWriteGuard<T> {}
+
+impl<T> RwLock<T> {
+    /// Creates an unlocked lock.
+    pub const fn new(data: T) -> RwLock<T> {
+        RwLock {
+            semaphore: Semaphore::new(MAX_READS),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the lock, returning the data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Waits for shared access.
+    ///
+    /// Cancel safe: dropping the future gives up its place in the queue.
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        // The semaphore is never closed.
+        let _ = self.semaphore.acquire(1).await;
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Waits for exclusive access.
+    ///
+    /// Cancel safe: dropping the future gives up its place in the queue.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let _ = self.semaphore.acquire(MAX_READS).await;
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Takes shared access if no writer holds or waits for the lock.
+    pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, TryLockError> {
+        match self.semaphore.try_acquire(1) {
+            Ok(()) => Ok(RwLockReadGuard { lock: self }),
+            Err(_) => Err(TryLockError(())),
+        }
+    }
+
+    /// Takes exclusive access if the lock is free and nobody waits for it.
+    pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T>, TryLockError> {
+        match self.semaphore.try_acquire(MAX_READS) {
+            Ok(()) => Ok(RwLockWriteGuard { lock: self }),
+            Err(_) => Err(TryLockError(())),
+        }
+    }
+
+    /// Waits for shared access, returning a guard which keeps the lock alive.
+    pub async fn read_owned(self: Arc<Self>) -> OwnedRwLockReadGuard<T> {
+        let _ = self.semaphore.acquire(1).await;
+        OwnedRwLockReadGuard { lock: self }
+    }
+
+    /// Waits for exclusive access, returning a guard which keeps the lock alive.
+    pub async fn write_owned(self: Arc<Self>) -> OwnedRwLockWriteGuard<T> {
+        let _ = self.semaphore.acquire(MAX_READS).await;
+        OwnedRwLockWriteGuard { lock: self }
+    }
+
+    /// Takes shared access if possible, returning a guard which keeps the lock alive.
+    pub fn try_read_owned(self: Arc<Self>) -> Result<OwnedRwLockReadGuard<T>, TryLockError> {
+        match self.semaphore.try_acquire(1) {
+            Ok(()) => Ok(OwnedRwLockReadGuard { lock: self }),
+            Err(_) => Err(TryLockError(())),
+        }
+    }
+
+    /// Takes exclusive access if possible, returning a guard which keeps the lock alive.
+    pub fn try_write_owned(self: Arc<Self>) -> Result<OwnedRwLockWriteGuard<T>, TryLockError> {
+        match self.semaphore.try_acquire(MAX_READS) {
+            Ok(()) => Ok(OwnedRwLockWriteGuard { lock: self }),
+            Err(_) => Err(TryLockError(())),
+        }
+    }
+
+    /// Returns a mutable reference to the data, no locking needed.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> RwLock<T> {
+        RwLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(data: T) -> RwLock<T> {
+        RwLock::new(data)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(_) => d.field("data", &format_args!("<locked>")),
+        };
+
+        d.finish()
+    }
+}
+
+macro_rules! guard_impls {
+    ($permits: expr, $([$($gen: tt)*] $ty: ty),*) => {$(
+        impl<$($gen)*> Deref for $ty {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                // Safety: no writer holds the lock while a guard exists.
+                unsafe { &*self.lock.data.get() }
+            }
+        }
+
+        impl<$($gen)*> Drop for $ty {
+            fn drop(&mut self) {
+                self.lock.semaphore.release($permits);
+            }
+        }
+
+        impl<$($gen)*> fmt::Debug for $ty where T: fmt::Debug {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&**self, f)
+            }
+        }
+    )*};
+}
+
+guard_impls!(1, ['l, T: ?Sized] RwLockReadGuard<'l, T>, [T: ?Sized] OwnedRwLockReadGuard<T>);
+guard_impls!(
+    MAX_READS,
+    ['l, T: ?Sized] RwLockWriteGuard<'l, T>,
+    [T: ?Sized] OwnedRwLockWriteGuard<T>
+);
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the guard holds every permit.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for OwnedRwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the guard holds every permit.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
@@ -0,0 +1,211 @@
+use super::batch;
+use super::{AcquireError, TryAcquireError};
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Counting semaphore handing out permits asynchronously.
+///
+/// Waiters are served in the order they started waiting,
+/// a waiter asking for many permits holds back the ones queued after it.
+pub struct Semaphore {
+    inner: batch::Semaphore,
+}
+
+/// Permit borrowed from a `Semaphore`, returned when dropped.
+#[must_use = "the permit is returned right away if unused"]
+pub struct SemaphorePermit<'s> {
+    semaphore: &'s Semaphore,
+    permits: usize,
+}
+
+/// Permit owned by an `Arc<Semaphore>`, returned when dropped.
+#[must_use = "the permit is returned right away if unused"]
+pub struct OwnedSemaphorePermit {
+    semaphore: Arc<Semaphore>,
+    permits: usize,
+}
+
+impl Semaphore {
+    /// Most permits a semaphore can hold.
+    pub const MAX_PERMITS: usize = batch::Semaphore::MAX_PERMITS;
+
+    /// Creates a semaphore with `permits` permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` exceeds `MAX_PERMITS`.
+    pub const fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            inner: batch::Semaphore::new(permits),
+        }
+    }
+
+    /// Returns the amount of permits not handed out.
+    pub fn available_permits(&self) -> usize {
+        self.inner.available_permits()
+    }
+
+    /// Adds `n` permits, waking the waiters they satisfy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total exceeds `MAX_PERMITS`.
+    pub fn add_permits(&self, n: usize) {
+        self.inner.release(n)
+    }
+
+    /// Waits for a permit.
+    ///
+    /// Cancel safe: dropping the future gives up its place in the queue.
+    /// Fails if the semaphore is closed.
+    pub async fn acquire(&self) -> Result<SemaphorePermit<'_>, AcquireError> {
+        self.acquire_many(1).await
+    }
+
+    /// Waits for `n` permits, which are granted all at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds `MAX_PERMITS`.
+    pub async fn acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_>, AcquireError> {
+        self.inner.acquire(n).await?;
+
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Takes a permit if one is available and nobody waits for one.
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_many(1)
+    }
+
+    /// Takes `n` permits if they are available and nobody waits for any.
+    pub fn try_acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.inner.try_acquire(n)?;
+
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Waits for a permit which keeps the semaphore alive.
+    pub async fn acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit, AcquireError> {
+        self.acquire_many_owned(1).await
+    }
+
+    /// Waits for `n` permits which keep the semaphore alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds `MAX_PERMITS`.
+    pub async fn acquire_many_owned(
+        self: Arc<Self>,
+        n: usize,
+    ) -> Result<OwnedSemaphorePermit, AcquireError> {
+        self.inner.acquire(n).await?;
+
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Takes a permit which keeps the semaphore alive, if one is available.
+    pub fn try_acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.try_acquire_many_owned(1)
+    }
+
+    /// Takes `n` permits which keep the semaphore alive, if they are available.
+    pub fn try_acquire_many_owned(
+        self: Arc<Self>,
+        n: usize,
+    ) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.inner.try_acquire(n)?;
+
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Closes the semaphore, failing every pending and future acquisition.
+    ///
+    /// Permits already handed out stay valid.
+    pub fn close(&self) {
+        self.inner.close()
+    }
+
+    /// Returns whether `close` was called.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.available_permits())
+            .finish()
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Drops the permit without returning it to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// Returns the amount of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl OwnedSemaphorePermit {
+    /// Drops the permit without returning it to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// Returns the amount of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Returns the semaphore the permit belongs to.
+    pub fn semaphore(&self) -> &Arc<Semaphore> {
+        &self.semaphore
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(self.permits);
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(self.permits);
+    }
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+impl fmt::Debug for OwnedSemaphorePermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedSemaphorePermit")
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
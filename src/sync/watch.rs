@@ -0,0 +1,413 @@
+//! Single-producer, multi-consumer channel holding only the latest value.
+//!
+//! Receivers can read the current value at any time,
+//! and wait for it to change with `changed().await`.
+
+use crate::task::poll_budgeted;
+
+use futures::Stream;
+use slab::Slab;
+
+use std::fmt;
+use std::future::poll_fn;
+use std::mem;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::task::{Context, Poll, Waker};
+
+pub mod error {
+    //! Errors of the watch channel.
+
+    use std::error::Error;
+    use std::fmt;
+
+    /// Every receiver was dropped, the value is handed back.
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SendError<T>(pub T);
+
+    impl<T> fmt::Debug for SendError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SendError").finish_non_exhaustive()
+        }
+    }
+
+    impl<T> fmt::Display for SendError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "channel closed")
+        }
+    }
+
+    impl<T> Error for SendError<T> {}
+
+    /// The sender was dropped.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct RecvError(pub(super) ());
+
+    impl fmt::Display for RecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "channel closed")
+        }
+    }
+
+    impl Error for RecvError {}
+}
+
+use error::{RecvError, SendError};
+
+struct State {
+    // Bumped on every send, while holding the value's write lock.
+    version: u64,
+    tx_dropped: bool,
+    receivers: usize,
+
+    // One slot per receiver, holding its waker while it waits.
+    wakers: Slab<Option<Waker>>,
+}
+
+struct Shared<T> {
+    value: RwLock<T>,
+    state: Mutex<State>,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn new_receiver(self: &Arc<Self>, version: u64) -> Receiver<T> {
+        let mut state = self.lock();
+        state.receivers += 1;
+
+        Receiver {
+            shared: Arc::clone(self),
+            seen: version,
+            key: state.wakers.insert(None),
+        }
+    }
+
+    fn wake_all(&self) {
+        let wakers: Vec<Waker> = {
+            let mut state = self.lock();
+            state
+                .wakers
+                .iter_mut()
+                .filter_map(|(_, w)| w.take())
+                .collect()
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// Creates a channel holding `init`.
+///
+/// The initial value counts as seen by the receiver.
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        value: RwLock::new(init),
+        state: Mutex::new(State {
+            version: 0,
+            tx_dropped: false,
+            receivers: 0,
+            wakers: Slab::new(),
+        }),
+    });
+
+    let rx = shared.new_receiver(0);
+    (Sender { shared }, rx)
+}
+
+/// Sending half of a watch channel.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receiving half of a watch channel, cloned for every consumer.
+///
+/// Also a `Stream` yielding the value after each change, for `T: Clone`.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+
+    // Version of the last value marked as seen.
+    seen: u64,
+
+    // Slot in `State::wakers`.
+    key: usize,
+}
+
+/// Borrow of the value in the channel.
+///
+/// Holds a read lock, sending blocks until it is dropped.
+pub struct Ref<'a, T> {
+    guard: RwLockReadGuard<'a, T>,
+    has_changed: bool,
+}
+
+impl<T> Ref<'_, T> {
+    /// Returns whether the value was unseen by the receiver when borrowed.
+    pub fn has_changed(&self) -> bool {
+        self.has_changed
+    }
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Ref<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Sender<T> {
+    /// Replaces the value and notifies the receivers.
+    ///
+    /// Fails if there are no receivers, handing `value` back.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.shared.lock().receivers == 0 {
+            return Err(SendError(value));
+        }
+
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replaces the value and notifies the receivers, even if there are none.
+    ///
+    /// Returns the previous value.
+    pub fn send_replace(&self, value: T) -> T {
+        let mut value = value;
+        self.send_modify(|old| mem::swap(old, &mut value));
+        value
+    }
+
+    /// Modifies the value in place and notifies the receivers, even if there are none.
+    pub fn send_modify<F: FnOnce(&mut T)>(&self, modify: F) {
+        {
+            let mut value = self.shared.value.write().unwrap_or_else(|e| e.into_inner());
+            modify(&mut value);
+            self.shared.lock().version += 1;
+        }
+
+        self.shared.wake_all();
+    }
+
+    /// Borrows the current value.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.shared.read(),
+            has_changed: false,
+        }
+    }
+
+    /// Creates a receiver which has seen the current value.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let guard = self.shared.read();
+        let version = self.shared.lock().version;
+        drop(guard);
+
+        self.shared.new_receiver(version)
+    }
+
+    /// Returns the amount of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.lock().receivers
+    }
+
+    /// Returns whether every receiver was dropped.
+    pub fn is_closed(&self) -> bool {
+        self.receiver_count() == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.lock().tx_dropped = true;
+        self.shared.wake_all();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Borrows the current value, without marking it as seen.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        let guard = self.shared.read();
+        let version = self.shared.lock().version;
+
+        Ref {
+            guard,
+            has_changed: version != self.seen,
+        }
+    }
+
+    /// Borrows the current value and marks it as seen.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        let guard = self.shared.read();
+        let version = self.shared.lock().version;
+        let has_changed = version != self.seen;
+        self.seen = version;
+
+        Ref { guard, has_changed }
+    }
+
+    /// Returns whether the value changed since it was last seen.
+    ///
+    /// Fails if the sender was dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.shared.lock();
+        if state.tx_dropped {
+            return Err(RecvError(()));
+        }
+
+        Ok(state.version != self.seen)
+    }
+
+    /// Waits for the value to change, marking it as seen.
+    ///
+    /// Fails once the sender was dropped, unless a last change was left unseen.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        poll_fn(|cx| self.poll_changed(cx)).await
+    }
+
+    /// Polls for a change of the value.
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RecvError>> {
+        poll_budgeted(cx, |cx| {
+            let mut state = self.shared.lock();
+
+            if state.version != self.seen {
+                self.seen = state.version;
+                return Poll::Ready(Ok(()));
+            }
+
+            if state.tx_dropped {
+                return Poll::Ready(Err(RecvError(())));
+            }
+
+            state.wakers[self.key] = Some(cx.waker().clone());
+            Poll::Pending
+        })
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.shared.new_receiver(self.seen)
+    }
+}
+
+impl<T: Clone> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+
+        match this.poll_changed(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(Some(this.borrow_and_update().clone())),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receivers -= 1;
+        state.wakers.remove(self.key);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use futures::executor::block_on;
+    use std::thread;
+
+    #[test]
+    fn changes_are_seen_once() {
+        let (tx, mut rx) = channel(1);
+        assert!(!rx.has_changed().unwrap());
+
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+
+        let other = rx.clone();
+        assert!(other.borrow().has_changed());
+
+        block_on(async {
+            // Both changes are seen as one.
+            rx.changed().await.unwrap();
+            assert_eq!(*rx.borrow(), 3);
+            assert!(!rx.has_changed().unwrap());
+        });
+
+        drop(tx);
+        assert!(rx.has_changed().is_err());
+        assert!(block_on(rx.changed()).is_err());
+    }
+
+    #[test]
+    fn changed_wakes_across_threads() {
+        let (tx, mut rx) = channel(0);
+
+        let handle = thread::spawn(move || {
+            for i in 1..=3 {
+                tx.send_modify(|v| *v += i);
+            }
+        });
+
+        let last = block_on(async {
+            let mut last = 0;
+            while rx.changed().await.is_ok() {
+                last = *rx.borrow_and_update();
+            }
+            last
+        });
+
+        handle.join().unwrap();
+        assert_eq!(last, 6);
+    }
+
+    #[test]
+    fn stream_and_close() {
+        let (tx, rx) = channel("a");
+        assert_eq!(tx.send_replace("b"), "a");
+
+        drop(tx);
+        let values: Vec<_> = block_on(rx.collect());
+        assert_eq!(values, vec!["b"]);
+
+        let (tx, rx) = channel(());
+        drop(rx);
+        assert!(tx.is_closed());
+        assert!(tx.send(()).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use super::handle::TaskHandle;
+use crate::runtime::{Executor, Handle};
+
+use std::future::Future;
+
+/// Configures a task before spawning it.
+///
+/// # Examples
+///
+/// ```ignore
+/// let handle = lamp::task::Builder::new()
+///     .name("conn-42")
+///     .spawn(serve(stream));
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Builder<'a> {
+    name: Option<&'a str>,
+}
+
+impl<'a> Builder<'a> {
+    /// Creates a builder for an unnamed task.
+    pub fn new() -> Builder<'a> {
+        Builder { name: None }
+    }
+
+    /// Names the task, the name shows up in [`Handle::dump`].
+    pub fn name(self, name: &'a str) -> Builder<'a> {
+        Builder { name: Some(name) }
+    }
+
+    /// Spawns a future onto the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics outside of a runtime's context, see [`Executor::get`].
+    #[track_caller]
+    pub fn spawn<F>(self, f: F) -> TaskHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        Executor::get().spawn_named(f, self.name)
+    }
+
+    /// Spawns a future onto the runtime of `handle`.
+    #[track_caller]
+    pub fn spawn_on<F>(self, f: F, handle: &Handle) -> TaskHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        handle.inner().spawn_named(f, self.name)
+    }
+}
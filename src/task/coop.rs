@@ -0,0 +1,269 @@
+// Cooperative scheduling budget.
+//
+// Every poll of a task gets a budget, consumed by lamp's I/O futures and sync
+// primitives. Once it runs out they wake the task and return `Pending`, so that a task
+// whose resources are always ready still lets the other tasks on its worker run.
+use pin_project_lite::pin_project;
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Units given to every poll of a task.
+const BUDGET: u8 = 128;
+
+thread_local! {
+    // `None` outside of a task, or inside of `unconstrained`.
+    static CURRENT: Cell<Option<u8>> = const { Cell::new(None) };
+}
+
+/// Runs `f` with a fresh budget, restoring the previous one afterwards.
+pub(crate) fn budget<R>(f: impl FnOnce() -> R) -> R {
+    with_budget(Some(BUDGET), f)
+}
+
+fn with_budget<R>(budget: Option<u8>, f: impl FnOnce() -> R) -> R {
+    struct Reset(Option<u8>);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CURRENT.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _reset = Reset(CURRENT.with(|cell| cell.replace(budget)));
+
+    f()
+}
+
+/// Polls `f` if the budget isn't exhausted, consuming a unit of it when `f` is ready.
+pub(crate) fn poll_budgeted<T>(
+    cx: &mut Context<'_>,
+    f: impl FnOnce(&mut Context<'_>) -> Poll<T>,
+) -> Poll<T> {
+    let progress = match poll_proceed(cx) {
+        Poll::Ready(progress) => progress,
+        Poll::Pending => return Poll::Pending,
+    };
+
+    let output = f(cx);
+    if output.is_ready() {
+        progress.made_progress();
+    }
+
+    output
+}
+
+/// Consumes a unit of the budget.
+///
+/// Once it is exhausted, wakes the task and returns `Pending`.
+/// The unit is given back if the guard is dropped without calling `made_progress`,
+/// so operations which end up `Pending` don't count.
+fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    CURRENT.with(|cell| match cell.get() {
+        None => Poll::Ready(RestoreOnPending(Cell::new(None))),
+
+        Some(0) => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+
+        Some(units) => {
+            cell.set(Some(units - 1));
+            Poll::Ready(RestoreOnPending(Cell::new(Some(units))))
+        }
+    })
+}
+
+/// Gives a unit of the budget back when dropped, unless progress was made.
+struct RestoreOnPending(Cell<Option<u8>>);
+
+impl RestoreOnPending {
+    fn made_progress(&self) {
+        self.0.set(None);
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        if let Some(units) = self.0.get() {
+            CURRENT.with(|cell| {
+                if cell.get().is_some() {
+                    cell.set(Some(units));
+                }
+            });
+        }
+    }
+}
+
+/// Yields to the runtime, letting the other tasks run before continuing.
+///
+/// # Examples
+///
+/// ```ignore
+/// loop {
+///     crunch_numbers();
+///     lamp::task::yield_now().await;
+/// }
+/// ```
+pub async fn yield_now() {
+    let mut yielded = false;
+
+    std::future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+pin_project! {
+    /// Future returned by [`unconstrained`].
+    #[derive(Debug)]
+    pub struct Unconstrained<F> {
+        #[pin]
+        inner: F,
+    }
+}
+
+/// Opts a future out of the cooperative budget.
+///
+/// lamp's I/O futures and sync primitives make a task yield once it used up its
+/// budget for the current poll, inside of `inner` they never do.
+/// A future which is always ready can then starve the other tasks of its worker.
+pub fn unconstrained<F: Future>(inner: F) -> Unconstrained<F> {
+    Unconstrained { inner }
+}
+
+impl<F: Future> Future for Unconstrained<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let inner = self.project().inner;
+
+        with_budget(None, || inner.poll(cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BUDGET, budget, poll_budgeted, poll_proceed, unconstrained};
+
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Count(AtomicUsize);
+
+    impl Wake for Count {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn budget_runs_out() {
+        let count = Arc::new(Count(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&count));
+        let mut cx = Context::from_waker(&waker);
+
+        budget(|| {
+            for _ in 0..BUDGET {
+                match poll_proceed(&mut cx) {
+                    Poll::Ready(progress) => progress.made_progress(),
+                    Poll::Pending => panic!("budget ran out early"),
+                }
+            }
+
+            assert!(poll_proceed(&mut cx).is_pending());
+        });
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+
+        // A new poll gets a new budget.
+        budget(|| assert!(poll_proceed(&mut cx).is_ready()));
+    }
+
+    #[test]
+    fn pending_polls_are_free() {
+        let mut cx = Context::from_waker(Waker::noop());
+
+        budget(|| {
+            for _ in 0..BUDGET as usize * 2 {
+                assert!(poll_budgeted(&mut cx, |_| Poll::<()>::Pending).is_pending());
+            }
+
+            assert!(poll_budgeted(&mut cx, |_| Poll::Ready(())).is_ready());
+        });
+    }
+
+    #[test]
+    fn unconstrained_has_no_budget() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut fut = pin!(unconstrained(std::future::poll_fn(|cx| {
+            for _ in 0..BUDGET as usize * 2 {
+                match poll_proceed(cx) {
+                    Poll::Ready(progress) => progress.made_progress(),
+                    Poll::Pending => return Poll::Ready(false),
+                }
+            }
+
+            Poll::Ready(true)
+        })));
+
+        budget(|| assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(true)));
+    }
+
+    // Without yielding, the busy task would keep the only worker forever.
+    #[cfg(not(miri))]
+    fn run_until_stopped<F>(busy: impl FnOnce(Arc<AtomicBool>) -> F + Send + 'static)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        use crate::Executor;
+
+        let mut exec = Executor::new(1);
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let _ = exec.block_on(async move {
+            let stop = Arc::new(AtomicBool::new(false));
+            let busy = Executor::spawn(busy(Arc::clone(&stop)));
+            Executor::spawn(async move { stop.store(true, Ordering::SeqCst) }).await;
+
+            busy.await;
+            tx.send(()).unwrap();
+        });
+
+        rx.recv().unwrap();
+        exec.shutdown();
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn ready_channel_yields() {
+        run_until_stopped(|stop| async move {
+            let (tx, mut rx) = crate::sync::mpsc::unbounded_channel();
+
+            while !stop.load(Ordering::SeqCst) {
+                tx.send(()).unwrap();
+                rx.recv().await;
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn yield_now_yields() {
+        run_until_stopped(|stop| async move {
+            while !stop.load(Ordering::SeqCst) {
+                super::yield_now().await;
+            }
+        });
+    }
+}
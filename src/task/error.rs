@@ -0,0 +1,89 @@
+use std::any::Any;
+use std::fmt;
+
+enum Repr {
+    Cancelled,
+    Panic(Box<dyn Any + Send + 'static>),
+}
+
+/// Reason a task didn't produce its output.
+pub struct JoinError {
+    repr: Repr,
+}
+
+impl JoinError {
+    pub(crate) fn cancelled() -> JoinError {
+        JoinError {
+            repr: Repr::Cancelled,
+        }
+    }
+
+    pub(crate) fn panic(payload: Box<dyn Any + Send + 'static>) -> JoinError {
+        JoinError {
+            repr: Repr::Panic(payload),
+        }
+    }
+
+    /// Whether the task was aborted, or cancelled by the runtime's shutdown.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.repr, Repr::Cancelled)
+    }
+
+    /// Whether the task panicked.
+    pub fn is_panic(&self) -> bool {
+        matches!(self.repr, Repr::Panic(_))
+    }
+
+    /// Obtains the panic payload, to resume it with [`std::panic::resume_unwind`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task was cancelled instead.
+    pub fn into_panic(self) -> Box<dyn Any + Send + 'static> {
+        match self.try_into_panic() {
+            Ok(payload) => payload,
+            Err(_) => panic!("task was cancelled, it didn't panic"),
+        }
+    }
+
+    /// Obtains the panic payload, or gives the error back if the task was cancelled.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, JoinError> {
+        match self.repr {
+            Repr::Panic(payload) => Ok(payload),
+            repr => Err(JoinError { repr }),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => write!(f, "task was cancelled"),
+            Repr::Panic(payload) => match panic_message(payload.as_ref()) {
+                Some(msg) => write!(f, "task panicked with message {msg:?}"),
+                None => write!(f, "task panicked"),
+            },
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => write!(f, "JoinError::Cancelled"),
+            Repr::Panic(payload) => match panic_message(payload.as_ref()) {
+                Some(msg) => write!(f, "JoinError::Panic({msg:?}, ...)"),
+                None => write!(f, "JoinError::Panic(...)"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    match payload.downcast_ref::<&'static str>() {
+        Some(msg) => Some(msg),
+        None => payload.downcast_ref::<String>().map(String::as_str),
+    }
+}
@@ -1,11 +1,22 @@
+use super::error::JoinError;
+use super::id::Id;
 use super::task::RawTask;
 use super::waker::make_waker;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::panic;
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, Waker};
 
-use log::warn;
+/// Handle to a spawned task, resolving to its output.
+///
+/// Dropping the handle detaches the task, it keeps running.
+///
+/// # Panics
+///
+/// Awaiting it resumes the task's panic if it panicked,
+/// and panics if the task was aborted or cancelled by the runtime's shutdown.
+/// Use a [`JoinSet`](super::JoinSet) to get a [`JoinError`] instead.
 pub struct TaskHandle<T> {
     raw: RawTask,
     _t: PhantomData<T>,
@@ -13,7 +24,6 @@ pub struct TaskHandle<T> {
 
 impl<T> TaskHandle<T> {
     pub(crate) fn new(raw: RawTask) -> TaskHandle<T> {
-        warn!("Incremented in TaskHandle::new");
         raw.ref_inc();
         TaskHandle {
             raw,
@@ -23,6 +33,50 @@ impl<T> TaskHandle<T> {
 }
 
 impl<T> TaskHandle<T> {
+    /// Obtains the task's id.
+    pub fn id(&self) -> Id {
+        self.raw.header().meta.id
+    }
+
+    /// Aborts the task.
+    ///
+    /// Its future is dropped right away, or once its current poll ends
+    /// if a worker is polling it. Does nothing if the task already completed.
+    pub fn abort(&self) {
+        self.raw.cancel();
+
+        // Lets the runtime notice it completed and release it.
+        self.raw.send_note();
+    }
+
+    /// Whether the task completed, successfully or not.
+    ///
+    /// Once it did, awaiting the handle doesn't block.
+    pub fn is_finished(&self) -> bool {
+        self.raw.is_complete()
+    }
+
+    /// Polls for the task's output, registering `waker` for when it completes.
+    pub(crate) fn poll_join(&self, waker: &Waker) -> Poll<Result<T, JoinError>> {
+        let mut out = Poll::Pending;
+
+        self.raw.review(&mut out as *mut _ as *const (), waker);
+
+        out
+    }
+
+    /// Registers `waker` to be woken once the task completes.
+    pub(crate) fn register(&self, waker: &Waker) {
+        self.raw.set_handle_waker(waker);
+    }
+
+    /// Turns the handle into a waker scheduling the task itself.
+    ///
+    /// # Safety
+    ///
+    /// Waking it polls the task's future on top of the polls its own wakers trigger,
+    /// the caller must make sure the future copes with them. Its output becomes
+    /// unreachable.
     pub unsafe fn expose_waker(self) -> std::task::Waker {
         make_waker(self.raw.get_ptr())
     }
@@ -32,15 +86,12 @@ impl<T> Future for TaskHandle<T> {
     type Output = T;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
-        let mut out = Poll::Pending;
-
-        self.raw.review(&mut out as *mut _ as *const (), cx.waker());
-
-        // DO NOT REMOVE
-        // THIS WEIRDLY CAUSES A DATA RACE NOT TO HAPPEN
-        // WILL BE FIXED
-        //dbg!(&out);
-        out
+        match self.poll_join(cx.waker()) {
+            Poll::Ready(Ok(output)) => Poll::Ready(output),
+            Poll::Ready(Err(err)) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+            Poll::Ready(Err(err)) => panic!("{err}"),
+            Poll::Pending => Poll::Pending,
+        }
     }
 }
 
@@ -0,0 +1,94 @@
+use std::cell::Cell;
+use std::fmt;
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identifier of a task, unique within the process.
+///
+/// Unlike the key a runtime stores a task under, it is never reused.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Id(NonZeroU64);
+
+impl Id {
+    pub(crate) fn next() -> Id {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+
+        let id = NEXT.fetch_add(1, Ordering::Relaxed);
+        Id(NonZeroU64::new(id).expect("task ids ran out"))
+    }
+
+    /// The id as a number.
+    pub fn as_u64(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Debug for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Id({})", self.0)
+    }
+}
+
+thread_local! {
+    // Task being polled on this thread.
+    static CURRENT: Cell<Option<Id>> = const { Cell::new(None) };
+}
+
+/// Obtains the id of the task being polled.
+///
+/// # Panics
+///
+/// Panics outside of a task, see [`try_id`].
+pub fn id() -> Id {
+    try_id().expect("`task::id` called outside of a task")
+}
+
+/// Obtains the id of the task being polled, if there is one.
+pub fn try_id() -> Option<Id> {
+    CURRENT.with(Cell::get)
+}
+
+/// Runs `f` as the task `id`, restoring the previous one afterwards.
+pub(crate) fn enter<R>(id: Id, f: impl FnOnce() -> R) -> R {
+    struct Reset(Option<Id>);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CURRENT.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _reset = Reset(CURRENT.with(|cell| cell.replace(Some(id))));
+
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Id, enter, id, try_id};
+
+    #[test]
+    fn ids_are_not_reused() {
+        let (a, b) = (Id::next(), Id::next());
+        assert!(a < b);
+    }
+
+    #[test]
+    fn current_id_is_restored() {
+        let (outer, inner) = (Id::next(), Id::next());
+        assert_eq!(try_id(), None);
+
+        enter(outer, || {
+            enter(inner, || assert_eq!(id(), inner));
+            assert_eq!(id(), outer);
+        });
+
+        assert_eq!(try_id(), None);
+    }
+}
@@ -0,0 +1,334 @@
+use super::error::JoinError;
+use super::handle::TaskHandle;
+use crate::runtime::Executor;
+
+use slab::Slab;
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+use std::future::{Future, poll_fn};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+/// Collection of tasks, joined in the order they complete.
+///
+/// Dropping the set aborts every task still in it.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut set = JoinSet::new();
+///
+/// for i in 0..10 {
+///     set.spawn(async move { i });
+/// }
+///
+/// while let Some(res) = set.join_next().await {
+///     println!("{}", res.unwrap());
+/// }
+/// ```
+pub struct JoinSet<T> {
+    // Each task's handle, with the waker its completion wakes.
+    tasks: Slab<(TaskHandle<T>, Waker)>,
+
+    ready: Arc<Mutex<Ready>>,
+}
+
+// Tasks worth polling, woken by their completion.
+struct Ready {
+    keys: VecDeque<usize>,
+
+    // Waker of the task joining the set.
+    waker: Option<Waker>,
+}
+
+// Waker of a single task of the set.
+struct Entry {
+    key: usize,
+    ready: Arc<Mutex<Ready>>,
+}
+
+impl Wake for Entry {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let mut ready = self.ready.lock().unwrap_or_else(|e| e.into_inner());
+        ready.keys.push_back(self.key);
+
+        let waker = ready.waker.take();
+        drop(ready);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> JoinSet<T> {
+    /// Creates an empty set.
+    pub fn new() -> JoinSet<T> {
+        JoinSet {
+            tasks: Slab::new(),
+            ready: Arc::new(Mutex::new(Ready {
+                keys: VecDeque::new(),
+                waker: None,
+            })),
+        }
+    }
+
+    /// Amount of tasks in the set, including the completed ones not joined yet.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Whether the set has no tasks.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Spawns a future onto the current runtime, adding its task to the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics outside of a runtime's context, see [`Executor::get`].
+    #[track_caller]
+    pub fn spawn<F>(&mut self, f: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let handle = Executor::get().spawn(f);
+
+        let entry = self.tasks.vacant_entry();
+        let waker = Waker::from(Arc::new(Entry {
+            key: entry.key(),
+            ready: Arc::clone(&self.ready),
+        }));
+
+        // Completing wakes it, unless it completed before it was registered.
+        handle.register(&waker);
+        if handle.is_finished() {
+            waker.wake_by_ref();
+        }
+
+        entry.insert((handle, waker));
+    }
+
+    /// Waits for one of the tasks to complete, removing it from the set.
+    ///
+    /// Returns `None` once the set is empty. Aborted tasks resolve to a
+    /// [`JoinError`] which [is cancelled](JoinError::is_cancelled), tasks which
+    /// panicked to one which [is a panic](JoinError::is_panic).
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        poll_fn(|cx| self.poll_join_next(cx)).await
+    }
+
+    /// Polls for the next completed task, see [`JoinSet::join_next`].
+    ///
+    /// Only the tasks which completed since the last call are checked,
+    /// the others wake `cx` once they do.
+    pub fn poll_join_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, JoinError>>> {
+        if self.tasks.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        super::poll_budgeted(cx, |cx| {
+            loop {
+                // Released before polling, completing a task locks it too.
+                let mut ready = self.ready.lock().unwrap_or_else(|e| e.into_inner());
+                let key = match ready.keys.pop_front() {
+                    Some(key) => key,
+                    None => {
+                        ready.waker = Some(cx.waker().clone());
+                        return Poll::Pending;
+                    }
+                };
+                drop(ready);
+
+                // Joined or detached since it was woken,
+                // or woken twice as it completed while being spawned.
+                let Some((handle, waker)) = self.tasks.get(key) else {
+                    continue;
+                };
+
+                match handle.poll_join(waker) {
+                    Poll::Ready(output) => {
+                        self.tasks.remove(key);
+                        return Poll::Ready(Some(output));
+                    }
+
+                    Poll::Pending => continue,
+                }
+            }
+        })
+    }
+
+    /// Aborts every task of the set.
+    ///
+    /// They stay in it, and are joined with a cancellation error
+    /// unless they completed before.
+    pub fn abort_all(&mut self) {
+        for (_, (handle, _)) in self.tasks.iter() {
+            handle.abort();
+        }
+    }
+
+    /// Removes every task from the set without aborting them, they keep running.
+    pub fn detach_all(&mut self) {
+        self.tasks.clear();
+
+        let mut ready = self.ready.lock().unwrap_or_else(|e| e.into_inner());
+        ready.keys.clear();
+    }
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> JoinSet<T> {
+        JoinSet::new()
+    }
+}
+
+impl<T> Debug for JoinSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinSet")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::JoinSet;
+    use crate::Executor;
+    use crate::sync::oneshot;
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+
+    // Runs `f` as the main task, sending its output back.
+    fn run<T, F>(f: impl FnOnce() -> F) -> T
+    where
+        T: Send + 'static,
+        F: Future<Output = T> + Send + 'static,
+    {
+        let mut exec = Executor::new(1);
+        let (tx, rx) = mpsc::channel();
+        let f = f();
+
+        let _ = exec.block_on(async move { tx.send(f.await).unwrap() });
+
+        let out = rx.recv().unwrap();
+        exec.shutdown();
+        out
+    }
+
+    #[test]
+    fn joins_in_completion_order() {
+        let order = run(|| async {
+            let (tx, rx) = oneshot::channel();
+            let mut set = JoinSet::new();
+
+            set.spawn(async move { rx.await.unwrap() });
+            set.spawn(async move {
+                tx.send(1).unwrap();
+                2
+            });
+
+            let mut order = Vec::new();
+            while let Some(res) = set.join_next().await {
+                order.push(res.unwrap());
+            }
+            order
+        });
+
+        assert_eq!(order, [2, 1]);
+    }
+
+    #[test]
+    fn abort_all_cancels_tasks() {
+        let (len, cancelled) = run(|| async {
+            let mut set = JoinSet::new();
+            for _ in 0..3 {
+                set.spawn(std::future::pending::<()>());
+            }
+            set.abort_all();
+
+            let len = set.len();
+            let mut cancelled = 0;
+            while let Some(res) = set.join_next().await {
+                cancelled += res.unwrap_err().is_cancelled() as usize;
+            }
+            (len, cancelled)
+        });
+
+        assert_eq!((len, cancelled), (3, 3));
+    }
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn drop_aborts_tasks() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&dropped);
+
+        run(move || async move {
+            let (tx, rx) = oneshot::channel();
+            let mut set = JoinSet::new();
+            set.spawn(async move {
+                let _guard = SetOnDrop(flag);
+                tx.send(()).unwrap();
+                std::future::pending::<()>().await
+            });
+
+            // Dropped once it started.
+            rx.await.unwrap();
+            drop(set);
+        });
+
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn panics_become_errors() {
+        let err = run(|| async {
+            let mut set = JoinSet::<()>::new();
+            set.spawn(async { panic!("boom") });
+
+            set.join_next().await.unwrap().unwrap_err()
+        });
+
+        assert!(err.is_panic());
+        assert_eq!(err.to_string(), "task panicked with message \"boom\"");
+    }
+
+    #[test]
+    fn detach_all_keeps_tasks_running() {
+        let out = run(|| async {
+            let (tx, rx) = oneshot::channel();
+            let mut set = JoinSet::new();
+            set.spawn(async move { tx.send(7).unwrap() });
+
+            set.detach_all();
+            assert!(set.join_next().await.is_none());
+            rx.await.unwrap()
+        });
+
+        assert_eq!(out, 7);
+    }
+}
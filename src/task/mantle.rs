@@ -1,10 +1,16 @@
 // Mantle for the task.
+use super::error::JoinError;
 use super::note::Note;
-use super::task::{Core, Header};
+use super::task::{CANCELLED, COMPLETE, Core, Header, NOTIFIED, RUNNING, SCHEDULED, Scheduler};
+use crate::trace::trace_event;
+
+use log::warn;
 
 use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
 use std::ptr::NonNull;
+use std::sync::atomic::Ordering;
 use std::task::{Context, Poll, Waker};
 
 
@@ -24,37 +30,175 @@ impl<F: Future + Send + 'static> Mantle<F> {
         unsafe { self.ptr.as_ref() }
     }
 
+    // Polls the future, returns whether the task is done.
+    //
+    // If another thread is already polling it, that thread is asked
+    // to poll it once more instead.
     pub(crate) fn poll(self) -> bool {
-        let future = unsafe { Pin::new_unchecked(self.core().future()) };
+        let state = &self.core().header().state;
+
+        loop {
+            let mut current = state.load(Ordering::Acquire);
+            loop {
+                if current & COMPLETE != 0 {
+                    return true;
+                }
+
+                let next = match current & RUNNING {
+                    0 => current | RUNNING,
+                    _ => current | NOTIFIED,
+                };
+
+                match state.compare_exchange_weak(
+                    current,
+                    next & !SCHEDULED,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) if current & RUNNING != 0 => return false,
+                    Ok(_) => break,
+                    Err(actual) => current = actual,
+                }
+            }
+
+            if current & CANCELLED != 0 {
+                self.complete(Err(JoinError::cancelled()));
+                return true;
+            }
+
+            // Only completing drops it, and it isn't complete.
+            let future = unsafe { Pin::new_unchecked(self.core().future().unwrap()) };
+            let mut cx = Context::from_waker(self.core().waker().unwrap());
+
+            let header = self.core().header();
+            header.polls.fetch_add(1, Ordering::Relaxed);
+
+            let output = panic::catch_unwind(AssertUnwindSafe(|| {
+                header.meta.enter(|| super::budget(|| future.poll(&mut cx)))
+            }));
+
+            match output {
+                Ok(Poll::Ready(output)) => {
+                    self.complete(Ok(output));
+                    return true;
+                }
+
+                Err(payload) => {
+                    self.complete(Err(JoinError::panic(payload)));
+                    return true;
+                }
+
+                Ok(Poll::Pending) => (),
+            }
+
+            // Woken or cancelled while running, it has to be claimed again.
+            let previous = state.fetch_and(!(RUNNING | NOTIFIED), Ordering::AcqRel);
+            if previous & (NOTIFIED | CANCELLED) == 0 {
+                return false;
+            }
+        }
+    }
+
+    // Cancels the task, completing it right away unless it is running,
+    // in which case the poller completes it once done.
+    pub(crate) fn cancel(self) {
+        let state = &self.core().header().state;
+        let mut current = state.load(Ordering::Acquire);
+
+        loop {
+            if current & COMPLETE != 0 {
+                return;
+            }
+
+            let next = match current & RUNNING {
+                0 => current | CANCELLED | RUNNING,
+                _ => current | CANCELLED,
+            };
+
+            match state.compare_exchange_weak(current, next, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) if current & RUNNING != 0 => return,
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+
+        self.complete(Err(JoinError::cancelled()));
+    }
 
-        let mut cx = Context::from_waker(self.core().waker().unwrap());
-        let output = future.poll(&mut cx);
-        let ready = output.is_ready();
-        let field = unsafe { &mut *self.core().middle().poll.get() };
-        *field = output;
+    // Drops the future, stores the output and wakes up the handle.
+    // Must be called by the thread which set `RUNNING`.
+    fn complete(self, output: Result<F::Output, JoinError>) {
+        let core = self.core();
+
+        // Drops whatever the future borrowed before anyone can see it complete.
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| core.drop_future())) {
+            warn!(
+                "dropping a task's future panicked (id: {})",
+                core.header().meta.id
+            );
+            drop(payload);
+        }
 
-        ready
+        trace_event!(
+            parent: &core.header().meta.span,
+            outcome = match &output {
+                Ok(_) => "done",
+                Err(e) if e.is_cancelled() => "cancelled",
+                Err(_) => "panicked",
+            },
+            "task completed",
+        );
+
+        // Cancelled while its last poll returned, it completed all the same.
+        let cancelled = matches!(&output, Err(e) if e.is_cancelled());
+        unsafe { *core.middle().output.get() = Some(output) };
+
+        // Nobody polls it anymore, its own waker only kept it alive.
+        core.tail().set_waker(None);
+
+        let _ = core
+            .header()
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |state| {
+                let state = (state | COMPLETE) & !(RUNNING | NOTIFIED);
+                Some(if cancelled { state } else { state & !CANCELLED })
+            });
+
+        self.wake_handle();
+    }
+
+    // Whether the output, or the reason there is none, is available.
+    fn is_complete(&self) -> bool {
+        self.core().header().state.load(Ordering::Acquire) & COMPLETE != 0
     }
 
     pub(crate) fn destroy(self) {
         drop(unsafe { Box::from_raw(self.ptr.as_ptr()) })
     }
 
-    // Obtains the poll from the future.
+    // Takes the output once the task completed,
     // writes it into the provided pointer.
     pub(crate) fn review(self, dst: *const (), waker: &Waker) {
-        let output = self.core().poll_output();
-        let dest = unsafe { &mut *(dst as *mut Poll<F::Output>) };
+        let dest = unsafe { &mut *(dst as *mut Poll<Result<F::Output, JoinError>>) };
 
-        if output.is_pending() {
-            self.core().set_handle_waker(waker);
-        };
+        // Set first, so that completing right after the check still wakes it.
+        self.core().set_handle_waker(waker);
 
-        *dest = output;
+        if self.is_complete() {
+            match self.core().take_output() {
+                Some(output) => *dest = Poll::Ready(output),
+                None => panic!("task output taken twice"),
+            }
+        }
+    }
+
+    // Sets the waker woken once the task completes.
+    pub(crate) fn set_handle_waker(self, waker: &Waker) {
+        self.core().set_handle_waker(waker);
     }
 
     // Wakes up the handle if there is a waker present.
-    pub(crate) fn wake_handle(self) {
+    fn wake_handle(self) {
         let waker = self.core().tail().h_waker.lock().unwrap();
         if let Some(w) = waker.as_ref() {
             w.wake_by_ref();
@@ -66,10 +210,24 @@ impl<F: Future + Send + 'static> Mantle<F> {
         self.core().tail().set_waker(waker);
     }
 
-    // Sends a note to the assigned channel
+    // Sends a note to the assigned scheduler
+    // Ignored if it is gone, nobody is left to poll the task.
     pub(crate) fn send_note(self) {
         let header = self.core().header();
-
-        header.sender.send(Note(header.id)).unwrap();
+        let note = Note(header.id);
+
+        header.state.fetch_or(SCHEDULED, Ordering::AcqRel);
+        trace_event!(parent: &header.meta.span, "task woken");
+
+        match &header.scheduler {
+            Scheduler::Runtime(rt) => {
+                if let Some(rt) = rt.upgrade() {
+                    rt.schedule(note);
+                }
+            }
+            Scheduler::Channel(sender) => {
+                let _ = sender.send(note);
+            }
+        }
     }
 }
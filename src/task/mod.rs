@@ -1,6 +1,34 @@
-pub mod handle;
-pub mod mantle;
-pub mod note;
-pub mod task;
-pub mod vtable;
-pub mod waker;
+//! Asynchronous tasks and utilities for them.
+//!
+//! Tasks are spawned with [`Executor::spawn`](crate::Executor::spawn) or a runtime's
+//! [`Handle`](crate::runtime::Handle), and each poll of one gets a cooperative budget,
+//! see [`unconstrained`].
+//!
+//! With the `tracing` feature, each task gets a `task` span carrying its id and name,
+//! entered around each of its polls along with a `poll` span. It is a child of the span
+//! current when spawning, so tasks spawned from a future wrapped with
+//! `tracing::Instrument` show up below that future's span.
+
+mod builder;
+mod coop;
+mod error;
+pub(crate) mod handle;
+mod id;
+mod join_set;
+pub(crate) mod mantle;
+pub(crate) mod note;
+mod scope;
+pub(crate) mod task;
+mod task_local;
+pub(crate) mod vtable;
+pub(crate) mod waker;
+
+pub use builder::Builder;
+pub use coop::{Unconstrained, unconstrained, yield_now};
+pub(crate) use coop::{budget, poll_budgeted};
+pub use error::JoinError;
+pub use handle::TaskHandle;
+pub use id::{Id, id, try_id};
+pub use join_set::JoinSet;
+pub use scope::{Scope, ScopeFuture, scope};
+pub use task_local::{AccessError, LocalKey, TaskLocalFuture};
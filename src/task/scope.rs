@@ -0,0 +1,382 @@
+// Structured concurrency, children which may borrow from the parent.
+//
+// The children are polled by the scope future itself rather than spawned onto
+// the runtime, which is what makes borrowing sound without any `unsafe`.
+use pin_project_lite::pin_project;
+use slab::Slab;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Wake, Waker};
+
+type Child<'env> = Pin<Box<dyn Future<Output = ()> + Send + 'env>>;
+
+/// Creates a scope, in which futures borrowing from the enclosing frame can be spawned.
+///
+/// `f` gets a [`Scope`] to spawn the children with, the returned future resolves to the
+/// output of the future `f` returns, once it and every child completed.
+///
+/// The children run concurrently with each other and with the body of the scope, but on
+/// the task awaiting the scope, never in parallel. Blocking or CPU heavy work still
+/// belongs in [`Executor::spawn`](crate::Executor::spawn) or `spawn_blocking`.
+///
+/// # Panics
+///
+/// If a child panics, the others still run to completion, then the panic is resumed
+/// by the scope future. A panic of the body unwinds right away, dropping the children.
+///
+/// # Cancellation
+///
+/// The children are owned by the scope future. Dropping it drops them, before the
+/// borrows they hold end, so cancelling a scope is always sound. Leaking it, with
+/// [`mem::forget`] for instance, leaks them too: they are never polled again.
+///
+/// # Examples
+///
+/// ```ignore
+/// let names = vec!["a", "b", "c"];
+/// let count = AtomicUsize::new(0);
+///
+/// lamp::task::scope(|s| {
+///     let (names, count) = (&names, &count);
+///
+///     async move {
+///         for name in names {
+///             s.spawn(async move {
+///                 lookup(name).await;
+///                 count.fetch_add(1, Ordering::Relaxed);
+///             });
+///         }
+///     }
+/// })
+/// .await;
+///
+/// assert_eq!(count.into_inner(), names.len());
+/// ```
+pub fn scope<'env, F, Fut>(f: F) -> ScopeFuture<'env, Fut>
+where
+    F: FnOnce(Scope<'env>) -> Fut,
+    Fut: Future,
+{
+    let scope = Scope {
+        shared: Arc::new(Shared {
+            spawned: Mutex::new(Vec::new()),
+            ready: Arc::new(Mutex::new(Ready {
+                keys: VecDeque::new(),
+                waker: None,
+            })),
+        }),
+    };
+
+    ScopeFuture {
+        body: f(scope.clone()),
+        output: None,
+        scope,
+        children: Slab::new(),
+        panic: None,
+    }
+}
+
+/// Spawns the children of a [`scope`], cheap to clone.
+#[derive(Clone)]
+pub struct Scope<'env> {
+    shared: Arc<Shared<'env>>,
+}
+
+struct Shared<'env> {
+    // Spawned since the scope future was last polled.
+    spawned: Mutex<Vec<Child<'env>>>,
+
+    ready: Arc<Mutex<Ready>>,
+}
+
+// Children worth polling.
+struct Ready {
+    keys: VecDeque<usize>,
+
+    // Waker of the task awaiting the scope.
+    waker: Option<Waker>,
+}
+
+impl Ready {
+    fn wake(mut ready: MutexGuard<'_, Ready>) {
+        let waker = ready.waker.take();
+        drop(ready);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+// Waker of a single child.
+struct ChildWaker {
+    key: usize,
+    ready: Arc<Mutex<Ready>>,
+}
+
+impl Wake for ChildWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let mut ready = self.ready.lock().unwrap_or_else(|e| e.into_inner());
+        ready.keys.push_back(self.key);
+
+        Ready::wake(ready);
+    }
+}
+
+impl<'env> Scope<'env> {
+    /// Spawns a child, which may borrow anything outliving the scope.
+    ///
+    /// The scope doesn't complete before it does.
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: Future<Output = ()> + Send + 'env,
+    {
+        let mut spawned = self
+            .shared
+            .spawned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        spawned.push(Box::pin(f));
+        drop(spawned);
+
+        // Spawned from outside of the scope future's poll, it has to notice.
+        Ready::wake(self.shared.ready.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+impl Debug for Scope<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+pin_project! {
+    /// Future returned by [`scope`].
+    pub struct ScopeFuture<'env, Fut: Future> {
+        #[pin]
+        body: Fut,
+        output: Option<Fut::Output>,
+        scope: Scope<'env>,
+        children: Slab<(Child<'env>, Waker)>,
+
+        // First panic of a child, resumed once they all completed.
+        panic: Option<Box<dyn Any + Send + 'static>>,
+    }
+}
+
+impl<Fut: Future> Future for ScopeFuture<'_, Fut> {
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Fut::Output> {
+        let this = self.project();
+        let shared = &this.scope.shared;
+
+        if this.output.is_none() {
+            match this.body.poll(cx) {
+                Poll::Ready(output) => *this.output = Some(output),
+                Poll::Pending => (),
+            }
+        }
+
+        let spawned = mem::take(&mut *shared.spawned.lock().unwrap_or_else(|e| e.into_inner()));
+
+        let mut ready = shared.ready.lock().unwrap_or_else(|e| e.into_inner());
+        for child in spawned {
+            let entry = this.children.vacant_entry();
+            let waker = Waker::from(Arc::new(ChildWaker {
+                key: entry.key(),
+                ready: Arc::clone(&shared.ready),
+            }));
+
+            ready.keys.push_back(entry.key());
+            entry.insert((child, waker));
+        }
+
+        // Registered before polling the children, so that their wakes and spawns
+        // from now on poll the scope again.
+        ready.waker = Some(cx.waker().clone());
+
+        // Each child is polled once at most, one waking itself can't starve the rest.
+        let batch = mem::take(&mut ready.keys);
+        drop(ready);
+
+        for key in batch {
+            // Completed since it was woken.
+            let Some((child, waker)) = this.children.get_mut(key) else {
+                continue;
+            };
+
+            let mut cx = Context::from_waker(waker);
+            match panic::catch_unwind(AssertUnwindSafe(|| child.as_mut().poll(&mut cx))) {
+                Ok(Poll::Pending) => (),
+
+                Ok(Poll::Ready(())) => {
+                    drop(this.children.remove(key));
+                }
+
+                Err(payload) => {
+                    drop(this.children.remove(key));
+                    this.panic.get_or_insert(payload);
+                }
+            }
+        }
+
+        // The children spawned while polling woke the scope, they are not lost.
+        let spawning = !shared
+            .spawned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty();
+        if this.output.is_none() || !this.children.is_empty() || spawning {
+            return Poll::Pending;
+        }
+
+        if let Some(payload) = this.panic.take() {
+            panic::resume_unwind(payload);
+        }
+
+        Poll::Ready(this.output.take().unwrap())
+    }
+}
+
+impl<Fut: Future> Debug for ScopeFuture<'_, Fut> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopeFuture")
+            .field("children", &self.children.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scope;
+
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::task::{Context, Poll, Waker};
+
+    struct SetOnDrop<'a>(&'a AtomicBool);
+
+    impl Drop for SetOnDrop<'_> {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn dropping_the_scope_drops_children() {
+        let dropped = AtomicBool::new(false);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        {
+            let dropped = &dropped;
+            let mut fut = pin!(scope(|s| async move {
+                s.spawn(async move {
+                    let _guard = SetOnDrop(dropped);
+                    std::future::pending::<()>().await
+                });
+            }));
+
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert!(!dropped.load(Ordering::SeqCst));
+        }
+
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn children_may_spawn() {
+        let count = AtomicUsize::new(0);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let count_ref = &count;
+        let mut fut = pin!(scope(|s| async move {
+            let child = s.clone();
+            s.spawn(async move {
+                child.spawn(async move {
+                    count_ref.fetch_add(1, Ordering::SeqCst);
+                });
+                count_ref.fetch_add(1, Ordering::SeqCst);
+            });
+            "done"
+        }));
+
+        // The grandchild is adopted by the next poll.
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready("done"));
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn children_borrow_the_parent() {
+        use crate::Executor;
+
+        let mut exec = Executor::new(1);
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let _ = exec.block_on(async move {
+            let numbers: Vec<usize> = (1..=10).collect();
+            let sum = AtomicUsize::new(0);
+
+            let len = scope(|s| {
+                let (numbers, sum) = (&numbers, &sum);
+
+                async move {
+                    for n in numbers {
+                        s.spawn(async move {
+                            crate::task::yield_now().await;
+                            sum.fetch_add(*n, Ordering::SeqCst);
+                        });
+                    }
+                    numbers.len()
+                }
+            })
+            .await;
+
+            tx.send((len, sum.into_inner())).unwrap();
+        });
+
+        assert_eq!(rx.recv().unwrap(), (10, 55));
+        exec.shutdown();
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn child_panics_propagate() {
+        use crate::Executor;
+        use crate::runtime::executor::RtState;
+
+        let mut exec = Executor::new(1);
+        let finished = std::sync::Arc::new(AtomicBool::new(false));
+        let flag = std::sync::Arc::clone(&finished);
+
+        let res = exec.block_on(async move {
+            scope(|s| async move {
+                s.spawn(async { panic!("child") });
+                s.spawn(async move {
+                    crate::task::yield_now().await;
+                    flag.store(true, Ordering::SeqCst);
+                });
+            })
+            .await;
+        });
+
+        // The other child still completed.
+        assert!(matches!(res, Err(RtState::MainTaskPanicked)));
+        assert!(finished.load(Ordering::SeqCst));
+        exec.shutdown();
+    }
+}
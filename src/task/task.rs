@@ -1,19 +1,36 @@
+use super::error::JoinError;
 use super::handle::TaskHandle;
+use super::id::Id;
 use super::note::Note;
 use super::vtable::{Vtable, vtable};
 use super::waker;
-
-use log::warn;
+use crate::runtime::ExecutorHandle;
+use crate::trace::trace_event;
 
 use std::cell::UnsafeCell;
 use std::future::Future;
+use std::panic::Location;
 use std::ptr::NonNull;
 use std::sync::Mutex;
-use std::sync::atomic::AtomicU8;
+use std::sync::Weak;
+use std::sync::atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
-use std::task::{Poll, Waker};
-
-static REF_COUNT_BASE: u8 = 1;
+use std::task::Waker;
+
+static REF_COUNT_BASE: usize = 1;
+
+// Bits of the task's state.
+// A worker is polling the future.
+pub(crate) const RUNNING: u8 = 1;
+// Woken while running, it has to be polled again.
+pub(crate) const NOTIFIED: u8 = 1 << 1;
+// Cancelled, it completes without polling the future again.
+// Kept once complete only if it ended cancelled.
+pub(crate) const CANCELLED: u8 = 1 << 2;
+// The future was dropped and the output stored.
+pub(crate) const COMPLETE: u8 = 1 << 3;
+// A note was sent, a worker is about to poll it.
+pub(crate) const SCHEDULED: u8 = 1 << 4;
 
 /// Owned task struct.
 pub struct Task {
@@ -24,9 +41,10 @@ impl Task {
     pub(crate) fn new<F: Future + Send + 'static>(
         f: F,
         id: u64,
-        sender: Sender<Note>,
+        scheduler: Scheduler,
+        meta: Meta,
     ) -> (Task, Note, TaskHandle<F::Output>) {
-        let raw = RawTask::new(f, sender, id);
+        let raw = RawTask::new(f, scheduler, id, meta);
         let waker = waker::make_waker(raw.ptr);
 
         raw.set_waker(Some(waker));
@@ -34,34 +52,40 @@ impl Task {
         (Task { raw }, Note(id), TaskHandle::new(raw))
     }
 
-    pub(crate) fn new_alone<F: Future + Send + 'static>(
-        f: F,
-        id: u64,
-        sender: Sender<Note>,
-    ) -> Task {
-        let raw = RawTask::new(f, sender, id);
-        let waker = waker::make_waker(raw.ptr);
+    pub(crate) fn poll(&self) -> bool {
+        self.raw.poll()
+    }
 
-        raw.set_waker(Some(waker));
+    /// Drops the future in place, without polling it to completion.
+    ///
+    /// If it is being polled, the poller drops it once done.
+    /// Its handle then resolves to a cancellation error.
+    pub(crate) fn cancel(&self) {
+        self.raw.cancel()
+    }
 
-        Task { raw }
+    /// Obtains a new reference to the task, released with `ref_destroy`.
+    pub(crate) fn share(&self) -> RawTask {
+        self.raw.ref_inc();
+        self.raw
     }
 
-    pub(crate) fn poll(&self) -> bool {
-        self.raw.poll()
+    /// Obtains the task's header, to inspect it.
+    pub(crate) fn header(&self) -> &Header {
+        self.raw.header()
+    }
+
+    /// Whether `raw` points to this task.
+    pub(crate) fn is(&self, raw: RawTask) -> bool {
+        self.raw.get_ptr() == raw.get_ptr()
     }
 }
 
 impl std::ops::Drop for Task {
     fn drop(&mut self) {
-        // Wakes up the handle.
-        // This is here because the task is considered as finished
-        // when it is dropped in the Executor's loop
-        self.raw.wake_handle();
-
-        // The cell containing the waker is set to None,
-        // which drops the waker inside, which decreases our ref count.
-        self.raw.set_waker(None);
+        // Completed tasks are left alone, the others are cancelled.
+        // Completing drops the task's own waker, which decreases our ref count.
+        self.raw.cancel();
 
         // This decreases the ref count
         // and destroys the pointer if it is 0.
@@ -72,30 +96,97 @@ impl std::ops::Drop for Task {
 unsafe impl Send for Task {}
 unsafe impl Sync for Task {}
 
+// Where the notes of a task go once it is woken.
+pub(crate) enum Scheduler {
+    // Straight to the workers of a runtime, if it still exists.
+    Runtime(Weak<ExecutorHandle>),
+
+    // To a channel, for tasks polled outside of the workers.
+    Channel(Sender<Note>),
+}
+
+// Identity of a task, for introspection.
+pub(crate) struct Meta {
+    // Never reused, unlike the key.
+    pub(crate) id: Id,
+
+    pub(crate) name: Option<Box<str>>,
+
+    // Where it was spawned.
+    pub(crate) location: &'static Location<'static>,
+
+    // Entered around each poll, a child of the span current when spawned.
+    #[cfg(feature = "tracing")]
+    pub(crate) span: tracing::Span,
+}
+
+impl Meta {
+    #[track_caller]
+    pub(crate) fn new(name: Option<&str>) -> Meta {
+        let id = Id::next();
+        let location = Location::caller();
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!(
+            "task",
+            task.id = id.as_u64(),
+            task.name = name,
+            spawned.at = %location,
+        );
+        trace_event!(parent: &span, "task spawned");
+
+        Meta {
+            id,
+            name: name.map(Into::into),
+            location,
+            #[cfg(feature = "tracing")]
+            span,
+        }
+    }
+
+    // Runs `f` as the task, inside its span and a `poll` span below it.
+    pub(crate) fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "tracing")]
+        let _task = self.span.enter();
+        #[cfg(feature = "tracing")]
+        let _poll = tracing::trace_span!("poll").entered();
+
+        super::id::enter(self.id, f)
+    }
+}
+
 // Header, often used and updated data.
 pub(crate) struct Header {
-    // Id of the task.
+    // Key of the task in its runtime, reused once it completed.
     pub(crate) id: u64,
 
-    // Number of references.
-    pub(crate) refs: AtomicU8,
+    // Number of references, every waker clone holds one.
+    pub(crate) refs: AtomicUsize,
+
+    // Running, notified, cancelled, complete and scheduled bits.
+    pub(crate) state: AtomicU8,
+
+    // Amount of times the future was polled.
+    pub(crate) polls: AtomicU64,
+
+    pub(crate) meta: Meta,
 
     // Virtual function table.
     pub(crate) vtable: &'static Vtable,
 
-    // Channel to send Notifications.
-    pub(crate) sender: Sender<Note>,
+    // Where to send Notifications.
+    pub(crate) scheduler: Scheduler,
 }
 
 unsafe impl Send for Header {}
 
 // Middle, contains the future and it's output.
 pub(crate) struct Middle<F: Future + Send + 'static> {
-    // Stored Future
-    future: UnsafeCell<F>,
+    // Stored Future, None once it has been cancelled.
+    future: UnsafeCell<Option<F>>,
 
-    // Previous poll output.
-    pub(crate) poll: UnsafeCell<Poll<F::Output>>,
+    // Output, or why there is none, set once the task completed.
+    pub(crate) output: UnsafeCell<Option<Result<F::Output, JoinError>>>,
 }
 
 // Tail of the task, used for rarely updated data
@@ -115,6 +206,8 @@ impl Tail {
     }
 }
 
+// The header comes first, tasks are reached through pointers to it.
+#[repr(C)]
 pub(crate) struct Core<F: Future + Send + 'static> {
     head: Header,
     mid: Middle<F>,
@@ -122,17 +215,21 @@ pub(crate) struct Core<F: Future + Send + 'static> {
 }
 
 impl<F: Future + Send + 'static> Core<F> {
-    pub(crate) fn new(f: F, id: u64, sender: Sender<Note>) -> Core<F> {
+    pub(crate) fn new(f: F, id: u64, scheduler: Scheduler, meta: Meta) -> Core<F> {
         let head = Header {
             id,
-            refs: AtomicU8::new(REF_COUNT_BASE),
+            refs: AtomicUsize::new(REF_COUNT_BASE),
+            // Its first note is sent by whoever spawns it.
+            state: AtomicU8::new(SCHEDULED),
+            polls: AtomicU64::new(0),
+            meta,
             vtable: vtable::<F>(),
-            sender,
+            scheduler,
         };
 
         let mid = Middle {
-            future: UnsafeCell::new(f),
-            poll: UnsafeCell::new(Poll::Pending),
+            future: UnsafeCell::new(Some(f)),
+            output: UnsafeCell::new(None),
         };
 
         let tail = Tail {
@@ -158,8 +255,13 @@ impl<F: Future + Send + 'static> Core<F> {
     #[allow(clippy::mut_from_ref)]
     // This will be probably changed later.
     /// Obtains a mutable reference to the Future inside
-    pub(crate) fn future(&self) -> &mut F {
-        unsafe { &mut *self.mid.future.get() }
+    pub(crate) fn future(&self) -> Option<&mut F> {
+        unsafe { (*self.mid.future.get()).as_mut() }
+    }
+
+    /// Drops the Future inside where it is, as it is pinned.
+    pub(crate) fn drop_future(&self) {
+        unsafe { *self.mid.future.get() = None };
     }
 
     /// Obtains a reference or None to the waker.
@@ -175,13 +277,9 @@ impl<F: Future + Send + 'static> Core<F> {
         *self.tail().h_waker.lock().unwrap() = Some(waker.clone())
     }
 
-    /// Obtains the poll output.
-    pub(crate) fn poll_output(&self) -> Poll<F::Output> {
-        use std::mem;
-
-        let reference = unsafe { &mut *self.mid.poll.get() };
-
-        mem::replace(reference, Poll::Pending)
+    /// Takes the output, None if it was already taken.
+    pub(crate) fn take_output(&self) -> Option<Result<F::Output, JoinError>> {
+        unsafe { (*self.mid.output.get()).take() }
     }
 }
 
@@ -194,9 +292,14 @@ pub(crate) struct RawTask {
 
 impl RawTask {
     /// Creates a new raw task from a future
-    pub fn new<F: Future + Send + 'static>(f: F, sender: Sender<Note>, id: u64) -> RawTask {
-        let ptr =
-            unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(Core::new(f, id, sender)))) };
+    pub(crate) fn new<F: Future + Send + 'static>(
+        f: F,
+        scheduler: Scheduler,
+        id: u64,
+        meta: Meta,
+    ) -> RawTask {
+        let core = Box::new(Core::new(f, id, scheduler, meta));
+        let ptr = unsafe { NonNull::new_unchecked(Box::into_raw(core)) };
 
         RawTask {
             ptr: ptr.cast::<Header>(),
@@ -235,10 +338,27 @@ impl RawTask {
     }
 
     // Polls the future inside
-    fn poll(self) -> bool {
+    pub(crate) fn poll(self) -> bool {
         (self.vtable().poll)(self.ptr)
     }
 
+    // Drops the future inside
+    pub(crate) fn cancel(self) {
+        (self.vtable().cancel)(self.ptr)
+    }
+
+    /// Whether the task completed, its output is then ready to be taken.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.header().state.load(Ordering::Acquire) & COMPLETE != 0
+    }
+
+    /// Whether the task completed by being cancelled, rather than
+    /// with its future's output or panic.
+    pub(crate) fn was_cancelled(&self) -> bool {
+        let state = self.header().state.load(Ordering::Acquire);
+        state & (COMPLETE | CANCELLED) == COMPLETE | CANCELLED
+    }
+
     /// Reviews the saved poll
     ///
     /// Writes it to the pointer and attaches the waker.
@@ -246,6 +366,11 @@ impl RawTask {
         (self.vtable().review)(self.ptr, dst, waker)
     }
 
+    /// Sets the waker woken once the task completes, without reviewing it.
+    pub(crate) fn set_handle_waker(self, waker: &Waker) {
+        (self.vtable().set_handle_waker)(self.ptr, waker)
+    }
+
     /// Sends notification to the channel.
     pub(crate) fn send_note(self) {
         (self.vtable().send_note)(self.ptr)
@@ -256,11 +381,6 @@ impl RawTask {
         (self.vtable().set_waker)(self.ptr, waker);
     }
 
-    /// Wakes up the handle's waker (if there is one).
-    pub(crate) fn wake_handle(self) {
-        (self.vtable().wake_handle)(self.ptr)
-    }
-
     /// Deallocates the pointer used for handling the task.
     pub(crate) fn destroy(self) {
         (self.vtable().destroy)(self.ptr)
@@ -268,13 +388,15 @@ impl RawTask {
 
     /// Decreases the reference count by 1.
     /// Returns ref count after subtrackting.
-    pub(crate) fn ref_dec(self) -> u8 {
+    pub(crate) fn ref_dec(self) -> usize {
         (self.vtable().ref_dec)(self.ptr)
     }
 
     /// Increases the reference count by 1.
     /// Returns reference count after adding
-    pub(crate) fn ref_inc(self) -> u8 {
+    ///
+    /// Aborts the process past `isize::MAX` references, as `Arc` does.
+    pub(crate) fn ref_inc(self) -> usize {
         (self.vtable().ref_inc)(self.ptr)
     }
 }
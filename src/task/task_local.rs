@@ -0,0 +1,274 @@
+// Task-local storage.
+//
+// A task moves between workers, so its values can't live in thread-locals for good.
+// Instead they are swapped into one around each poll of the scoped future,
+// and back out once it returns.
+use pin_project_lite::pin_project;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread::LocalKey as ThreadLocalKey;
+
+/// Declares task-local keys, of type [`LocalKey`].
+///
+/// Values are set for the duration of a future with [`LocalKey::scope`].
+///
+/// # Examples
+///
+/// ```ignore
+/// lamp::task_local! {
+///     static REQUEST_ID: u64;
+///     pub static TENANT: String;
+/// }
+///
+/// REQUEST_ID
+///     .scope(42, async {
+///         handle().await;
+///         REQUEST_ID.with(|id| println!("done with request {id}"));
+///     })
+///     .await;
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = {
+            ::std::thread_local! {
+                static __KEY: ::std::cell::RefCell<::std::option::Option<$t>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+
+            $crate::task::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// Key for a task-local value, declared with [`task_local!`](crate::task_local).
+pub struct LocalKey<T: 'static> {
+    // Holds the value while the scoped future is polled.
+    #[doc(hidden)]
+    pub inner: ThreadLocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Sets the value to `value` for the duration of `f`.
+    ///
+    /// Nested scopes of the same key shadow it, the outer value is back once they end.
+    pub fn scope<F: Future>(&'static self, value: T, f: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            key: self,
+            slot: Some(value),
+            future: f,
+        }
+    }
+
+    /// Calls `f` with a reference to the current value.
+    ///
+    /// # Panics
+    ///
+    /// Panics outside of a [`scope`](LocalKey::scope) of this key.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        match self.try_with(f) {
+            Ok(res) => res,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Calls `f` with a reference to the current value, if there is one.
+    pub fn try_with<F, R>(&'static self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let res = self.inner.try_with(|cell| match cell.borrow().as_ref() {
+            Some(value) => Ok(f(value)),
+            None => Err(AccessError(())),
+        });
+
+        res.unwrap_or(Err(AccessError(())))
+    }
+
+    // Swaps the slot's value in for the duration of `f`.
+    fn scope_inner<R>(&'static self, slot: &mut Option<T>, f: impl FnOnce() -> R) -> R {
+        struct Guard<'a, T: 'static> {
+            key: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                // Swapped in successfully, it can be swapped back out.
+                self.key
+                    .inner
+                    .with(|cell| mem::swap(self.slot, &mut *cell.borrow_mut()));
+            }
+        }
+
+        let swapped = self.inner.try_with(|cell| match cell.try_borrow_mut() {
+            Ok(mut value) => {
+                mem::swap(slot, &mut *value);
+                true
+            }
+            Err(_) => false,
+        });
+
+        match swapped {
+            Ok(true) => (),
+            Ok(false) => panic!("cannot enter a task-local scope while it is borrowed by `with`"),
+            Err(_) => panic!("cannot enter a task-local scope while the thread is exiting"),
+        }
+
+        let _guard = Guard { key: self, slot };
+
+        f()
+    }
+}
+
+impl<T: 'static> Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+pin_project! {
+    /// Future returned by [`LocalKey::scope`].
+    pub struct TaskLocalFuture<T: 'static, F> {
+        key: &'static LocalKey<T>,
+
+        // The value, while the future isn't being polled.
+        slot: Option<T>,
+
+        #[pin]
+        future: F,
+    }
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = self.project();
+        let future = this.future;
+
+        this.key.scope_inner(this.slot, || future.poll(cx))
+    }
+}
+
+impl<T: 'static, F> Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskLocalFuture").finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`LocalKey::try_with`] outside of a scope of the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError(());
+
+impl Display for AccessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "task-local value not set")
+    }
+}
+
+impl Error for AccessError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::task::yield_now;
+
+    use futures::executor::block_on;
+
+    crate::task_local! {
+        static NUMBER: u32;
+        static NAME: String
+    }
+
+    #[test]
+    fn scope_sets_the_value() {
+        assert!(NUMBER.try_with(|_| ()).is_err());
+
+        let out = block_on(NUMBER.scope(1, async {
+            yield_now().await;
+            NUMBER.with(|n| *n + 1)
+        }));
+
+        assert_eq!(out, 2);
+        assert!(NUMBER.try_with(|_| ()).is_err());
+    }
+
+    #[test]
+    fn nested_scopes_shadow() {
+        block_on(NAME.scope("outer".into(), async {
+            NAME.scope("inner".into(), async {
+                yield_now().await;
+                NAME.with(|name| assert_eq!(name, "inner"));
+            })
+            .await;
+
+            NAME.with(|name| assert_eq!(name, "outer"));
+        }));
+    }
+
+    #[test]
+    fn value_is_swapped_out_between_polls() {
+        use std::pin::pin;
+        use std::task::{Context, Waker};
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut fut = pin!(NUMBER.scope(7, async {
+            yield_now().await;
+            NUMBER.with(|n| *n)
+        }));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        assert!(NUMBER.try_with(|_| ()).is_err());
+        assert!(fut.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn tasks_have_their_own_values() {
+        use crate::Executor;
+
+        let mut exec = Executor::new(1);
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let _ = exec.block_on(async move {
+            let a = Executor::spawn(NUMBER.scope(1, async {
+                yield_now().await;
+                NUMBER.with(|n| *n)
+            }));
+            let b = Executor::spawn(async {
+                yield_now().await;
+                NUMBER.try_with(|n| *n).ok()
+            });
+
+            tx.send((a.await, b.await)).unwrap();
+        });
+
+        assert_eq!(rx.recv().unwrap(), (1, None));
+        exec.shutdown();
+    }
+}
@@ -11,21 +11,23 @@ type Ptr = NonNull<Header>;
 
 pub(crate) struct Vtable {
     pub(crate) poll: fn(Ptr) -> bool,
+    pub(crate) cancel: fn(Ptr),
     pub(crate) review: fn(Ptr, *const (), &Waker),
-    pub(crate) wake_handle: fn(Ptr),
+    pub(crate) set_handle_waker: fn(Ptr, &Waker),
     pub(crate) send_note: fn(Ptr),
     pub(crate) set_waker: fn(Ptr, Option<Waker>),
-    pub(crate) ref_dec: fn(Ptr) -> u8,
-    pub(crate) ref_inc: fn(Ptr) -> u8,
+    pub(crate) ref_dec: fn(Ptr) -> usize,
+    pub(crate) ref_inc: fn(Ptr) -> usize,
     pub(crate) destroy: fn(Ptr),
 }
 
 pub(crate) fn vtable<F: Future + Send + 'static>() -> &'static Vtable {
     &Vtable {
         poll: poll::<F>,
+        cancel: cancel::<F>,
         destroy: destroy::<F>,
         review: review::<F>,
-        wake_handle: wake_handle::<F>,
+        set_handle_waker: set_handle_waker::<F>,
         send_note: send_note::<F>,
         set_waker: set_waker::<F>,
         ref_dec,
@@ -38,6 +40,11 @@ fn poll<F: Future + Send + 'static>(ptr: Ptr) -> bool {
     m.poll()
 }
 
+fn cancel<F: Future + Send + 'static>(ptr: Ptr) {
+    let m: Mantle<F> = Mantle::from_raw(ptr);
+    m.cancel();
+}
+
 fn destroy<F: Future + Send + 'static>(ptr: Ptr) {
     let m: Mantle<F> = Mantle::from_raw(ptr);
     m.destroy();
@@ -48,10 +55,9 @@ fn review<F: Future + Send + 'static>(ptr: Ptr, dst: *const (), waker: &Waker) {
     m.review(dst, waker);
 }
 
-fn wake_handle<F: Future + Send + 'static>(ptr: Ptr) {
+fn set_handle_waker<F: Future + Send + 'static>(ptr: Ptr, waker: &Waker) {
     let m: Mantle<F> = Mantle::from_raw(ptr);
-
-    m.wake_handle();
+    m.set_handle_waker(waker);
 }
 
 fn send_note<F: Future + Send + 'static>(ptr: Ptr) {
@@ -66,19 +72,26 @@ fn set_waker<F: Future + Send + 'static>(ptr: Ptr, waker: Option<Waker>) {
     m.set_waker(waker);
 }
 
-fn ref_dec(ptr: Ptr) -> u8 {
+fn ref_dec(ptr: Ptr) -> usize {
     let output = unsafe { (*ptr.as_ptr()).refs.fetch_sub(1, Ordering::SeqCst) };
 
-    let _id = unsafe { (*ptr.as_ptr()).id };
+    let id = unsafe { (*ptr.as_ptr()).id };
     let val = output - 1;
-    log::warn!("ref count decrement! value: {val} id: {_id}");
+    log::trace!("ref count decrement! value: {val} id: {id}");
     val
 }
-fn ref_inc(ptr: Ptr) -> u8 {
+
+fn ref_inc(ptr: Ptr) -> usize {
     let output = unsafe { (*ptr.as_ptr()).refs.fetch_add(1, Ordering::SeqCst) };
 
-    let _id = unsafe { (*ptr.as_ptr()).id };
+    // Leaked wakers could wrap the count around and free the task
+    // while still in use, unwinding could run their destructors.
+    if output > isize::MAX as usize {
+        std::process::abort();
+    }
+
+    let id = unsafe { (*ptr.as_ptr()).id };
     let val = output + 1;
-    log::warn!("ref count increment! value: {val} id: {_id}");
+    log::trace!("ref count increment! value: {val} id: {id}");
     val
 }
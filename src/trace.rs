@@ -0,0 +1,146 @@
+//! Instrumentation through `tracing`, compiled out without the `tracing` feature.
+
+/// Emits a trace level event, takes the arguments of [`tracing::trace!`].
+///
+/// Expands to nothing without the feature, so the arguments must not be
+/// the only use of a binding.
+macro_rules! trace_event {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($args)*);
+    };
+}
+
+pub(crate) use trace_event;
+
+#[cfg(test)]
+#[cfg(not(miri))]
+#[cfg(feature = "tracing")]
+mod tests {
+    use crate::Executor;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Instrument, Metadata, Subscriber};
+
+    use std::cell::RefCell;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    thread_local! {
+        // Spans entered on this thread, innermost last.
+        static STACK: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Entries along with the span they belong to.
+    type Log<T> = Arc<Mutex<Vec<(T, Option<u64>)>>>;
+
+    // Records the spans as `(name, parent)` and the events as `(message, span)`.
+    #[derive(Default)]
+    struct Recorder {
+        spans: Log<&'static str>,
+        events: Log<String>,
+    }
+
+    impl Recorder {
+        fn current() -> Option<u64> {
+            STACK.with(|stack| stack.borrow().last().copied())
+        }
+    }
+
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{value:?}");
+            }
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let parent = match span.parent() {
+                Some(parent) => Some(parent.into_u64()),
+                None if span.is_contextual() => Recorder::current(),
+                None => None,
+            };
+
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name(), parent));
+
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let span = match event.parent() {
+                Some(parent) => Some(parent.into_u64()),
+                None => Recorder::current(),
+            };
+
+            let mut message = Message(String::new());
+            event.record(&mut message);
+            self.events.lock().unwrap().push((message.0, span));
+        }
+
+        fn enter(&self, span: &Id) {
+            STACK.with(|stack| stack.borrow_mut().push(span.into_u64()));
+        }
+
+        fn exit(&self, _: &Id) {
+            STACK.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+
+    #[test]
+    fn tasks_are_traced() {
+        let recorder = Recorder::default();
+        let (spans, events) = (Arc::clone(&recorder.spans), Arc::clone(&recorder.events));
+
+        // With a single dispatcher, a callsite first reached by another
+        // test's thread would only ask that thread's, which is none.
+        let _other = tracing::Dispatch::new(tracing::subscriber::NoSubscriber::default());
+
+        let mut exec = Executor::new(1);
+        tracing::subscriber::with_default(recorder, || {
+            let outer = tracing::trace_span!("outer");
+            // The child runs on a worker, which doesn't see the subscriber.
+            let main = async {
+                drop(Executor::spawn(std::future::pending::<()>()));
+                tracing::trace!("inside");
+            };
+
+            assert!(exec.block_on(main.instrument(outer)).is_ok());
+        });
+        exec.shutdown();
+
+        let spans = spans.lock().unwrap();
+        let events = events.lock().unwrap();
+        let name = |id: Option<u64>| id.map(|id| spans[id as usize - 1].0);
+        let parent = |id: Option<u64>| id.and_then(|id| spans[id as usize - 1].1);
+
+        // The main task, then the child, spawned inside the future's span.
+        let tasks: Vec<_> = spans.iter().filter(|span| span.0 == "task").collect();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(name(tasks[1].1), Some("outer"));
+
+        // Events of the runtime are attached to the task, the future's ones
+        // to the span it entered.
+        let event = |msg: &str| events.iter().find(|e| e.0 == msg).unwrap().1;
+        assert_eq!(name(event("task spawned")), Some("task"));
+        assert_eq!(name(event("task completed")), Some("task"));
+        assert_eq!(name(event("inside")), Some("outer"));
+
+        // Polls are spans of their own, inside the task's.
+        let poll = spans.iter().position(|span| span.0 == "poll");
+        assert_eq!(name(parent(poll.map(|i| i as u64 + 1))), Some("task"));
+    }
+}
@@ -0,0 +1,157 @@
+use super::{complete, driver, entry_len};
+use crate::fs::asyncify;
+use crate::reactor::uring::Uring;
+use crate::runtime::Executor;
+
+use io_uring::{opcode, types};
+
+use std::fs;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Handle to an open file, doing positional reads and writes.
+///
+/// There is no cursor, every read and write takes the offset to operate on.
+pub struct File {
+    std: Arc<fs::File>,
+    uring: Option<Arc<Uring>>,
+}
+
+impl File {
+    /// Opens a file in read-only mode.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let std = asyncify(move || fs::File::open(path)).await?;
+
+        Ok(File::from_std(std))
+    }
+
+    /// Opens a file in write-only mode, creating or truncating it.
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let std = asyncify(move || fs::File::create(path)).await?;
+
+        Ok(File::from_std(std))
+    }
+
+    /// Wraps a `std::fs::File`.
+    pub fn from_std(std: fs::File) -> File {
+        File {
+            std: Arc::new(std),
+            uring: driver(),
+        }
+    }
+
+    /// Reads up to `buf.capacity()` bytes at `offset` into `buf`, replacing its contents.
+    ///
+    /// Resolves to the amount of bytes read and the buffer,
+    /// whose length is set to that amount. On error the buffer is empty.
+    pub async fn read_at(&self, mut buf: Vec<u8>, offset: u64) -> (io::Result<usize>, Vec<u8>) {
+        let uring = match &self.uring {
+            Some(uring) => uring,
+            None => return self.read_at_blocking(buf, offset).await,
+        };
+
+        let fd = types::Fd(self.std.as_raw_fd());
+        let entry = opcode::Read::new(fd, buf.as_mut_ptr(), entry_len(buf.capacity()))
+            .offset(offset)
+            .build();
+
+        // Safety: the buffer and the file are owned by the operation.
+        let (res, (_, mut buf)) =
+            unsafe { complete(uring, entry, (Arc::clone(&self.std), buf)) }.await;
+
+        match res {
+            Ok(n) => {
+                // Safety: the kernel initialized `n` bytes.
+                unsafe { buf.set_len(n as usize) };
+                (Ok(n as usize), buf)
+            }
+
+            Err(e) => {
+                buf.clear();
+                (Err(e), buf)
+            }
+        }
+    }
+
+    /// Writes `buf` at `offset`.
+    ///
+    /// Resolves to the amount of bytes written and the buffer.
+    pub async fn write_at(&self, buf: Vec<u8>, offset: u64) -> (io::Result<usize>, Vec<u8>) {
+        let uring = match &self.uring {
+            Some(uring) => uring,
+            None => return self.write_at_blocking(buf, offset).await,
+        };
+
+        let fd = types::Fd(self.std.as_raw_fd());
+        let entry = opcode::Write::new(fd, buf.as_ptr(), entry_len(buf.len()))
+            .offset(offset)
+            .build();
+
+        // Safety: the buffer and the file are owned by the operation.
+        let (res, (_, buf)) = unsafe { complete(uring, entry, (Arc::clone(&self.std), buf)) }.await;
+        (res.map(|n| n as usize), buf)
+    }
+
+    /// Flushes data and metadata to the disk.
+    pub async fn sync_all(&self) -> io::Result<()> {
+        self.sync(types::FsyncFlags::empty()).await
+    }
+
+    /// Flushes data to the disk, skipping metadata which isn't needed to read it back.
+    pub async fn sync_data(&self) -> io::Result<()> {
+        self.sync(types::FsyncFlags::DATASYNC).await
+    }
+
+    async fn sync(&self, flags: types::FsyncFlags) -> io::Result<()> {
+        let uring = match &self.uring {
+            Some(uring) => uring,
+            None => {
+                let std = Arc::clone(&self.std);
+                return match flags.contains(types::FsyncFlags::DATASYNC) {
+                    true => asyncify(move || std.sync_data()).await,
+                    false => asyncify(move || std.sync_all()).await,
+                };
+            }
+        };
+
+        let fd = types::Fd(self.std.as_raw_fd());
+        let entry = opcode::Fsync::new(fd).flags(flags).build();
+
+        // Safety: the file is owned by the operation.
+        let (res, _) = unsafe { complete(uring, entry, Arc::clone(&self.std)) }.await;
+        res.map(|_| ())
+    }
+
+    async fn read_at_blocking(
+        &self,
+        mut buf: Vec<u8>,
+        offset: u64,
+    ) -> (io::Result<usize>, Vec<u8>) {
+        let std = Arc::clone(&self.std);
+
+        Executor::get()
+            .spawn_blocking(move || {
+                let cap = buf.capacity();
+                buf.clear();
+                buf.resize(cap, 0);
+
+                let res = std.read_at(&mut buf, offset);
+                buf.truncate(*res.as_ref().unwrap_or(&0));
+                (res, buf)
+            })
+            .await
+    }
+
+    async fn write_at_blocking(&self, buf: Vec<u8>, offset: u64) -> (io::Result<usize>, Vec<u8>) {
+        let std = Arc::clone(&self.std);
+
+        Executor::get()
+            .spawn_blocking(move || (std.write_at(&buf, offset), buf))
+            .await
+    }
+}
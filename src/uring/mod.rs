@@ -0,0 +1,134 @@
+//! Completion based I/O through io_uring.
+//!
+//! Operations are submitted to the kernel and complete asynchronously,
+//! which, unlike readiness based I/O, works for regular files too.
+//! The kernel writes into and reads from the buffers while the operation runs,
+//! so they are owned by it: every method takes the buffer by value
+//! and hands it back along with the result.
+//!
+//! If the kernel rejects setting up the ring, for example because io_uring is disabled,
+//! the types fall back to the blocking pool for files and to epoll for sockets.
+//! `enabled` tells which path the current runtime uses.
+
+mod file;
+mod net;
+
+pub use file::File;
+pub use net::{TcpListener, TcpStream};
+
+use crate::reactor::uring::{Uring, cqe_result};
+use crate::runtime::Executor;
+
+use io_uring::squeue;
+
+use std::io;
+use std::sync::Arc;
+
+/// Returns whether the current runtime drives I/O through io_uring.
+///
+/// `false` means the ring could not be set up and the fallbacks are used.
+pub fn enabled() -> bool {
+    driver().is_some()
+}
+
+/// Returns the current runtime's ring, if there is one.
+fn driver() -> Option<Arc<Uring>> {
+    Executor::get().reactor_fn(|r| r.uring().cloned())
+}
+
+/// Submits `entry` and waits for it to complete.
+///
+/// Resolves to the result of the operation and `data`.
+///
+/// # Safety
+///
+/// Every pointer inside `entry` must point into `data` or otherwise stay valid
+/// until the operation completes.
+async unsafe fn complete<T: Send + 'static>(
+    uring: &Arc<Uring>,
+    entry: squeue::Entry,
+    data: T,
+) -> (io::Result<u32>, T) {
+    // Safety: upheld by the caller.
+    match unsafe { uring.submit_op(entry, data) } {
+        Ok(op) => {
+            let (res, data) = op.await;
+            (cqe_result(res), data)
+        }
+
+        Err((e, data)) => (Err(e), data),
+    }
+}
+
+/// Clamps a buffer length to what fits in a submission queue entry.
+fn entry_len(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+#[cfg(not(miri))]
+mod tests {
+    use super::*;
+    use crate::Executor;
+
+    #[test]
+    fn file_read_write_at() {
+        let path = std::env::temp_dir().join(format!("lamp-uring-{}", std::process::id()));
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async move {
+            let file = File::create(&path).await.unwrap();
+            let (res, _) = file.write_at(b"hello".to_vec(), 0).await;
+            assert_eq!(res.unwrap(), 5);
+            let (res, _) = file.write_at(b" world".to_vec(), 5).await;
+            assert_eq!(res.unwrap(), 6);
+            file.sync_all().await.unwrap();
+            drop(file);
+
+            let file = File::open(&path).await.unwrap();
+            let (res, buf) = file.read_at(Vec::with_capacity(5), 6).await;
+            assert_eq!(res.unwrap(), 5);
+            assert_eq!(buf, b"world");
+
+            let (res, buf) = file.read_at(buf, 11).await;
+            assert_eq!(res.unwrap(), 0);
+            assert!(buf.is_empty());
+
+            std::fs::remove_file(&path).unwrap();
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+
+    #[test]
+    fn tcp_echo() {
+        let mut exec = Executor::new(1);
+
+        let res = exec.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+
+            let server = Executor::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let (res, buf) = stream.read(Vec::with_capacity(16)).await;
+                res.unwrap();
+
+                let (res, _) = stream.write(buf).await;
+                res.unwrap()
+            });
+
+            let stream = TcpStream::connect(&addr).await.unwrap();
+            let (res, _) = stream.write(b"echo".to_vec()).await;
+            assert_eq!(res.unwrap(), 4);
+
+            let (res, buf) = stream.read(Vec::with_capacity(16)).await;
+            assert_eq!(res.unwrap(), 4);
+            assert_eq!(buf, b"echo");
+            assert_eq!(server.await, 4);
+        });
+
+        exec.shutdown();
+        assert!(res.is_ok(), "runtime shutdown abruptly due to an error");
+    }
+}
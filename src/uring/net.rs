@@ -0,0 +1,294 @@
+use super::{complete, driver, entry_len};
+use crate::io::{self as lio, AsyncReadExt, AsyncWriteExt};
+use crate::reactor::uring::Uring;
+use crate::runtime::Executor;
+
+use io_uring::{opcode, types};
+
+use std::io;
+use std::mem;
+use std::net::{self, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::Arc;
+
+/// Socket address in the layout the kernel expects, with its length.
+type RawAddr = (libc::sockaddr_storage, libc::socklen_t);
+
+enum Listener {
+    Uring(Arc<net::TcpListener>, Arc<Uring>),
+    Epoll(lio::TcpListener),
+}
+
+/// TCP socket listening for connections.
+pub struct TcpListener {
+    inner: Listener,
+}
+
+impl TcpListener {
+    /// Binds a new listener to `addr`.
+    pub fn bind(addr: &str) -> io::Result<TcpListener> {
+        let listener = net::TcpListener::bind(parse_addr(addr)?)?;
+
+        let inner = match driver() {
+            Some(uring) => Listener::Uring(Arc::new(listener), uring),
+            None => Listener::Epoll(lio::TcpListener::from_std(listener)?),
+        };
+
+        Ok(TcpListener { inner })
+    }
+
+    /// Returns the address the listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &self.inner {
+            Listener::Uring(io, _) => io.local_addr(),
+            Listener::Epoll(io) => io.local_addr(),
+        }
+    }
+
+    /// Accepts a new connection.
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (io, uring) = match &self.inner {
+            Listener::Uring(io, uring) => (io, uring),
+            Listener::Epoll(io) => {
+                let (stream, addr) = io.accept().await?;
+                return Ok((
+                    TcpStream {
+                        inner: Stream::Epoll(stream),
+                    },
+                    addr,
+                ));
+            }
+        };
+
+        // Safety: all zeroes is a valid `sockaddr_storage`.
+        let storage = unsafe { mem::zeroed::<libc::sockaddr_storage>() };
+        let mut raw: Box<RawAddr> =
+            Box::new((storage, mem::size_of::<libc::sockaddr_storage>() as _));
+
+        let entry = opcode::Accept::new(
+            types::Fd(io.as_raw_fd()),
+            &mut raw.0 as *mut libc::sockaddr_storage as *mut libc::sockaddr,
+            &mut raw.1,
+        )
+        .flags(libc::SOCK_CLOEXEC)
+        .build();
+
+        // Safety: the address and the listener are owned by the operation.
+        let (res, (_, raw)) = unsafe { complete(uring, entry, (Arc::clone(io), raw)) }.await;
+
+        // Safety: the kernel returned a new socket, owned by no one else.
+        let stream = unsafe { net::TcpStream::from_raw_fd(res? as i32) };
+        let addr = from_raw(&raw.0)?;
+
+        let stream = TcpStream {
+            inner: Stream::Uring(Arc::new(stream), Arc::clone(uring)),
+        };
+
+        Ok((stream, addr))
+    }
+}
+
+enum Stream {
+    Uring(Arc<net::TcpStream>, Arc<Uring>),
+    Epoll(lio::TcpStream),
+}
+
+/// TCP socket connected to a peer.
+pub struct TcpStream {
+    inner: Stream,
+}
+
+impl TcpStream {
+    /// Connects to `addr`.
+    pub async fn connect(addr: &str) -> io::Result<TcpStream> {
+        let addr = parse_addr(addr)?;
+
+        let uring = match driver() {
+            Some(uring) => uring,
+            None => {
+                let std = Executor::get()
+                    .spawn_blocking(move || net::TcpStream::connect(addr))
+                    .await?;
+
+                return Ok(TcpStream {
+                    inner: Stream::Epoll(lio::TcpStream::from_std(std)?),
+                });
+            }
+        };
+
+        let domain = match addr {
+            SocketAddr::V4(_) => libc::AF_INET,
+            SocketAddr::V6(_) => libc::AF_INET6,
+        };
+
+        // Safety: plain syscall, the result is checked below.
+        let fd = unsafe { libc::socket(domain, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Safety: the descriptor was just created.
+        let io = Arc::new(net::TcpStream::from(unsafe { OwnedFd::from_raw_fd(fd) }));
+        let raw = Box::new(to_raw(&addr));
+
+        let entry = opcode::Connect::new(
+            types::Fd(io.as_raw_fd()),
+            &raw.0 as *const libc::sockaddr_storage as *const libc::sockaddr,
+            raw.1,
+        )
+        .build();
+
+        // Safety: the address and the socket are owned by the operation.
+        let (res, (io, _)) = unsafe { complete(&uring, entry, (io, raw)) }.await;
+        res?;
+
+        Ok(TcpStream {
+            inner: Stream::Uring(io, uring),
+        })
+    }
+
+    /// Reads up to `buf.capacity()` bytes into `buf`, replacing its contents.
+    ///
+    /// Resolves to the amount of bytes read and the buffer,
+    /// whose length is set to that amount. On error the buffer is empty.
+    pub async fn read(&self, mut buf: Vec<u8>) -> (io::Result<usize>, Vec<u8>) {
+        let (io, uring) = match &self.inner {
+            Stream::Uring(io, uring) => (io, uring),
+            Stream::Epoll(io) => {
+                let cap = buf.capacity();
+                buf.clear();
+                buf.resize(cap, 0);
+
+                let mut io = io;
+                let res = io.read(&mut buf).await;
+                buf.truncate(*res.as_ref().unwrap_or(&0));
+                return (res, buf);
+            }
+        };
+
+        let fd = types::Fd(io.as_raw_fd());
+        let entry = opcode::Recv::new(fd, buf.as_mut_ptr(), entry_len(buf.capacity())).build();
+
+        // Safety: the buffer and the socket are owned by the operation.
+        let (res, (_, mut buf)) = unsafe { complete(uring, entry, (Arc::clone(io), buf)) }.await;
+
+        match res {
+            Ok(n) => {
+                // Safety: the kernel initialized `n` bytes.
+                unsafe { buf.set_len(n as usize) };
+                (Ok(n as usize), buf)
+            }
+
+            Err(e) => {
+                buf.clear();
+                (Err(e), buf)
+            }
+        }
+    }
+
+    /// Writes `buf` into the socket.
+    ///
+    /// Resolves to the amount of bytes written and the buffer.
+    pub async fn write(&self, buf: Vec<u8>) -> (io::Result<usize>, Vec<u8>) {
+        let (io, uring) = match &self.inner {
+            Stream::Uring(io, uring) => (io, uring),
+            Stream::Epoll(io) => {
+                let mut io = io;
+                let res = io.write(&buf).await;
+                return (res, buf);
+            }
+        };
+
+        let fd = types::Fd(io.as_raw_fd());
+        let entry = opcode::Send::new(fd, buf.as_ptr(), entry_len(buf.len())).build();
+
+        // Safety: the buffer and the socket are owned by the operation.
+        let (res, (_, buf)) = unsafe { complete(uring, entry, (Arc::clone(io), buf)) }.await;
+        (res.map(|n| n as usize), buf)
+    }
+}
+
+fn parse_addr(addr: &str) -> io::Result<SocketAddr> {
+    match addr.parse() {
+        Ok(o) => Ok(o),
+        Err(_e) => Err(io::Error::new(io::ErrorKind::NotFound, "invalid address")),
+    }
+}
+
+fn to_raw(addr: &SocketAddr) -> RawAddr {
+    // Safety: all zeroes is a valid `sockaddr_storage`.
+    let mut storage = unsafe { mem::zeroed::<libc::sockaddr_storage>() };
+
+    let len = match addr {
+        SocketAddr::V4(v4) => {
+            let sin = libc::sockaddr_in {
+                sin_family: libc::AF_INET as libc::sa_family_t,
+                sin_port: v4.port().to_be(),
+                sin_addr: libc::in_addr {
+                    s_addr: u32::from_ne_bytes(v4.ip().octets()),
+                },
+                sin_zero: [0; 8],
+            };
+
+            // Safety: `sockaddr_storage` is big enough for any address.
+            unsafe {
+                (&mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr_in).write(sin)
+            };
+            mem::size_of::<libc::sockaddr_in>()
+        }
+
+        SocketAddr::V6(v6) => {
+            let sin6 = libc::sockaddr_in6 {
+                sin6_family: libc::AF_INET6 as libc::sa_family_t,
+                sin6_port: v6.port().to_be(),
+                sin6_flowinfo: v6.flowinfo(),
+                sin6_addr: libc::in6_addr {
+                    s6_addr: v6.ip().octets(),
+                },
+                sin6_scope_id: v6.scope_id(),
+            };
+
+            // Safety: `sockaddr_storage` is big enough for any address.
+            unsafe {
+                (&mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr_in6).write(sin6)
+            };
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+
+    (storage, len as libc::socklen_t)
+}
+
+fn from_raw(storage: &libc::sockaddr_storage) -> io::Result<SocketAddr> {
+    match storage.ss_family as libc::c_int {
+        libc::AF_INET => {
+            // Safety: the family says it holds a `sockaddr_in`.
+            let sin =
+                unsafe { &*(storage as *const libc::sockaddr_storage as *const libc::sockaddr_in) };
+            let ip = Ipv4Addr::from(sin.sin_addr.s_addr.to_ne_bytes());
+            Ok(SocketAddr::V4(SocketAddrV4::new(
+                ip,
+                u16::from_be(sin.sin_port),
+            )))
+        }
+
+        libc::AF_INET6 => {
+            // Safety: the family says it holds a `sockaddr_in6`.
+            let sin6 = unsafe {
+                &*(storage as *const libc::sockaddr_storage as *const libc::sockaddr_in6)
+            };
+            let ip = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+            Ok(SocketAddr::V6(SocketAddrV6::new(
+                ip,
+                u16::from_be(sin6.sin6_port),
+                sin6.sin6_flowinfo,
+                sin6.sin6_scope_id,
+            )))
+        }
+
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unsupported address family",
+        )),
+    }
+}